@@ -0,0 +1,396 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Ahead-of-time building of Marlin models from a build script.
+//!
+//! By default, a [`VerilatorRuntime`] invokes Verilator lazily the first time
+//! a model is created, so the first test to run pays for the whole build and
+//! Verilator errors only surface as test failures. Calling [`build`] from
+//! `build.rs` instead builds the libraries during `cargo build`:
+//!
+//! ```no_run
+//! // build.rs
+//! fn main() -> Result<(), snafu::Whatever> {
+//!     marlin_build::build(&marlin_build::BuildOptions {
+//!         source_files: vec!["src/main.sv".into()],
+//!         scan: vec!["src".into()],
+//!         ..Default::default()
+//!     })?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The libraries are placed in the directory given by the
+//! `MARLIN_BUILD_ARTIFACT_DIRECTORY` environment variable at compile time,
+//! which a runtime with [`VerilatorRuntimeOptions::prebuilt_only`] loads them
+//! from without ever invoking Verilator:
+//!
+//! ```ignore
+//! let runtime = VerilatorRuntime::new(
+//!     env!("MARLIN_BUILD_ARTIFACT_DIRECTORY").into(),
+//!     &["src/main.sv".as_ref()],
+//!     &[],
+//!     [],
+//!     VerilatorRuntimeOptions {
+//!         prebuilt_only: true,
+//!         ..Default::default()
+//!     },
+//! )?;
+//! ```
+//!
+//! The runtime must be created with the same source files and include
+//! directories as given to [`build`].
+
+use std::{env, fs, path::PathBuf};
+
+use camino::{Utf8Path, Utf8PathBuf};
+pub use marlin_verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntimeOptions,
+};
+use marlin_verilator::{ProjectConfig, VerilatorRuntime};
+use marlin_verilog_macro_builder::{
+    MacroArgs, format_define_text, format_parameter_override,
+    parse_verilog_ports,
+};
+use snafu::{ResultExt, Whatever, whatever};
+
+/// A model to build that is not declared with `#[verilog]`, e.g., one created
+/// with [`VerilatorRuntime::create_dyn_model`]. The fields are the arguments
+/// that will be passed to `create_dyn_model`.
+#[derive(Debug, Clone)]
+pub struct DynamicModel {
+    pub name: String,
+    pub source_path: String,
+    pub ports: Vec<(String, usize, usize, PortDirection)>,
+    pub config: VerilatedModelConfig,
+}
+
+/// What [`build`] should build.
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    /// The (System)Verilog source files, as given to [`VerilatorRuntime::new`].
+    pub source_files: Vec<Utf8PathBuf>,
+
+    /// The include directories, as given to [`VerilatorRuntime::new`].
+    pub include_directories: Vec<Utf8PathBuf>,
+
+    /// Rust source files, or directories searched recursively for them, whose
+    /// `#[verilog(...)]` declarations should be built with the project's
+    /// default [`VerilatedModelConfig`] (see [`ProjectConfig`]), as
+    /// [`VerilatorRuntime::create_model_simple`] would. Files with `#[spade]`
+    /// or `#[veryl]` declarations or DPI functions are rejected, since their
+    /// models cannot be built ahead of time; leave them out so their models
+    /// are built lazily.
+    pub scan: Vec<Utf8PathBuf>,
+
+    /// Additional models to build.
+    pub models: Vec<DynamicModel>,
+
+    /// Options for the runtime used to build the models.
+    /// [`VerilatorRuntimeOptions::prebuilt_only`] is ignored.
+    pub runtime_options: VerilatorRuntimeOptions,
+}
+
+fn collect_rust_files(
+    path: &Utf8Path,
+    rust_files: &mut Vec<Utf8PathBuf>,
+) -> Result<(), Whatever> {
+    if path.is_file() {
+        rust_files.push(path.to_path_buf());
+        return Ok(());
+    }
+    for entry in path
+        .read_dir_utf8()
+        .whatever_context(format!("Failed to read directory {path}"))?
+    {
+        let entry = entry
+            .whatever_context(format!("Failed to read entry in {path}"))?;
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            collect_rust_files(entry_path, rust_files)?;
+        } else if entry_path.extension() == Some("rs") {
+            rust_files.push(entry_path.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+fn attribute_name(attribute: &syn::Attribute) -> Option<&syn::Ident> {
+    attribute
+        .path()
+        .segments
+        .last()
+        .map(|segment| &segment.ident)
+}
+
+/// Collects the `#[verilog(...)]` declarations in `items`. Declarations this
+/// crate cannot build are rejected rather than skipped, so that they are not
+/// mistaken for prebuilt ones: `#[spade]` and `#[veryl]` models need their
+/// compilers, and DPI functions are linked into every library a runtime
+/// builds, which a build script cannot do.
+fn collect_verilog_declarations(
+    items: &[syn::Item],
+    declarations: &mut Vec<MacroArgs>,
+) -> syn::Result<()> {
+    for item in items {
+        match item {
+            syn::Item::Struct(item_struct) => {
+                for attribute in &item_struct.attrs {
+                    if !matches!(attribute.meta, syn::Meta::List(_)) {
+                        continue;
+                    }
+                    match attribute_name(attribute) {
+                        Some(name) if name == "verilog" => {
+                            declarations.push(attribute.parse_args()?);
+                        }
+                        Some(name) if name == "spade" || name == "veryl" => {
+                            return Err(syn::Error::new_spanned(
+                                attribute,
+                                format!(
+                                    "marlin-build cannot build #[{name}] models; leave this file out of `scan`"
+                                ),
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            syn::Item::Fn(item_fn) => {
+                if let Some(attribute) =
+                    item_fn.attrs.iter().find(|attribute| {
+                        attribute_name(attribute)
+                            .is_some_and(|name| name == "dpi")
+                    })
+                {
+                    return Err(syn::Error::new_spanned(
+                        attribute,
+                        "marlin-build cannot build models for runtimes with DPI functions; leave this file out of `scan`",
+                    ));
+                }
+            }
+            syn::Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    collect_verilog_declarations(items, declarations)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Computes the model `#[verilog(...)]` with `args` would create, mirroring
+/// what the macro and [`VerilatorRuntime::create_model_simple`] do.
+fn model_from_declaration(
+    manifest_directory: &Utf8Path,
+    default_config: &VerilatedModelConfig,
+    args: &MacroArgs,
+) -> Result<DynamicModel, Whatever> {
+    let manifest_directory = PathBuf::from(manifest_directory);
+    let source_path = manifest_directory.join(args.source_path.value());
+
+    let parameters = args
+        .parameters
+        .iter()
+        .map(|(name, value)| {
+            (name.to_string(), format_parameter_override(value))
+        })
+        .collect::<Vec<_>>();
+    let defines = args
+        .defines
+        .iter()
+        .map(|(name, value)| {
+            (name.to_string(), value.as_ref().map(format_define_text))
+        })
+        .collect::<Vec<_>>();
+    let include_directories = args
+        .include
+        .iter()
+        .map(|directory| manifest_directory.join(directory.value()))
+        .collect::<Vec<_>>();
+
+    let ports = match parse_verilog_ports(
+        &args.name,
+        &args.source_path,
+        &source_path,
+        &args.parameters,
+        &defines,
+        &include_directories,
+    ) {
+        Ok(ports) => ports,
+        Err(error) => {
+            whatever!(
+                "Failed to parse the ports of module {}: {}",
+                args.name.value(),
+                error
+            );
+        }
+    };
+
+    let include_directories = include_directories
+        .iter()
+        .map(|directory| directory.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let config = default_config
+        .with_settings(
+            &args.name.value(),
+            &parameters
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect::<Vec<_>>(),
+            &defines
+                .iter()
+                .map(|(name, text)| (name.as_str(), text.as_deref()))
+                .collect::<Vec<_>>(),
+            &include_directories
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+        )
+        .whatever_context(format!(
+            "Invalid configuration for model `{}`",
+            args.name.value()
+        ))?;
+
+    Ok(DynamicModel {
+        name: args.name.value(),
+        source_path: source_path.to_string_lossy().into_owned(),
+        ports,
+        config,
+    })
+}
+
+/// Builds the models described by `options` into `$OUT_DIR/marlin`, returning
+/// that directory. This function must be called from a build script: it
+/// prints `cargo:rerun-if-changed` lines for every input of the builds,
+/// forwards Verilator warnings as `cargo:warning` lines, and sets the
+/// `MARLIN_BUILD_ARTIFACT_DIRECTORY` environment variable for the crate.
+pub fn build(options: &BuildOptions) -> Result<Utf8PathBuf, Whatever> {
+    let out_directory = env::var("OUT_DIR")
+        .whatever_context("`OUT_DIR` is not set; call this from build.rs")?;
+    let manifest_directory = env::var("CARGO_MANIFEST_DIR").whatever_context(
+        "`CARGO_MANIFEST_DIR` is not set; call this from build.rs",
+    )?;
+    let manifest_directory = Utf8PathBuf::from(manifest_directory);
+    let artifact_directory = Utf8PathBuf::from(out_directory).join("marlin");
+
+    // the project configuration is also read when the models are created, so
+    // rebuild whenever it changes
+    let project_config = ProjectConfig::discover()
+        .whatever_context("Failed to read the project configuration")?;
+    if let Some(path) = &project_config.path {
+        println!("cargo:rerun-if-changed={path}");
+    }
+    for variable in [
+        "MARLIN_OPTIMIZATION",
+        "MARLIN_TRACE",
+        "MARLIN_IGNORED_WARNINGS",
+    ] {
+        println!("cargo:rerun-if-env-changed={variable}");
+    }
+    let default_config = project_config.model_config();
+
+    let mut models = vec![];
+
+    let mut rust_files = vec![];
+    for path in &options.scan {
+        collect_rust_files(&manifest_directory.join(path), &mut rust_files)?;
+    }
+    for rust_file in &rust_files {
+        println!("cargo:rerun-if-changed={rust_file}");
+        let source_code = fs::read_to_string(rust_file)
+            .whatever_context(format!("Failed to read {rust_file}"))?;
+        let file = syn::parse_file(&source_code)
+            .whatever_context(format!("Failed to parse {rust_file}"))?;
+        let mut declarations = vec![];
+        collect_verilog_declarations(&file.items, &mut declarations)
+            .whatever_context(format!(
+                "Failed to collect the #[verilog] declarations in {rust_file}"
+            ))?;
+        for declaration in declarations {
+            models.push(model_from_declaration(
+                &manifest_directory,
+                &default_config,
+                &declaration,
+            )?);
+        }
+    }
+    models.extend(options.models.iter().cloned());
+
+    for source_file in &options.source_files {
+        println!("cargo:rerun-if-changed={source_file}");
+    }
+    for include_directory in &options.include_directories {
+        println!("cargo:rerun-if-changed={include_directory}");
+    }
+
+    let runtime = VerilatorRuntime::new(
+        &artifact_directory,
+        &options
+            .source_files
+            .iter()
+            .map(|path| path.as_path())
+            .collect::<Vec<_>>(),
+        &options
+            .include_directories
+            .iter()
+            .map(|path| path.as_path())
+            .collect::<Vec<_>>(),
+        [],
+        VerilatorRuntimeOptions {
+            prebuilt_only: false,
+            ..options.runtime_options.clone()
+        },
+    )
+    .whatever_context("Failed to create the Verilator runtime")?;
+
+    for model in &models {
+        let ports = model
+            .ports
+            .iter()
+            .map(|(name, msb, lsb, direction)| {
+                (name.as_str(), *msb, *lsb, *direction)
+            })
+            .collect::<Vec<_>>();
+        let report = runtime
+            .build_dyn_model_library(
+                &model.name,
+                &model.source_path,
+                &ports,
+                &model.config,
+            )
+            .whatever_context(format!(
+                "Failed to build model `{}`",
+                model.name
+            ))?;
+        for diagnostic in &report.diagnostics {
+            let location = diagnostic
+                .location()
+                .map(|location| format!("{location}: "))
+                .unwrap_or_default();
+            let code = diagnostic
+                .code
+                .as_ref()
+                .map(|code| format!("[{code}]"))
+                .unwrap_or_default();
+            println!(
+                "cargo:warning={location}{}{code}: {}",
+                diagnostic.severity, diagnostic.message
+            );
+        }
+        for dependency in report.dependencies {
+            if !dependency.starts_with(&artifact_directory) {
+                println!("cargo:rerun-if-changed={dependency}");
+            }
+        }
+    }
+
+    println!(
+        "cargo:rustc-env=MARLIN_BUILD_ARTIFACT_DIRECTORY={artifact_directory}"
+    );
+
+    Ok(artifact_directory)
+}
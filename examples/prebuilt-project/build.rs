@@ -0,0 +1,43 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::env;
+
+use marlin_build::{BuildOptions, DynamicModel, PortDirection};
+
+#[snafu::report]
+fn main() -> Result<(), snafu::Whatever> {
+    // building the models invokes Verilator, which checking and linting the
+    // crate should not need
+    if env::var_os("CARGO_FEATURE_BUILD_MODELS").is_none() {
+        return Ok(());
+    }
+
+    marlin_build::build(&BuildOptions {
+        source_files: vec!["src/adder.sv".into()],
+        scan: vec!["src/lib.rs".into()],
+        models: vec![DynamicModel {
+            name: "adder".into(),
+            source_path: "src/adder.sv".into(),
+            ports: vec![
+                ("a".into(), 7, 0, PortDirection::Input),
+                ("b".into(), 7, 0, PortDirection::Input),
+                ("sum".into(), 7, 0, PortDirection::Output),
+            ],
+            config: Default::default(),
+        }],
+        ..Default::default()
+    })?;
+    Ok(())
+}
@@ -0,0 +1,7 @@
+module adder(
+    input[7:0] a,
+    input[7:0] b,
+    output[7:0] sum
+);
+    assign sum = a + b;
+endmodule
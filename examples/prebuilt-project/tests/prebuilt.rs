@@ -0,0 +1,88 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// the models are only built with the `build-models` feature
+#![cfg(feature = "build-models")]
+
+use example_prebuilt_project::Adder;
+use marlin::{
+    verilator::{
+        PortDirection, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+fn prebuilt_runtime() -> Result<VerilatorRuntime, VerilatorRuntimeError> {
+    VerilatorRuntime::new(
+        env!("MARLIN_BUILD_ARTIFACT_DIRECTORY").into(),
+        &["src/adder.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            prebuilt_only: true,
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )
+}
+
+#[test]
+#[snafu::report]
+fn loads_prebuilt_model() -> Result<(), VerilatorRuntimeError> {
+    let runtime = prebuilt_runtime()?;
+
+    let mut adder = runtime.create_model_simple::<Adder>()?;
+    adder.a = 3;
+    adder.b = 4;
+    adder.eval();
+    assert_eq!(adder.sum, 7);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn loads_prebuilt_dynamic_model() -> Result<(), VerilatorRuntimeError> {
+    let runtime = prebuilt_runtime()?;
+
+    let mut adder = runtime.create_dyn_model(
+        "adder",
+        "src/adder.sv",
+        &[
+            ("a", 7, 0, PortDirection::Input),
+            ("b", 7, 0, PortDirection::Input),
+            ("sum", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    adder.pin("a", 250u8).unwrap();
+    adder.pin("b", 10u8).unwrap();
+    adder.eval();
+    assert_eq!(adder.read("sum").unwrap(), 4u8.into());
+
+    Ok(())
+}
+
+#[test]
+fn refuses_to_build_missing_model() {
+    let runtime = prebuilt_runtime().unwrap();
+
+    let result = runtime.create_dyn_model(
+        "adder",
+        "src/adder.sv",
+        &[("sum", 7, 0, PortDirection::Output)],
+        VerilatedModelConfig::default(),
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,3 @@
+module broken(input logic a);
+  assign = a;
+endmodule
@@ -0,0 +1,8 @@
+module assertions(
+    input clk,
+    input[7:0] value
+);
+    always @(posedge clk) begin
+        assert (value < 10) else $error("value %0d is too large", value);
+    end
+endmodule
@@ -0,0 +1,16 @@
+module display(
+    input clk,
+    input say,
+    input warn,
+    input fail
+);
+    reg[7:0] count;
+    initial count = 0;
+
+    always @(posedge clk) begin
+        count <= count + 1;
+        if (say) $display("count = %0d", count);
+        if (warn) $warning("count is %0d", count);
+        if (fail) $error("failed at %0d", count);
+    end
+endmodule
@@ -0,0 +1,14 @@
+module finish(
+    input clk,
+    input stop,
+    input done,
+    output reg[7:0] count
+);
+    initial count = 0;
+
+    always @(posedge clk) begin
+        count <= count + 1;
+        if (done) $finish;
+        if (stop) $stop;
+    end
+endmodule
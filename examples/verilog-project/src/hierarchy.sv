@@ -0,0 +1,24 @@
+module hierarchy_core(
+    input clk,
+    output [7:0] value
+);
+    reg[7:0] count = 0;
+    assign value = count;
+
+    always @(posedge clk) begin
+        count <= count + 1;
+    end
+endmodule
+
+module hierarchy(
+    input clk,
+    output [7:0] out
+);
+    reg[7:0] regfile[0:3];
+
+    hierarchy_core core(.clk(clk), .value(out));
+
+    always @(posedge clk) begin
+        regfile[out[1:0]] <= out;
+    end
+endmodule
@@ -35,6 +35,58 @@ pub struct DpiMain;
 #[verilog(src = "src/more_dpi.sv", name = "dpi_main")]
 pub struct MoreDpiMain;
 
+#[verilog(src = "src/parameterized.sv", name = "parameterized")]
+pub struct Parameterized;
+
+#[verilog(
+    src = "src/parameterized.sv",
+    name = "parameterized",
+    parameters = [WIDTH = 48, OFFSET = 3]
+)]
+pub struct WideParameterized;
+
+#[verilog(src = "src/literal_defaults.sv", name = "literal_defaults")]
+pub struct LiteralDefaults;
+
+#[verilog(
+    src = "src/preprocessed.sv",
+    name = "preprocessed",
+    include = ["src/include"]
+)]
+pub struct Preprocessed;
+
+#[verilog(
+    src = "src/preprocessed.sv",
+    name = "preprocessed",
+    defines = [WITH_ENABLE, BUS_WIDTH = 40],
+    include = ["src/include"]
+)]
+pub struct PreprocessedWithEnable;
+
+#[verilog(src = "src/finish.sv", name = "finish")]
+pub struct Finish;
+
+#[verilog(src = "src/plusargs.sv", name = "plusargs")]
+pub struct Plusargs;
+
+#[verilog(src = "src/uninitialized.sv", name = "uninitialized")]
+pub struct Uninitialized;
+
+#[verilog(src = "src/display.sv", name = "display")]
+pub struct Display;
+
+#[verilog(src = "src/assertions.sv", name = "assertions")]
+pub struct Assertions;
+
+#[verilog(src = "src/timing.sv", name = "timing")]
+pub struct Timing;
+
+#[verilog(src = "src/hierarchy.sv", name = "hierarchy")]
+pub struct Hierarchy;
+
+#[verilog(src = "src/memories.sv", name = "memories")]
+pub struct Memories;
+
 pub mod enclosed {
     use marlin::verilog::prelude::*;
 
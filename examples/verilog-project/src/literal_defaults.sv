@@ -0,0 +1,11 @@
+module literal_defaults #(
+    parameter INIT = 8'hFF,
+    parameter NAME = "literal_defaults",
+    parameter SCALE = 1.5,
+    parameter WIDTH = 8
+) (
+    input[WIDTH - 1:0] value,
+    output[WIDTH - 1:0] value_xor_init
+);
+    assign value_xor_init = value ^ WIDTH'(INIT);
+endmodule
@@ -0,0 +1,9 @@
+module memories(
+    input[3:0] address,
+    output[31:0] instruction
+);
+    reg[31:0] rom[0:15];
+    reg[31:0] ram[0:15];
+
+    assign instruction = rom[address];
+endmodule
@@ -0,0 +1,9 @@
+module parameterized #(
+    parameter WIDTH = 8,
+    parameter OFFSET = 1
+) (
+    input[WIDTH - 1:0] value,
+    output[WIDTH - 1:0] value_plus_offset
+);
+    assign value_plus_offset = value + WIDTH'(OFFSET);
+endmodule
@@ -0,0 +1,9 @@
+module plusargs(
+    output reg verbose,
+    output reg[31:0] value
+);
+    initial begin
+        verbose = $test$plusargs("verbose") != 0;
+        if (!$value$plusargs("VALUE=%d", value)) value = 0;
+    end
+endmodule
@@ -0,0 +1,15 @@
+`include "widths.svh"
+
+module preprocessed(
+`ifdef WITH_ENABLE
+    input enable,
+`endif
+    input[`BUS_WIDTH - 1:0] value,
+    output[`BUS_WIDTH - 1:0] doubled
+);
+`ifdef WITH_ENABLE
+    assign doubled = enable ? value << 1 : value;
+`else
+    assign doubled = value << 1;
+`endif
+endmodule
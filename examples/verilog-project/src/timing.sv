@@ -0,0 +1,15 @@
+module timing(
+    input enable,
+    output reg clk,
+    output reg[7:0] ticks
+);
+    initial begin
+        clk = 0;
+        ticks = 0;
+        forever #5 clk = ~clk;
+    end
+
+    always @(posedge clk) begin
+        if (enable) ticks <= ticks + 1;
+    end
+endmodule
@@ -15,16 +15,17 @@
 use std::env;
 
 use marlin::{
-    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilator::{
+        VerilatorRuntime, VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
     verilog::prelude::*,
 };
-use snafu::Whatever;
 
 #[verilog(src = "src/main.sv", name = "main")]
 struct Main;
 
 #[snafu::report]
-fn main() -> Result<(), Whatever> {
+fn main() -> Result<(), VerilatorRuntimeError> {
     if env::var("RUST_LOG").is_ok() {
         env_logger::init();
     }
@@ -0,0 +1,6 @@
+module uninitialized(
+    output[31:0] state
+);
+    reg[31:0] register;
+    assign state = register;
+endmodule
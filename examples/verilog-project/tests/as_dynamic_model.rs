@@ -16,13 +16,13 @@ use std::env;
 
 use example_verilog_project::Main;
 use marlin::verilator::{
-    AsDynamicVerilatedModel, VerilatorRuntime, VerilatorRuntimeOptions,
+    AsDynamicVerilatedModel, VerilatorRuntime, VerilatorRuntimeError,
+    VerilatorRuntimeOptions,
 };
-use snafu::Whatever;
 
 #[test]
 #[snafu::report]
-fn main() -> Result<(), Whatever> {
+fn main() -> Result<(), VerilatorRuntimeError> {
     if env::var("RUST_LOG").is_ok() {
         env_logger::init();
     }
@@ -0,0 +1,110 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Assertions;
+use marlin::{
+    verilator::{
+        VerilatedModelConfig, VerilatorRuntime, VerilatorRuntimeError,
+        VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+fn runtime() -> Result<VerilatorRuntime, VerilatorRuntimeError> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/assertions.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            capture_output: true,
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )
+}
+
+fn tick(
+    assertions: &mut Assertions,
+    value: u8,
+) -> Result<(), VerilatorRuntimeError> {
+    assertions.value = value;
+    assertions.clk = 0;
+    assertions.try_eval()?;
+    assertions.clk = 1;
+    assertions.try_eval()?;
+    Ok(())
+}
+
+fn with_assertions() -> VerilatedModelConfig {
+    VerilatedModelConfig {
+        assertions: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+#[snafu::report]
+fn failed_assertion_is_an_error() -> Result<(), VerilatorRuntimeError> {
+    let runtime = runtime()?;
+    let mut assertions =
+        runtime.create_model::<Assertions>(&with_assertions())?;
+
+    tick(&mut assertions, 3)?;
+    match tick(&mut assertions, 12) {
+        Err(VerilatorRuntimeError::AssertionFailed {
+            file,
+            line,
+            message,
+        }) => {
+            assert!(file.is_some_and(|file| file.ends_with("assertions.sv")));
+            assert_eq!(line, Some(6));
+            assert!(message.contains("value 12 is too large"));
+        }
+        result => panic!("Expected an assertion failure, got {result:?}"),
+    }
+
+    // the model stays failed until the failure is cleared
+    assert!(tick(&mut assertions, 3).is_err());
+    assertions.context().clear_finish();
+    tick(&mut assertions, 3)?;
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "value 12 is too large")]
+fn failed_assertion_panics_in_eval() {
+    let runtime = runtime().unwrap();
+    let mut assertions = runtime
+        .create_model::<Assertions>(&with_assertions())
+        .unwrap();
+
+    assertions.value = 12;
+    assertions.clk = 0;
+    assertions.eval();
+    assertions.clk = 1;
+    assertions.eval();
+}
+
+#[test]
+#[snafu::report]
+fn assertions_are_disabled_by_default() -> Result<(), VerilatorRuntimeError> {
+    let runtime = runtime()?;
+    let mut assertions = runtime.create_model_simple::<Assertions>()?;
+
+    tick(&mut assertions, 12)?;
+    assert!(assertions.context().status().is_running());
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{fs, time::Duration};
+
+use example_verilog_project::Main;
+use marlin::verilator::{
+    VerilatorRuntime, VerilatorRuntimeOptions,
+    cache::{self, PruneOptions},
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn list_and_prune() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts_cache".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let _main = runtime
+        .create_model_simple::<Main>()
+        .whatever_context("Failed to create model")?;
+
+    let libraries = runtime
+        .cached_libraries()
+        .whatever_context("Failed to list cached libraries")?;
+    assert_eq!(libraries.len(), 1);
+    let key = libraries[0].key.as_ref().expect("key should be recorded");
+    assert_eq!(key.module_name, "main");
+    assert!(key.source_path.ends_with("src/main.sv"));
+    assert!(libraries[0].size > 0);
+    assert!(libraries[0].last_used.is_some());
+
+    let everything = PruneOptions {
+        older_than: Some(Duration::ZERO),
+        unreferenced: true,
+    };
+
+    // the runtime keeps the libraries it loaded
+    assert!(
+        runtime
+            .prune_cache(&everything)
+            .whatever_context("Failed to prune cache")?
+            .is_empty()
+    );
+
+    let removed =
+        cache::prune_cached_libraries("artifacts_cache".into(), &everything)
+            .whatever_context("Failed to prune cache")?;
+    assert_eq!(removed.len(), 1);
+    assert!(
+        runtime
+            .cached_libraries()
+            .whatever_context("Failed to list cached libraries")?
+            .is_empty()
+    );
+    // the lockfile stays, since another process may be waiting on it
+    assert!(
+        fs::exists(format!("{}.lock", removed[0].directory))
+            .whatever_context("Failed to find lockfile")?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Finish;
+use marlin::{
+    verilator::{
+        VerilatedModelConfig, VerilatorRuntime, VerilatorRuntimeError,
+        VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+fn tick(finish: &mut Finish) {
+    finish.clk = 0;
+    finish.eval();
+    finish.context().time_inc(1);
+    finish.clk = 1;
+    finish.eval();
+    finish.context().time_inc(1);
+}
+
+#[test]
+#[snafu::report]
+fn save_and_restore() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finish.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let savable = VerilatedModelConfig {
+        savable: true,
+        ..Default::default()
+    };
+    let checkpoint = "artifacts/finish_checkpoint.bin";
+
+    let mut finish = runtime.create_model::<Finish>(&savable)?;
+    for _ in 0..5 {
+        tick(&mut finish);
+    }
+    finish.save(checkpoint)?;
+    for _ in 0..3 {
+        tick(&mut finish);
+    }
+    assert_eq!(finish.count, 8);
+    finish.clk = 0;
+    finish.eval();
+
+    // inputs are part of the checkpoint too
+    finish.restore(checkpoint)?;
+    assert_eq!(finish.count, 5);
+    assert_eq!(finish.clk, 1);
+    assert_eq!(finish.context().time(), 10);
+
+    // a fresh model can start from the checkpoint
+    let mut other = runtime.create_model::<Finish>(&savable)?;
+    other.restore(checkpoint)?;
+    assert_eq!(other.count, 5);
+    assert_eq!(other.clk, 1);
+    tick(&mut other);
+    assert_eq!(other.count, 6);
+
+    // but not one from a different build
+    let mut optimized =
+        runtime.create_model::<Finish>(&VerilatedModelConfig {
+            verilator_optimization: 1,
+            ..savable.clone()
+        })?;
+    assert!(matches!(
+        optimized.restore(checkpoint),
+        Err(VerilatorRuntimeError::CheckpointMismatch { .. })
+    ));
+
+    assert!(matches!(
+        other.restore("artifacts/no_such_checkpoint.bin"),
+        Err(VerilatorRuntimeError::Checkpoint { .. })
+    ));
+
+    let mut unsavable = runtime.create_model_simple::<Finish>()?;
+    assert!(matches!(
+        unsavable.save(checkpoint),
+        Err(VerilatorRuntimeError::NotSavable { .. })
+    ));
+
+    Ok(())
+}
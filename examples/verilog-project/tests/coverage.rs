@@ -0,0 +1,166 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use example_verilog_project::Finish;
+use marlin::{
+    verilator::{
+        CoverageKinds, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeOptions,
+        coverage::{self, CoverageKind, CoverageReport},
+    },
+    verilog::prelude::*,
+};
+use snafu::{ResultExt, Whatever};
+
+fn tick(finish: &mut Finish) {
+    finish.clk = 0;
+    finish.eval();
+    finish.clk = 1;
+    finish.eval();
+}
+
+#[test]
+#[snafu::report]
+fn parse_coverage_database() -> Result<(), Whatever> {
+    let report = CoverageReport::parse(
+        "# SystemC::Coverage-3\n\
+         C '\x01t\x02line\x01page\x02v_line/finish\x01f\x02src/finish.sv\x01l\x0210\x01n\x023\x01S\x0210-12\x01o\x02block\x01h\x02TOP.finish' 7\n\
+         C '\x01t\x02line\x01page\x02v_line/finish\x01f\x02src/finish.sv\x01l\x0211\x01n\x029\x01S\x0211\x01o\x02if\x01h\x02TOP.finish' 0\n",
+    ).whatever_context("Failed to parse coverage database")?;
+    assert_eq!(report.counts.len(), 2);
+    let (point, count) = report.counts.iter().next().unwrap();
+    assert_eq!(point.kind, CoverageKind::Line);
+    assert_eq!(point.lines, [10, 11, 12]);
+    assert_eq!(point.hierarchy, "TOP.finish");
+    assert_eq!(*count, 7);
+
+    let mut merged = report.clone();
+    merged.merge(&report);
+    assert_eq!(merged.counts.values().sum::<u64>(), 14);
+
+    // line 11 is in both points, so it takes the least count
+    let lcov = merged.to_lcov();
+    assert!(lcov.contains("SF:src/finish.sv\n"));
+    assert!(lcov.contains("DA:10,14\nDA:11,0\nDA:12,14\n"));
+    assert!(lcov.contains("LF:3\nLH:2\n"));
+
+    let cobertura = merged.to_cobertura();
+    assert!(cobertura.contains(r#"filename="src/finish.sv""#));
+    assert!(cobertura.contains(r#"<line number="11" hits="0"/>"#));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn collect_coverage() -> Result<(), Whatever> {
+    let covered = VerilatedModelConfig {
+        coverage: CoverageKinds {
+            line: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let coverage_directory = {
+        let runtime = VerilatorRuntime::new(
+            "artifacts".into(),
+            &["src/finish.sv".as_ref()],
+            &[],
+            [],
+            VerilatorRuntimeOptions::default_logging(),
+        )
+        .whatever_context("Failed to create runtime")?;
+
+        let mut finish = runtime
+            .create_model::<Finish>(&covered)
+            .whatever_context("Failed to create model")?;
+        for _ in 0..5 {
+            tick(&mut finish);
+        }
+        assert!(finish.context().collects_coverage());
+
+        let snapshot = "artifacts/finish_coverage.dat";
+        finish
+            .context()
+            .write_coverage(snapshot)
+            .whatever_context("Failed to write coverage database")?;
+        let report = CoverageReport::read(snapshot)
+            .whatever_context("Failed to read coverage database")?;
+        assert!(report.counts.iter().any(|(point, count)| {
+            point.kind == CoverageKind::Line
+                && point.file.ends_with("finish.sv")
+                && *count == 5
+        }));
+
+        let mut other = runtime
+            .create_model::<Finish>(&covered)
+            .whatever_context("Failed to create model")?;
+        tick(&mut other);
+
+        let mut uncovered = runtime
+            .create_model_simple::<Finish>()
+            .whatever_context("Failed to create model")?;
+        assert!(!uncovered.context().collects_coverage());
+        assert!(
+            uncovered
+                .context()
+                .write_coverage("artifacts/uncovered.dat")
+                .is_err()
+        );
+
+        runtime.coverage_directory().to_path_buf()
+    };
+
+    // both models wrote their coverage when the runtime was dropped, to a
+    // directory of its own, so earlier runs of this test are not counted
+    let databases = fs::read_dir(&coverage_directory)
+        .whatever_context("Failed to read coverage directory")?
+        .count();
+    assert_eq!(databases, 2);
+    let report = CoverageReport::read_directory(&coverage_directory)
+        .whatever_context("Failed to read coverage databases")?;
+    assert!(report.counts.iter().any(|(point, count)| {
+        point.kind == CoverageKind::Line
+            && point.file.ends_with("finish.sv")
+            && *count == 6
+    }));
+    assert!(report.to_lcov().contains("finish.sv"));
+
+    let other_runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finish.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+    assert_ne!(
+        other_runtime.coverage_directory(),
+        coverage_directory.as_path()
+    );
+
+    coverage::remove_databases(&coverage_directory)
+        .whatever_context("Failed to remove coverage databases")?;
+    assert!(
+        CoverageReport::read_directory(&coverage_directory)
+            .whatever_context("Failed to read coverage databases")?
+            .counts
+            .is_empty()
+    );
+
+    Ok(())
+}
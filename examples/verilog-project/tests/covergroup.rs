@@ -0,0 +1,209 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Finish;
+use marlin::{
+    verilator::{
+        PortDirection, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeOptions,
+        covergroup::{Bin, Covergroup, CovergroupError, SampleOn},
+        dynamic::DynamicVerilatedModelError,
+    },
+    verilog::prelude::*,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn sample_on_clock_edges() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finish.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let mut group = Covergroup::new::<Finish>("finish")
+        .sample_on(SampleOn::PosEdge("clk".into()));
+    group
+        .coverpoint(
+            "count",
+            [
+                Bin::value("zero", 0),
+                Bin::range("low", 1..=3),
+                Bin::range("high", 4..=255),
+                Bin::transition("one_to_two", [1, 2]),
+            ],
+        )
+        .whatever_context("Failed to add coverpoint")?;
+    group
+        .coverpoint("done", [])
+        .whatever_context("Failed to add coverpoint")?;
+    group
+        .cross("count_x_done", ["count", "done"])
+        .whatever_context("Failed to add cross")?;
+
+    let mut finish = runtime
+        .create_model_simple::<Finish>()
+        .whatever_context("Failed to create model")?;
+    for _ in 0..3 {
+        finish.clk = 0;
+        finish.eval();
+        group.sample(&finish).whatever_context("Failed to sample")?;
+        finish.clk = 1;
+        finish.eval();
+        group.sample(&finish).whatever_context("Failed to sample")?;
+    }
+
+    let report = group.report();
+    assert_eq!(report.samples, 3);
+    let count = &report.coverpoints[0];
+    assert_eq!(
+        count.bins.iter().map(|bin| bin.hits).collect::<Vec<_>>(),
+        [0, 3, 0, 1]
+    );
+    assert_eq!(
+        count
+            .holes()
+            .map(|bin| bin.name.as_str())
+            .collect::<Vec<_>>(),
+        ["zero", "high"]
+    );
+    let done = &report.coverpoints[1];
+    assert_eq!(done.bins.len(), 2);
+    assert_eq!(done.bins[0].hits, 3);
+    assert_eq!(done.coverage(), 50.0);
+    let cross = &report.crosses[0];
+    assert_eq!(cross.bins.len(), 8);
+    assert!(
+        cross
+            .bins
+            .iter()
+            .any(|bin| bin.name == "<low, 0>" && bin.hits == 3)
+    );
+    assert!(!report.is_complete());
+    assert!(report.to_string().contains("holes:"));
+
+    assert!(matches!(
+        group.coverpoint("no_such_port", []),
+        Err(CovergroupError::NoSuchPort { .. })
+    ));
+    assert!(matches!(
+        group.cross("bad", ["stop"]),
+        Err(CovergroupError::NoSuchCoverpoint { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn sample_after_every_eval() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finish.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let mut group = Covergroup::new::<Finish>("finish")
+        .sample_on(SampleOn::PosEdge("clk".into()));
+    group
+        .coverpoint("count", [Bin::range("low", 1..=3)])
+        .whatever_context("Failed to add coverpoint")?;
+
+    let mut finish = runtime
+        .create_model_simple::<Finish>()
+        .whatever_context("Failed to create model")?;
+    let group = finish.attach_covergroup(group);
+    for _ in 0..3 {
+        finish.clk = 0;
+        finish.eval();
+        finish.clk = 1;
+        let time = finish.context().time();
+        finish
+            .run_until(time)
+            .whatever_context("Failed to run model")?;
+    }
+
+    let report = group.report();
+    assert_eq!(report.samples, 3);
+    assert_eq!(report.coverpoints[0].bins[0].hits, 3);
+
+    group.clear();
+    assert_eq!(group.report().samples, 0);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn sample_dynamic_model() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finish.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let ports = [
+        ("clk", 0, 0, PortDirection::Input),
+        ("stop", 0, 0, PortDirection::Input),
+        ("done", 0, 0, PortDirection::Input),
+        ("count", 7, 0, PortDirection::Output),
+    ];
+    let mut group = Covergroup::with_ports("finish", &ports);
+    group
+        .coverpoint("clk", [Bin::transition("rise", [0, 1])])
+        .whatever_context("Failed to add coverpoint")?;
+    group
+        .coverpoint("count", [Bin::range("some", 1..=255)])
+        .whatever_context("Failed to add coverpoint")?;
+
+    let mut finish = runtime
+        .create_dyn_model(
+            "finish",
+            "src/finish.sv",
+            &ports,
+            VerilatedModelConfig::default(),
+        )
+        .whatever_context("Failed to create model")?;
+    for clk in [0u8, 1, 0, 1] {
+        finish
+            .pin("clk", clk)
+            .whatever_context("Failed to pin clk")?;
+        finish.eval();
+        group.sample(&finish).whatever_context("Failed to sample")?;
+    }
+
+    let report = group.report();
+    assert_eq!(report.samples, 4);
+    assert_eq!(report.coverpoints[0].bins[0].hits, 2);
+    assert_eq!(report.coverpoints[1].bins[0].hits, 3);
+    assert!(report.is_complete());
+
+    // covergroups sample inputs, but they still cannot be read
+    assert!(matches!(
+        finish.read("clk"),
+        Err(DynamicVerilatedModelError::InvalidPortDirection { .. })
+    ));
+
+    Ok(())
+}
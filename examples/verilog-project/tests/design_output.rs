@@ -0,0 +1,102 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Display;
+use marlin::{
+    verilator::{
+        DesignSeverity, VerilatorRuntime, VerilatorRuntimeError,
+        VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+fn runtime(
+    capture_output: bool,
+) -> Result<VerilatorRuntime, VerilatorRuntimeError> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/display.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            capture_output,
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )
+}
+
+fn tick(display: &mut Display) {
+    display.clk = 0;
+    display.eval();
+    display.clk = 1;
+    display.eval();
+}
+
+#[test]
+#[snafu::report]
+fn captured_output() -> Result<(), VerilatorRuntimeError> {
+    let runtime = runtime(true)?;
+    let mut display = runtime.create_model_simple::<Display>()?;
+
+    display.say = 1;
+    tick(&mut display);
+    tick(&mut display);
+    assert_eq!(display.take_output(), "count = 0\ncount = 1\n");
+    assert_eq!(display.take_output(), "");
+    assert!(display.take_messages().is_empty());
+
+    display.say = 0;
+    display.warn = 1;
+    tick(&mut display);
+    display.warn = 0;
+    display.fail = 1;
+    tick(&mut display);
+
+    let messages = display.take_messages();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].severity, DesignSeverity::Warning);
+    assert!(messages[0].message.ends_with("count is 2"));
+    assert_eq!(messages[1].severity, DesignSeverity::Error);
+    assert!(messages[1].message.ends_with("failed at 3"));
+    assert_eq!(messages[1].line, Some(14));
+    assert!(
+        messages[1]
+            .file
+            .as_ref()
+            .is_some_and(|file| file.ends_with("display.sv"))
+    );
+    assert!(display.take_output().contains("failed at 3"));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn uncaptured_output_is_printed() -> Result<(), VerilatorRuntimeError> {
+    let runtime = runtime(false)?;
+    let mut display = runtime.create_model_simple::<Display>()?;
+
+    display.say = 1;
+    display.warn = 1;
+    tick(&mut display);
+    assert_eq!(display.take_output(), "");
+    assert_eq!(display.take_messages().len(), 1);
+
+    // capturing can also be toggled per model
+    display.context().set_capture_output(true);
+    tick(&mut display);
+    assert!(display.take_output().starts_with("count = 1\n"));
+
+    Ok(())
+}
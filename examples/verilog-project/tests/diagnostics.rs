@@ -0,0 +1,56 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::diagnostics::{Severity, parse_diagnostics};
+
+#[test]
+fn parses_warnings_and_errors() {
+    let output = "\
+%Warning-WIDTH: src/main.sv:10:5: Operator ASSIGNW expects 8 bits on the Assign RHS, but Assign RHS's VARREF 'wide' generates 16 bits.
+                                : ... note: In instance 'main'
+   10 |     assign narrow = wide;
+      |                   ^
+                ... For warning description see https://verilator.org/warn/WIDTH?v=5.030
+%Error: src/main.sv:12: syntax error, unexpected '='
+%Error: Cannot find file containing module: 'missing'
+%Error: Exiting due to 2 error(s)
+";
+
+    let diagnostics = parse_diagnostics(output);
+    assert_eq!(diagnostics.len(), 3);
+
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].code.as_deref(), Some("WIDTH"));
+    assert_eq!(
+        diagnostics[0].location().as_deref(),
+        Some("src/main.sv:10:5")
+    );
+    assert!(diagnostics[0].message.starts_with("Operator ASSIGNW"));
+    assert_eq!(
+        diagnostics[0].to_string().lines().nth(1),
+        Some("  --> src/main.sv:10:5")
+    );
+
+    assert_eq!(diagnostics[1].severity, Severity::Error);
+    assert_eq!(diagnostics[1].code, None);
+    assert_eq!(diagnostics[1].line, Some(12));
+    assert_eq!(diagnostics[1].column, None);
+    assert_eq!(diagnostics[1].message, "syntax error, unexpected '='");
+
+    assert_eq!(diagnostics[2].file, None);
+    assert_eq!(
+        diagnostics[2].message,
+        "Cannot find file containing module: 'missing'"
+    );
+}
@@ -16,10 +16,11 @@ use std::env;
 
 use example_verilog_project::{DpiMain, MoreDpiMain};
 use marlin::{
-    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilator::{
+        VerilatorRuntime, VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
     verilog::prelude::*,
 };
-use snafu::Whatever;
 
 const SET_OUT_TO: i32 = 3;
 
@@ -30,7 +31,7 @@ pub extern "C" fn set_out(output: &mut i32) {
 
 #[test]
 #[snafu::report]
-fn main_tutorial() -> Result<(), Whatever> {
+fn main_tutorial() -> Result<(), VerilatorRuntimeError> {
     if env::var("RUST_LOG").is_ok() {
         env_logger::init();
     }
@@ -71,7 +72,7 @@ pub extern "C" fn set_bool_out(output: &mut bool) {
 
 #[test]
 #[snafu::report]
-fn other_test() -> Result<(), Whatever> {
+fn other_test() -> Result<(), VerilatorRuntimeError> {
     let runtime = VerilatorRuntime::new(
         "artifacts".into(),
         &["src/more_dpi.sv".as_ref()],
@@ -16,13 +16,12 @@ use std::env;
 
 use marlin::verilator::{
     AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
-    VerilatorRuntime, VerilatorRuntimeOptions,
+    VerilatorRuntime, VerilatorRuntimeError, VerilatorRuntimeOptions,
 };
-use snafu::Whatever;
 
 #[test]
 #[snafu::report]
-fn main() -> Result<(), Whatever> {
+fn main() -> Result<(), VerilatorRuntimeError> {
     if env::var("RUST_LOG").is_ok() {
         env_logger::init();
     }
@@ -0,0 +1,85 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Memories;
+use marlin::{
+    verilator::{
+        PublicSignals, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions, elf::MemoryRegion,
+    },
+    verilog::prelude::*,
+};
+
+fn runtime() -> Result<VerilatorRuntime, VerilatorRuntimeError> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/memories.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+}
+
+fn public_signals() -> VerilatedModelConfig {
+    VerilatedModelConfig {
+        public_signals: Some(PublicSignals::All),
+        ..Default::default()
+    }
+}
+
+#[test]
+#[snafu::report]
+fn load_program_into_memories() -> Result<(), VerilatorRuntimeError> {
+    let runtime = runtime()?;
+    let mut memories = runtime.create_model::<Memories>(&public_signals())?;
+
+    let image = memories.load_elf(
+        "tests/programs/program.elf",
+        &[
+            MemoryRegion::new("memories.rom", 0x0),
+            MemoryRegion::new("memories.ram", 0x100),
+        ],
+    )?;
+    assert_eq!(image.entry, 0x0);
+    assert_eq!(image.symbol("tohost"), Some(0x100));
+    assert_eq!(image.symbol("_end"), Some(0x104));
+
+    memories.address = 1;
+    memories.eval();
+    assert_eq!(memories.instruction, 0x00100093);
+
+    let ram = memories.read_memory("memories.ram")?;
+    assert_eq!(ram[0], 0x2a);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn segments_must_be_mapped() -> Result<(), VerilatorRuntimeError> {
+    let runtime = runtime()?;
+    let mut memories = runtime.create_model::<Memories>(&public_signals())?;
+
+    // the .data segment at 0x100 is not in any region
+    assert!(
+        memories
+            .load_elf(
+                "tests/programs/program.elf",
+                &[MemoryRegion::new("memories.rom", 0x0)],
+            )
+            .is_err()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeError, VerilatorRuntimeOptions, diagnostics::Severity,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+fn missing_source_file() {
+    let result = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/does_not_exist.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    );
+    assert!(matches!(
+        result,
+        Err(VerilatorRuntimeError::MissingSourceFile { .. })
+    ));
+}
+
+#[test]
+#[snafu::report]
+fn source_file_not_provided() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let result = runtime.create_dyn_model(
+        "parameterized",
+        "src/parameterized.sv",
+        &[],
+        VerilatedModelConfig::default(),
+    );
+    assert!(matches!(
+        result,
+        Err(VerilatorRuntimeError::SourceFileNotProvided { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn verilator_not_found() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts_errors".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            verilator_executable: "marlin-no-such-verilator".into(),
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let result = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[("medium_input", 31, 0, PortDirection::Input)],
+        VerilatedModelConfig::default(),
+    );
+    assert!(matches!(
+        result,
+        Err(VerilatorRuntimeError::VerilatorNotFound { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn verilator_version_failed() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts_errors".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            // `false --version` exits unsuccessfully
+            verilator_executable: "false".into(),
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let result = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[("medium_input", 31, 0, PortDirection::Input)],
+        VerilatedModelConfig::default(),
+    );
+    assert!(matches!(
+        result,
+        Err(VerilatorRuntimeError::VerilatorVersionFailed { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn verilator_failed() -> Result<(), Whatever> {
+    fs::create_dir_all("artifacts_errors")
+        .whatever_context("Failed to create directory")?;
+    fs::write(
+        "artifacts_errors/broken.sv",
+        "module broken(input logic a);\n  assign = a;\nendmodule\n",
+    )
+    .whatever_context("Failed to write broken module")?;
+
+    let runtime = VerilatorRuntime::new(
+        "artifacts_errors".into(),
+        &["artifacts_errors/broken.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let result = runtime.create_dyn_model(
+        "broken",
+        "artifacts_errors/broken.sv",
+        &[("a", 0, 0, PortDirection::Input)],
+        VerilatedModelConfig::default(),
+    );
+    match result {
+        Err(VerilatorRuntimeError::VerilatorFailed {
+            stderr,
+            diagnostics,
+            ..
+        }) => {
+            assert!(stderr.contains("broken.sv"));
+            assert!(diagnostics.iter().any(|diagnostic| {
+                diagnostic.severity == Severity::Error
+                    && diagnostic.line == Some(2)
+            }));
+        }
+        _ => panic!("Expected Verilator to fail on a syntax error"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Finish;
+use marlin::{
+    verilator::{
+        EvalStatus, PortDirection, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+fn tick(finish: &mut Finish) -> EvalStatus {
+    finish.clk = 0;
+    finish.eval();
+    finish.context().time_inc(1);
+    finish.clk = 1;
+    let status = finish.eval();
+    finish.context().time_inc(1);
+    status
+}
+
+#[test]
+#[snafu::report]
+fn fork_models() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finish.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let savable = VerilatedModelConfig {
+        savable: true,
+        ..Default::default()
+    };
+
+    let mut finish = runtime.create_model::<Finish>(&savable)?;
+    for _ in 0..4 {
+        tick(&mut finish);
+    }
+
+    let mut forks = (0..3)
+        .map(|_| runtime.fork(&finish))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, fork) in forks.iter_mut().enumerate() {
+        assert_eq!(fork.count, 4);
+        assert_eq!(fork.clk, 1);
+        assert_eq!(fork.context().time(), 8);
+        for _ in 0..i {
+            tick(fork);
+        }
+    }
+    assert_eq!(
+        forks.iter().map(|fork| fork.count).collect::<Vec<_>>(),
+        [4, 5, 6]
+    );
+    assert_eq!(finish.count, 4);
+
+    forks[0].done = 1;
+    assert_eq!(tick(&mut forks[0]), EvalStatus::Finished);
+    assert_eq!(tick(&mut finish), EvalStatus::Running);
+
+    let unsavable = runtime.create_model_simple::<Finish>()?;
+    assert!(matches!(
+        runtime.fork(&unsavable),
+        Err(VerilatorRuntimeError::NotSavable { .. })
+    ));
+
+    let mut dynamic = runtime.create_dyn_model(
+        "finish",
+        "src/finish.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        savable,
+    )?;
+    dynamic.pin("clk", 0u8).unwrap();
+    dynamic.eval();
+    dynamic.pin("clk", 1u8).unwrap();
+    dynamic.eval();
+    let dynamic_fork = runtime.fork_dyn_model(&dynamic)?;
+    assert_eq!(dynamic_fork.read("count").unwrap(), 1u8.into());
+
+    Ok(())
+}
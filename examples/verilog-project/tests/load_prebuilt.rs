@@ -0,0 +1,122 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use example_verilog_project::Main;
+use marlin::{
+    verilator::{
+        VerilatedModelConfig, VerilatorRuntime, VerilatorRuntimeError,
+        VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+use snafu::{ResultExt, Whatever};
+
+/// Builds the library for [`Main`] and copies it with its metadata to
+/// `directory`, like shipping it from a CI stage that has Verilator.
+fn ship_main_library(directory: &str) -> Result<String, Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts_prebuilt".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+    let _main = runtime
+        .create_model_simple::<Main>()
+        .whatever_context("Failed to create model")?;
+    let built = runtime
+        .cached_libraries()
+        .whatever_context("Failed to list cached libraries")?[0]
+        .directory
+        .join("obj_dir/libmarlin_Vmain.so");
+
+    fs::create_dir_all(directory)
+        .whatever_context("Failed to create shipping directory")?;
+    let shipped = format!("{directory}/libmarlin_Vmain.so");
+    fs::copy(&built, &shipped).whatever_context("Failed to copy library")?;
+    fs::copy(format!("{built}.metadata"), format!("{shipped}.metadata"))
+        .whatever_context("Failed to copy library metadata")?;
+    Ok(shipped)
+}
+
+/// A runtime that cannot build anything.
+fn runtime_without_verilator() -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        "artifacts_prebuilt_runner".into(),
+        &[],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            verilator_executable: "/nonexistent/verilator".into(),
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )
+    .whatever_context("Failed to create runtime")
+}
+
+#[test]
+#[snafu::report]
+fn loads_shipped_library() -> Result<(), Whatever> {
+    let library = ship_main_library("artifacts_prebuilt_shipped")?;
+
+    let mut runtime = runtime_without_verilator()?;
+    runtime
+        .load_prebuilt(&library, format!("{library}.metadata"))
+        .whatever_context("Failed to load prebuilt library")?;
+
+    let mut main = runtime
+        .create_model_simple::<Main>()
+        .whatever_context("Failed to create model")?;
+    main.medium_input = 7;
+    main.eval();
+    assert_eq!(main.medium_output, 7);
+
+    // built with a different configuration than the shipped library
+    assert!(matches!(
+        runtime.create_model::<Main>(&VerilatedModelConfig {
+            enable_tracing: true,
+            ..Default::default()
+        }),
+        Err(VerilatorRuntimeError::PrebuiltMismatch { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn rejects_library_from_other_version() -> Result<(), Whatever> {
+    let library = ship_main_library("artifacts_prebuilt_old")?;
+    let metadata = format!("{library}.metadata");
+    let contents = fs::read_to_string(&metadata)
+        .whatever_context("Failed to read library metadata")?;
+    let contents = contents.replacen(
+        &format!("marlin {}", env!("CARGO_PKG_VERSION")),
+        "marlin 0.0.1",
+        1,
+    );
+    fs::write(&metadata, contents)
+        .whatever_context("Failed to write library metadata")?;
+
+    let mut runtime = runtime_without_verilator()?;
+    assert!(matches!(
+        runtime.load_prebuilt(&library, &metadata),
+        Err(VerilatorRuntimeError::PrebuiltMismatch { .. })
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use example_verilog_project::Hierarchy;
+use marlin::{
+    verilator::{
+        PublicSignals, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+        dynamic::VerilatorValue,
+        memory::{MemoryFormat, MemoryLayout},
+    },
+    verilog::prelude::*,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn backdoor_memory_access() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/hierarchy.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+    let mut hierarchy = runtime
+        .create_model::<Hierarchy>(&VerilatedModelConfig {
+            public_signals: Some(PublicSignals::All),
+            ..Default::default()
+        })
+        .whatever_context("Failed to create model")?;
+
+    assert_eq!(
+        hierarchy
+            .context()
+            .memory_layout("hierarchy.regfile")
+            .whatever_context("Failed to look up memory layout")?,
+        MemoryLayout {
+            width: 8,
+            low: 0,
+            high: 3
+        }
+    );
+
+    hierarchy
+        .write_memory("hierarchy.regfile", 1, &[0xab, 0xcd])
+        .whatever_context("Failed to write memory")?;
+    assert_eq!(
+        hierarchy
+            .read_memory("hierarchy.regfile")
+            .whatever_context("Failed to read memory")?,
+        [0, 0xab, 0xcd, 0]
+    );
+    assert_eq!(
+        hierarchy
+            .peek("hierarchy.regfile[2]")
+            .whatever_context("Failed to peek memory word")?,
+        VerilatorValue::CData(0xcd)
+    );
+
+    fs::write(
+        "artifacts/regfile.hex",
+        "// comments are skipped\n01 02\n@3 ff\n",
+    )
+    .whatever_context("Failed to write memory file")?;
+    hierarchy
+        .load_memory(
+            "hierarchy.regfile",
+            "artifacts/regfile.hex",
+            MemoryFormat::Hex,
+        )
+        .whatever_context("Failed to load memory file")?;
+    assert_eq!(
+        hierarchy
+            .read_memory("hierarchy.regfile")
+            .whatever_context("Failed to read memory")?,
+        [0x01, 0x02, 0xcd, 0xff]
+    );
+
+    hierarchy
+        .dump_memory(
+            "hierarchy.regfile",
+            "artifacts/regfile.bin",
+            MemoryFormat::Raw,
+        )
+        .whatever_context("Failed to dump memory file")?;
+    assert_eq!(
+        fs::read("artifacts/regfile.bin")
+            .whatever_context("Failed to read memory file")?,
+        [0x01, 0x02, 0xcd, 0xff]
+    );
+
+    assert!(matches!(
+        hierarchy.write_memory("hierarchy.regfile", 3, &[1, 2]),
+        Err(VerilatorRuntimeError::MemoryOutOfBounds { .. })
+    ));
+    assert!(matches!(
+        hierarchy.read_memory("hierarchy.core.count"),
+        Err(VerilatorRuntimeError::NotAMemory { .. })
+    ));
+
+    Ok(())
+}
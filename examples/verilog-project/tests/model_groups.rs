@@ -0,0 +1,136 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::{
+    Main, Parameterized, WideMain, WideParameterized,
+};
+use marlin::verilator::{
+    AsDynamicVerilatedModel, ModelGroup, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeError, VerilatorRuntimeOptions, WideIn,
+};
+
+const WIDE_MAIN4_PORTS: &[(&str, usize, usize, PortDirection)] = &[
+    ("wide_input", 255, 128, PortDirection::Input),
+    ("wide_output", 255, 128, PortDirection::Output),
+];
+
+#[test]
+#[snafu::report]
+fn group_members_share_one_library() -> Result<(), VerilatorRuntimeError> {
+    let mut runtime = VerilatorRuntime::new(
+        "artifacts_groups".into(),
+        &[
+            "src/main.sv".as_ref(),
+            "src/wide_main.sv".as_ref(),
+            "src/parameterized.sv".as_ref(),
+        ],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    runtime.add_model_group(
+        ModelGroup::new("units")
+            .with_model::<Main>()
+            .with_model::<WideMain>()
+            .with_model::<WideParameterized>()
+            .with_dyn_model(
+                "wide_main4",
+                "src/wide_main.sv",
+                WIDE_MAIN4_PORTS,
+                &[],
+            ),
+    )?;
+
+    let mut main = runtime.create_model_simple::<Main>()?;
+    let mut wide_main = runtime.create_model_simple::<WideMain>()?;
+    let mut parameterized =
+        runtime.create_model_simple::<WideParameterized>()?;
+    let mut wide_main4 = runtime.create_dyn_model(
+        "wide_main4",
+        "src/wide_main.sv",
+        &WIDE_MAIN4_PORTS[1..],
+        VerilatedModelConfig::default(),
+    )?;
+
+    // the models are independent even though they come from one library
+    main.medium_input = 5;
+    main.eval();
+    assert_eq!(main.medium_output, 5);
+
+    wide_main.wide_input = WideIn::new([u32::MAX, u32::MAX, 1]);
+    wide_main.eval();
+    assert_eq!(wide_main.wide_output.value(), &[u32::MAX, u32::MAX, 1]);
+
+    parameterized.value = 1 << 40;
+    parameterized.eval();
+    assert_eq!(parameterized.value_plus_offset, (1 << 40) + 3);
+
+    wide_main4.eval();
+    assert_eq!(wide_main4.read("wide_output").unwrap(), [0; 4].into());
+
+    let libraries = runtime.cached_libraries()?;
+    assert_eq!(libraries.len(), 1);
+    let key = libraries[0].key.as_ref().expect("key should be recorded");
+    assert_eq!(key.module_name, "units");
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn group_members_are_created_as_they_were_added()
+-> Result<(), VerilatorRuntimeError> {
+    let mut runtime = VerilatorRuntime::new(
+        "artifacts_groups".into(),
+        &["src/wide_main.sv".as_ref(), "src/parameterized.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    runtime.add_model_group(
+        ModelGroup::new("units")
+            .with_model::<Parameterized>()
+            .with_dyn_model(
+                "wide_main4",
+                "src/wide_main.sv",
+                &WIDE_MAIN4_PORTS[..1],
+                &[],
+            ),
+    )?;
+
+    assert!(matches!(
+        runtime.add_model_group(
+            ModelGroup::new("more_units").with_model::<WideParameterized>()
+        ),
+        Err(VerilatorRuntimeError::InvalidConfiguration { .. })
+    ));
+    assert!(matches!(
+        runtime.create_dyn_model(
+            "wide_main4",
+            "src/wide_main.sv",
+            WIDE_MAIN4_PORTS,
+            VerilatedModelConfig::default(),
+        ),
+        Err(VerilatorRuntimeError::InvalidConfiguration { .. })
+    ));
+    assert!(matches!(
+        runtime.create_model::<Parameterized>(&VerilatedModelConfig {
+            parameters: vec![("OFFSET".into(), "2".into())],
+            ..Default::default()
+        }),
+        Err(VerilatorRuntimeError::InvalidConfiguration { .. })
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::{
+    LiteralDefaults, Parameterized, WideParameterized,
+};
+use marlin::{
+    verilator::{
+        PortDirection, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+#[test]
+#[snafu::report]
+fn overrides_are_separate_bindings() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/parameterized.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut narrow = runtime.create_model_simple::<Parameterized>()?;
+    let mut wide = runtime.create_model_simple::<WideParameterized>()?;
+
+    narrow.value = u8::MAX - 1;
+    narrow.eval();
+    assert_eq!(narrow.value_plus_offset, u8::MAX);
+
+    wide.value = 1 << 40;
+    wide.eval();
+    assert_eq!(wide.value_plus_offset, (1 << 40) + 3);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn dynamic_model_overrides() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/parameterized.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut main = runtime.create_dyn_model(
+        "parameterized",
+        "src/parameterized.sv",
+        &[
+            ("value", 15, 0, PortDirection::Input),
+            ("value_plus_offset", 15, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig {
+            parameters: vec![
+                ("WIDTH".into(), "16".into()),
+                ("OFFSET".into(), "2".into()),
+            ],
+            ..Default::default()
+        },
+    )?;
+
+    main.pin("value", 1000u16).unwrap();
+    main.eval();
+    assert_eq!(main.read("value_plus_offset").unwrap(), 1002u16.into());
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn non_numeric_defaults_are_skipped() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/literal_defaults.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    // the hexadecimal, string, and real defaults cannot be evaluated by the
+    // macro, which still computes the port widths from `WIDTH`
+    let mut main = runtime.create_model_simple::<LiteralDefaults>()?;
+    main.value = 0x0f;
+    main.eval();
+    assert_eq!(main.value_xor_init, 0xf0u8);
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Plusargs;
+use marlin::{
+    verilator::{
+        PortDirection, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+#[test]
+#[snafu::report]
+fn plusargs_per_model() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/plusargs.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            plusargs: vec!["+VALUE=1".into()],
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )?;
+
+    let mut plain = runtime.create_model_simple::<Plusargs>()?;
+    plain.eval();
+    assert_eq!(plain.verbose, 0);
+    assert_eq!(plain.value, 1);
+
+    let mut configured = runtime.create_model_with_plusargs::<Plusargs>(
+        &VerilatedModelConfig::default(),
+        &["+verbose", "+VALUE=42"],
+    )?;
+    configured.eval();
+    assert_eq!(configured.verbose, 1);
+    assert_eq!(configured.value, 42);
+
+    let mut dynamic = runtime.create_dyn_model_with_plusargs(
+        "plusargs",
+        "src/plusargs.sv",
+        &[
+            ("verbose", 0, 0, PortDirection::Output),
+            ("value", 31, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+        &["+VALUE=7"],
+    )?;
+    dynamic.eval();
+    assert_eq!(dynamic.read("verbose").unwrap(), 0u8.into());
+    assert_eq!(dynamic.read("value").unwrap(), 7u32.into());
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::{Preprocessed, PreprocessedWithEnable};
+use marlin::{
+    verilator::{
+        PortDirection, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+#[test]
+#[snafu::report]
+fn defines_and_includes_shape_bindings() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/preprocessed.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut plain = runtime.create_model_simple::<Preprocessed>()?;
+    let mut with_enable =
+        runtime.create_model_simple::<PreprocessedWithEnable>()?;
+
+    plain.value = 0x7ff;
+    plain.eval();
+    assert_eq!(plain.doubled, 0xffe);
+
+    with_enable.value = 1 << 32;
+    with_enable.enable = 0;
+    with_enable.eval();
+    assert_eq!(with_enable.doubled, 1 << 32);
+    with_enable.enable = 1;
+    with_enable.eval();
+    assert_eq!(with_enable.doubled, 1 << 33);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn dynamic_model_defines() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/preprocessed.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut main = runtime.create_dyn_model(
+        "preprocessed",
+        "src/preprocessed.sv",
+        &[
+            ("enable", 0, 0, PortDirection::Input),
+            ("value", 15, 0, PortDirection::Input),
+            ("doubled", 15, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig {
+            defines: vec![
+                ("WITH_ENABLE".into(), None),
+                ("BUS_WIDTH".into(), Some("16".into())),
+            ],
+            include_directories: vec!["src/include".into()],
+            ..Default::default()
+        },
+    )?;
+
+    main.pin("enable", 1u8).unwrap();
+    main.pin("value", 1000u16).unwrap();
+    main.eval();
+    assert_eq!(main.read("doubled").unwrap(), 2000u16.into());
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, env, fs};
+
+use example_verilog_project::Main;
+use marlin::{
+    verilator::{
+        ProjectConfig, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+use snafu::{ResultExt, Whatever};
+
+fn write_config(name: &str, contents: &str) -> Result<String, Whatever> {
+    fs::create_dir_all("artifacts_config")
+        .whatever_context("Failed to create configuration directory")?;
+    let path = format!("artifacts_config/{name}");
+    fs::write(&path, contents)
+        .whatever_context("Failed to write configuration")?;
+    Ok(path)
+}
+
+#[test]
+#[snafu::report]
+fn environment_overrides_project_config() -> Result<(), Whatever> {
+    let path = write_config(
+        "marlin.toml",
+        r#"
+artifact-directory = "artifacts"
+verilator = "/opt/verilator/bin/verilator"
+log = true
+optimization = 2
+tracing = false
+ignored-warnings = ["WIDTH"]
+"#,
+    )?;
+
+    let config = ProjectConfig::read(&path)
+        .whatever_context("Failed to read configuration")?;
+    assert_eq!(
+        config.artifact_directory.as_deref(),
+        Some("artifacts_config/artifacts".as_ref())
+    );
+    assert_eq!(
+        config.runtime_options(),
+        VerilatorRuntimeOptions {
+            verilator_executable: "/opt/verilator/bin/verilator".into(),
+            log: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        config.model_config(),
+        VerilatedModelConfig {
+            verilator_optimization: 2,
+            ignored_warnings: vec!["WIDTH".into()],
+            ..Default::default()
+        }
+    );
+
+    let environment = HashMap::from([
+        ("MARLIN_FORCE_REBUILD", "1"),
+        ("MARLIN_TRACE", "true"),
+        ("MARLIN_IGNORED_WARNINGS", "WIDTH, UNUSED"),
+    ]);
+    let config = config
+        .override_with(|variable| {
+            environment.get(variable).map(|value| value.to_string())
+        })
+        .whatever_context("Failed to apply environment overrides")?;
+    assert!(config.runtime_options().force_verilator_rebuild);
+    assert_eq!(
+        config.model_config(),
+        VerilatedModelConfig {
+            verilator_optimization: 2,
+            enable_tracing: true,
+            ignored_warnings: vec!["WIDTH".into(), "UNUSED".into()],
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn invalid_project_config() -> Result<(), Whatever> {
+    for (name, contents) in [
+        ("unknown_key.toml", "tracng = true"),
+        ("wrong_type.toml", "tracing = \"yes\""),
+        ("bad_optimization.toml", "optimization = 4"),
+    ] {
+        let path = write_config(name, contents)?;
+        assert!(
+            matches!(
+                ProjectConfig::read(&path),
+                Err(VerilatorRuntimeError::InvalidConfiguration { .. })
+            ),
+            "{contents} should be invalid"
+        );
+    }
+
+    let result = ProjectConfig::default().override_with(|variable| {
+        (variable == "MARLIN_TRACE").then(|| "yes".to_string())
+    });
+    assert!(matches!(
+        result,
+        Err(VerilatorRuntimeError::InvalidConfiguration { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn environment_configures_runtime_and_models() -> Result<(), Whatever> {
+    // SAFETY: no other test in this binary reads the environment
+    unsafe {
+        env::set_var("MARLIN_ARTIFACT_DIRECTORY", "artifacts_config/artifacts");
+        env::set_var("MARLIN_TRACE", "1");
+    }
+
+    let runtime = VerilatorRuntime::from_env(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+    )
+    .whatever_context("Failed to create runtime")?;
+    // the default coverage directory is in the artifact directory
+    assert!(
+        runtime
+            .coverage_directory()
+            .starts_with("artifacts_config/artifacts")
+    );
+
+    // only a model built with tracing writes a VCD
+    let vcd_path = "artifacts_config/environment_trace.vcd";
+    let _ = fs::remove_file(vcd_path);
+    let mut main = runtime
+        .create_model_simple::<Main>()
+        .whatever_context("Failed to create model")?;
+    let mut vcd = main.open_vcd(vcd_path);
+    main.eval();
+    vcd.dump(0);
+    vcd.close();
+    assert!(fs::exists(vcd_path).whatever_context("Failed to find VCD")?);
+
+    Ok(())
+}
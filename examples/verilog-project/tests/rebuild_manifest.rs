@@ -0,0 +1,75 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn manifest_tracks_included_headers() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts_manifest".into(),
+        &["src/preprocessed.sv".as_ref()],
+        &["src/include".as_ref()],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+    .whatever_context("Failed to create runtime")?;
+
+    let _model = runtime
+        .create_dyn_model(
+            "preprocessed",
+            "src/preprocessed.sv",
+            &[
+                ("value", 11, 0, PortDirection::Input),
+                ("doubled", 11, 0, PortDirection::Output),
+            ],
+            VerilatedModelConfig::default(),
+        )
+        .whatever_context("Failed to create model")?;
+
+    let header = fs::canonicalize("src/include/widths.svh")
+        .whatever_context("Failed to canonicalize header path")?;
+    let mut found_manifest = false;
+    for entry in fs::read_dir("artifacts_manifest")
+        .whatever_context("Failed to read artifacts directory")?
+    {
+        let entry = entry.whatever_context("Failed to read directory entry")?;
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("preprocessed_")
+        {
+            continue;
+        }
+        let manifest =
+            fs::read_to_string(entry.path().join("marlin-manifest.txt"))
+                .whatever_context("Failed to read build manifest")?;
+        assert!(manifest.starts_with("verilator "));
+        assert!(
+            manifest.lines().any(|line| line.starts_with("file ")
+                && line.ends_with(header.to_string_lossy().as_ref())),
+            "Manifest does not list included header:\n{manifest}"
+        );
+        found_manifest = true;
+    }
+    assert!(found_manifest);
+
+    Ok(())
+}
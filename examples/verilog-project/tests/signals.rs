@@ -0,0 +1,94 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Hierarchy;
+use marlin::{
+    verilator::{
+        PublicSignals, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+        dynamic::VerilatorValue,
+    },
+    verilog::prelude::*,
+};
+
+fn runtime() -> Result<VerilatorRuntime, VerilatorRuntimeError> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/hierarchy.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+}
+
+fn tick(hierarchy: &mut Hierarchy) {
+    hierarchy.clk = 0;
+    hierarchy.eval();
+    hierarchy.clk = 1;
+    hierarchy.eval();
+}
+
+#[test]
+#[snafu::report]
+fn peek_and_poke_internal_signals() -> Result<(), VerilatorRuntimeError> {
+    let runtime = runtime()?;
+    let mut hierarchy =
+        runtime.create_model::<Hierarchy>(&VerilatedModelConfig {
+            public_signals: Some(PublicSignals::All),
+            ..Default::default()
+        })?;
+
+    for _ in 0..3 {
+        tick(&mut hierarchy);
+    }
+    assert_eq!(
+        hierarchy.peek("hierarchy.core.count")?,
+        VerilatorValue::CData(3)
+    );
+    assert_eq!(
+        hierarchy.peek("hierarchy.regfile[1]")?,
+        VerilatorValue::CData(1)
+    );
+
+    hierarchy.poke("hierarchy.core.count", 100u8)?;
+    hierarchy.eval();
+    assert_eq!(hierarchy.out, 100);
+    tick(&mut hierarchy);
+    assert_eq!(hierarchy.out, 101);
+
+    assert!(matches!(
+        hierarchy.poke("hierarchy.core.count", 300u16),
+        Err(VerilatorRuntimeError::SignalValueTooWide { width: 8, .. })
+    ));
+    assert!(matches!(
+        hierarchy.peek("hierarchy.core.missing"),
+        Err(VerilatorRuntimeError::NoSuchSignal { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn signals_are_private_by_default() -> Result<(), VerilatorRuntimeError> {
+    let runtime = runtime()?;
+    let mut hierarchy = runtime.create_model_simple::<Hierarchy>()?;
+
+    assert!(matches!(
+        hierarchy.peek("hierarchy.core.count"),
+        Err(VerilatorRuntimeError::InvalidConfiguration { .. })
+    ));
+
+    Ok(())
+}
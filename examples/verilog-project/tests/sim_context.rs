@@ -0,0 +1,73 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Finish;
+use marlin::{
+    verilator::{
+        EvalStatus, VerilatorRuntime, VerilatorRuntimeError,
+        VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+fn tick(finish: &mut Finish) -> EvalStatus {
+    finish.clk = 0;
+    finish.eval();
+    finish.context().time_inc(1);
+    finish.clk = 1;
+    let status = finish.eval();
+    finish.context().time_inc(1);
+    status
+}
+
+#[test]
+#[snafu::report]
+fn time_and_finish() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finish.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut finish = runtime.create_model_simple::<Finish>()?;
+    let mut other = runtime.create_model_simple::<Finish>()?;
+
+    assert_eq!(finish.context().time(), 0);
+    for _ in 0..3 {
+        assert_eq!(tick(&mut finish), EvalStatus::Running);
+    }
+    assert_eq!(finish.count, 3);
+    assert_eq!(finish.context().time(), 6);
+
+    // each model has its own context
+    assert_eq!(other.context().time(), 0);
+    other.context().set_time(100);
+    assert_eq!(other.context().time(), 100);
+    assert_eq!(finish.context().time(), 6);
+
+    finish.done = 1;
+    assert_eq!(tick(&mut finish), EvalStatus::Finished);
+    assert!(finish.context().got_finish());
+    assert_eq!(other.context().status(), EvalStatus::Running);
+
+    finish.context().clear_finish();
+    finish.done = 0;
+    finish.stop = 1;
+    assert_eq!(tick(&mut finish), EvalStatus::Stopped);
+    assert_eq!(finish.context().status(), EvalStatus::Stopped);
+
+    Ok(())
+}
@@ -16,16 +16,17 @@ use std::env;
 
 use example_verilog_project::Main;
 use marlin::{
-    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilator::{
+        VerilatorRuntime, VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
     verilog::prelude::*,
 };
-use snafu::Whatever;
 
 macro_rules! test {
     ($name:ident) => {
         #[test]
         #[snafu::report]
-        fn $name() -> Result<(), Whatever> {
+        fn $name() -> Result<(), VerilatorRuntimeError> {
             if stringify!($name) == "verbose_test" {
                 if env::var("RUST_LOG").is_ok() {
                     env_logger::init();
@@ -0,0 +1,51 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Main;
+use marlin::{
+    verilator::{
+        ThreadsDpi, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+#[test]
+#[snafu::report]
+fn multithreaded_model() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut single = runtime.create_model_simple::<Main>()?;
+    let mut multi = runtime.create_model::<Main>(&VerilatedModelConfig {
+        threads: Some(2),
+        threads_dpi: Some(ThreadsDpi::None),
+        ..Default::default()
+    })?;
+
+    for value in [1, 0xdead_beef, u32::MAX] {
+        single.medium_input = value;
+        single.eval();
+        multi.medium_input = value;
+        multi.eval();
+        assert_eq!(single.medium_output, multi.medium_output);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::{Finish, Timing};
+use marlin::{
+    verilator::{
+        EvalStatus, VerilatedModelConfig, VerilatorRuntime,
+        VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
+    verilog::prelude::*,
+};
+
+#[test]
+#[snafu::report]
+fn run_clock_generator() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/timing.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut timing = runtime.create_model::<Timing>(&VerilatedModelConfig {
+        timing: true,
+        ..Default::default()
+    })?;
+
+    timing.enable = 1;
+    // the clock rises at 5, 15, ..., 95
+    assert_eq!(timing.run_until(100)?, EvalStatus::Running);
+    assert_eq!(timing.context().time(), 100);
+    assert_eq!(timing.ticks, 10);
+    assert_eq!(timing.clk, 0);
+
+    assert_eq!(timing.context().next_time_slot(), Some(105));
+    assert_eq!(timing.step_to_next_event()?, Some(105));
+    assert_eq!(timing.ticks, 11);
+
+    // inputs pinned between calls take effect from then on
+    timing.enable = 0;
+    timing.run_until(200)?;
+    assert_eq!(timing.ticks, 11);
+    assert_eq!(timing.context().time(), 200);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn no_events_without_timing() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finish.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut finish = runtime.create_model_simple::<Finish>()?;
+
+    assert!(!finish.context().events_pending());
+    assert_eq!(finish.step_to_next_event()?, None);
+    finish.run_until(50)?;
+    assert_eq!(finish.context().time(), 50);
+
+    Ok(())
+}
@@ -15,15 +15,15 @@
 use example_verilog_project::Main;
 use marlin::{
     verilator::{
-        VerilatedModelConfig, VerilatorRuntime, VerilatorRuntimeOptions,
+        VerilatedModelConfig, VerilatorRuntime, VerilatorRuntimeError,
+        VerilatorRuntimeOptions,
     },
     verilog::prelude::*,
 };
-use snafu::Whatever;
 
 #[test]
 #[snafu::report]
-fn forwards_correctly() -> Result<(), Whatever> {
+fn forwards_correctly() -> Result<(), VerilatorRuntimeError> {
     let runtime = VerilatorRuntime::new(
         "artifacts".into(),
         &["src/main.sv".as_ref()],
@@ -0,0 +1,78 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    VerilatorInfo, VerilatorRuntime, VerilatorRuntimeError,
+    VerilatorRuntimeOptions,
+    version::{MINIMUM_VERILATOR_VERSION, VerilatorVersion},
+};
+
+#[test]
+#[snafu::report]
+fn runtime_detects_verilator_once() -> Result<(), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts_verilator_info".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let verilator_info = runtime.verilator_info()?;
+    assert!(verilator_info.version_string.starts_with("Verilator "));
+    assert!(verilator_info.version >= MINIMUM_VERILATOR_VERSION);
+    assert!(std::ptr::eq(verilator_info, runtime.verilator_info()?));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn flags_depend_on_version() -> Result<(), VerilatorRuntimeError> {
+    let older = VerilatorInfo::from_version_string(
+        "Verilator 5.030 2024-10-27 rev v5.030\n",
+    )?;
+    assert_eq!(older.version, VerilatorVersion::new(5, 30));
+    assert_eq!(
+        older.version_string,
+        "Verilator 5.030 2024-10-27 rev v5.030"
+    );
+    assert_eq!(older.trace_flag(), "--trace");
+
+    let newer = VerilatorInfo::from_version_string(
+        "Verilator 5.036 2025-04-27 rev v5.036",
+    )?;
+    assert_eq!(newer.trace_flag(), "--trace-vcd");
+
+    Ok(())
+}
+
+#[test]
+fn rejects_unsupported_versions() {
+    for (version_string, version) in [
+        ("Verilator 4.228 2022-10-01 rev v4.228", "4.228"),
+        ("Icarus Verilog 12.0", "Icarus Verilog 12.0"),
+    ] {
+        let result = VerilatorInfo::from_version_string(version_string);
+        let Err(error @ VerilatorRuntimeError::UnsupportedVerilator { .. }) =
+            result
+        else {
+            panic!("{version_string} should be unsupported, not {result:?}");
+        };
+        assert_eq!(
+            error.to_string(),
+            format!("Unsupported Verilator version {version}, need >= 5.025")
+        );
+    }
+}
@@ -16,14 +16,15 @@ use std::env;
 
 use example_verilog_project::enclosed;
 use marlin::{
-    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilator::{
+        VerilatorRuntime, VerilatorRuntimeError, VerilatorRuntimeOptions,
+    },
     verilog::prelude::*,
 };
-use snafu::Whatever;
 
 #[test]
 #[snafu::report]
-fn main() -> Result<(), Whatever> {
+fn main() -> Result<(), VerilatorRuntimeError> {
     if env::var("RUST_LOG").is_ok() {
         env_logger::init();
     }
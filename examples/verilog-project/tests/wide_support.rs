@@ -15,13 +15,12 @@
 use example_verilog_project::WideMain;
 use marlin::verilator::{
     AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
-    VerilatorRuntime, VerilatorRuntimeOptions, WideIn,
+    VerilatorRuntime, VerilatorRuntimeError, VerilatorRuntimeOptions, WideIn,
 };
-use snafu::Whatever;
 
 #[test]
 #[snafu::report]
-fn all_wide_mains_forward_correctly() -> Result<(), Whatever> {
+fn all_wide_mains_forward_correctly() -> Result<(), VerilatorRuntimeError> {
     let runtime = VerilatorRuntime::new(
         "artifacts2".into(),
         &["src/wide_main.sv".as_ref()],
@@ -74,7 +73,8 @@ fn all_wide_mains_forward_correctly() -> Result<(), Whatever> {
 
 #[test]
 #[snafu::report]
-fn wide_main_forwards_correctly_dynamically() -> Result<(), Whatever> {
+fn wide_main_forwards_correctly_dynamically()
+-> Result<(), VerilatorRuntimeError> {
     let runtime = VerilatorRuntime::new(
         "artifacts2".into(),
         &["src/wide_main.sv".as_ref()],
@@ -109,7 +109,8 @@ fn wide_main_forwards_correctly_dynamically() -> Result<(), Whatever> {
 
 #[test]
 #[snafu::report]
-fn wide_main4_forwards_correctly_dynamically() -> Result<(), Whatever> {
+fn wide_main4_forwards_correctly_dynamically()
+-> Result<(), VerilatorRuntimeError> {
     let runtime = VerilatorRuntime::new(
         "artifacts2".into(),
         &["src/wide_main.sv".as_ref()],
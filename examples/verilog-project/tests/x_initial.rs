@@ -0,0 +1,73 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Uninitialized;
+use marlin::{
+    verilator::{
+        VerilatedModelConfig, VerilatorRuntime, VerilatorRuntimeError,
+        VerilatorRuntimeOptions, XInit,
+    },
+    verilog::prelude::*,
+};
+
+fn initial_state(
+    x_initial: XInit,
+    seed: Option<u32>,
+) -> Result<(u32, u32), VerilatorRuntimeError> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/uninitialized.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            seed,
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )?;
+
+    let mut model =
+        runtime.create_model::<Uninitialized>(&VerilatedModelConfig {
+            x_initial: Some(x_initial),
+            ..Default::default()
+        })?;
+    model.eval();
+    let seed = model.context().seed();
+    Ok((model.state, seed))
+}
+
+#[test]
+#[snafu::report]
+fn zero_initial_state() -> Result<(), VerilatorRuntimeError> {
+    assert_eq!(initial_state(XInit::Zero, None)?.0, 0);
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn unique_initial_state_is_reproducible() -> Result<(), VerilatorRuntimeError> {
+    let (first, first_seed) = initial_state(XInit::Unique, Some(1))?;
+    let (second, second_seed) = initial_state(XInit::Unique, Some(2))?;
+    assert_eq!(first_seed, 1);
+    assert_eq!(second_seed, 2);
+    assert_ne!(first, second);
+
+    assert_eq!(initial_state(XInit::Unique, Some(1))?.0, first);
+
+    // an unspecified seed can be recovered to reproduce the state
+    let (random, random_seed) = initial_state(XInit::Unique, None)?;
+    assert_ne!(random_seed, 0);
+    assert_eq!(initial_state(XInit::Unique, Some(random_seed))?.0, random);
+
+    Ok(())
+}
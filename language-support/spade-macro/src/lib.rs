@@ -26,6 +26,10 @@ fn search_for_swim_toml(mut start: Utf8PathBuf) -> Option<Utf8PathBuf> {
 pub fn spade(args: TokenStream, item: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as MacroArgs);
 
+    if let Err(error) = args.reject_verilog_only_arguments() {
+        return error.into_compile_error().into();
+    }
+
     let manifest_directory = Utf8PathBuf::from(
         env::var("CARGO_MANIFEST_DIR").expect("Please use CARGO"),
     );
@@ -187,6 +191,9 @@ pub fn spade(args: TokenStream, item: TokenStream) -> TokenStream {
         args.name,
         verilog_source_path,
         ports,
+        &[],
+        &[],
+        &[],
         item.into(),
     )
     .into()
@@ -221,7 +221,8 @@ impl SpadeRuntime {
                 &include_files,
                 [],
                 options.verilator_options,
-            )?,
+            )
+            .whatever_context("Failed to create the Verilator runtime")?,
         })
     }
 
@@ -230,7 +231,9 @@ impl SpadeRuntime {
     pub fn create_model_simple<'ctx, M: AsVerilatedModel<'ctx>>(
         &'ctx self,
     ) -> Result<M, Whatever> {
-        self.verilator_runtime.create_model_simple()
+        self.verilator_runtime
+            .create_model_simple()
+            .whatever_context("Failed to create the Spade unit")
     }
 
     /// Instantiates a new Spade unit. This function simply wraps
@@ -241,5 +244,6 @@ impl SpadeRuntime {
     ) -> Result<M, Whatever> {
         self.verilator_runtime
             .create_model(&config.verilator_config)
+            .whatever_context("Failed to create the Spade unit")
     }
 }
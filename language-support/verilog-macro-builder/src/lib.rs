@@ -4,7 +4,10 @@
 // v. 2.0. If a copy of the MPL was not distributed with this file, You can
 // obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use marlin_verilator::{
     PortDirection, compute_wdata_word_count_from_width_not_msb,
@@ -12,7 +15,7 @@ use marlin_verilator::{
 };
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use sv_parser::{self as sv, Locate, RefNode, unwrap_node};
+use sv_parser::{self as sv, RefNode, unwrap_node};
 
 mod util;
 
@@ -20,16 +23,87 @@ pub struct MacroArgs {
     pub source_path: syn::LitStr,
     pub name: syn::LitStr,
 
+    /// Overrides for the module's parameters, e.g., `parameters = [WIDTH =
+    /// 8]`. Only supported for Verilog.
+    pub parameters: Vec<(syn::Ident, syn::Lit)>,
+    /// Preprocessor defines, e.g., `defines = [SYNTHESIS, DEPTH = 4]`. Only
+    /// supported for Verilog.
+    pub defines: Vec<(syn::Ident, Option<syn::Lit>)>,
+    /// Include directories relative to the crate root, e.g., `include =
+    /// ["rtl/include"]`. Only supported for Verilog.
+    pub include: Vec<syn::LitStr>,
+
     /// Deprecated; does nothing.
     pub clock_port: Option<syn::LitStr>,
     /// Deprecated; does nothing.
     pub reset_port: Option<syn::LitStr>,
 }
 
+fn parse_parameter_override(
+    input: syn::parse::ParseStream,
+) -> syn::Result<(syn::Ident, syn::Lit)> {
+    let name = input.parse::<syn::Ident>()?;
+    input.parse::<syn::Token![=]>()?;
+    let value = input.parse::<syn::Lit>()?;
+    if !matches!(value, syn::Lit::Int(_) | syn::Lit::Str(_)) {
+        return Err(syn::Error::new_spanned(
+            value,
+            "Parameter overrides must be integer or string literals",
+        ));
+    }
+    Ok((name, value))
+}
+
+fn parse_define(
+    input: syn::parse::ParseStream,
+) -> syn::Result<(syn::Ident, Option<syn::Lit>)> {
+    let name = input.parse::<syn::Ident>()?;
+    if !input.peek(syn::Token![=]) {
+        return Ok((name, None));
+    }
+    input.parse::<syn::Token![=]>()?;
+    let value = input.parse::<syn::Lit>()?;
+    if !matches!(value, syn::Lit::Int(_) | syn::Lit::Str(_)) {
+        return Err(syn::Error::new_spanned(
+            value,
+            "Define values must be integer or string literals",
+        ));
+    }
+    Ok((name, Some(value)))
+}
+
+impl MacroArgs {
+    /// Errors if any arguments only supported for Verilog modules were given.
+    pub fn reject_verilog_only_arguments(&self) -> syn::Result<()> {
+        if let Some((name, _)) = self.parameters.first() {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Parameter overrides are only supported for Verilog modules",
+            ));
+        }
+        if let Some((name, _)) = self.defines.first() {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Defines are only supported for Verilog modules",
+            ));
+        }
+        if let Some(directory) = self.include.first() {
+            return Err(syn::Error::new_spanned(
+                directory,
+                "Include directories are only supported for Verilog modules",
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl syn::parse::Parse for MacroArgs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         syn::custom_keyword!(src);
         syn::custom_keyword!(name);
+        syn::custom_keyword!(parameters);
+        syn::custom_keyword!(defines);
+        syn::custom_keyword!(include);
 
         syn::custom_keyword!(clock);
         syn::custom_keyword!(reset);
@@ -43,13 +117,49 @@ impl syn::parse::Parse for MacroArgs {
         input.parse::<syn::Token![=]>()?;
         let name = input.parse::<syn::LitStr>()?;
 
+        let mut parameter_overrides = vec![];
+        let mut defines_list = vec![];
+        let mut include_directories = vec![];
         let mut clock_port = None;
         let mut reset_port = None;
         while input.peek(syn::Token![,]) {
             input.parse::<syn::Token![,]>()?;
 
             let lookahead = input.lookahead1();
-            if lookahead.peek(clock) {
+            if lookahead.peek(parameters) {
+                input.parse::<parameters>()?;
+                input.parse::<syn::Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                parameter_overrides.extend(
+                    syn::punctuated::Punctuated::<_, syn::Token![,]>::parse_terminated_with(
+                        &content,
+                        parse_parameter_override,
+                    )?,
+                );
+            } else if lookahead.peek(defines) {
+                input.parse::<defines>()?;
+                input.parse::<syn::Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                defines_list.extend(
+                    syn::punctuated::Punctuated::<_, syn::Token![,]>::parse_terminated_with(
+                        &content,
+                        parse_define,
+                    )?,
+                );
+            } else if lookahead.peek(include) {
+                input.parse::<include>()?;
+                input.parse::<syn::Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                include_directories.extend(syn::punctuated::Punctuated::<
+                    syn::LitStr,
+                    syn::Token![,],
+                >::parse_terminated(
+                    &content
+                )?);
+            } else if lookahead.peek(clock) {
                 input.parse::<clock>()?;
                 input.parse::<syn::Token![=]>()?;
                 clock_port = Some(input.parse::<syn::LitStr>()?);
@@ -65,17 +175,24 @@ impl syn::parse::Parse for MacroArgs {
         Ok(Self {
             source_path,
             name,
+            parameters: parameter_overrides,
+            defines: defines_list,
+            include: include_directories,
             clock_port,
             reset_port,
         })
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn build_verilated_struct(
     macro_name: &str,
     top_name: syn::LitStr,
     source_path: syn::LitStr,
     verilog_ports: Vec<(String, usize, usize, PortDirection)>,
+    parameters: &[(String, String)],
+    defines: &[(String, Option<String>)],
+    include_directories: &[String],
     item: TokenStream,
 ) -> TokenStream {
     let crate_name = format_ident!("{}", macro_name);
@@ -90,27 +207,31 @@ pub fn build_verilated_struct(
 
     let mut preeval_impl = vec![];
     let mut posteval_impl = vec![];
+    let mut read_ports_impl = vec![];
 
     let mut verilated_model_ports_impl = vec![];
     let mut verilated_model_init_impl = vec![];
     let mut verilated_model_init_self = vec![];
 
     let mut dynamic_read_arms = vec![];
+    let mut dynamic_input_read_arms = vec![];
     let mut dynamic_pin_arms = vec![];
 
     verilated_model_init_impl.push(quote! {
-        let new_model: extern "C" fn() -> *mut std::ffi::c_void =
-            *unsafe { library.get(concat!("ffi_new_V", #top_name).as_bytes()) }
-                .expect("failed to get symbol");
-        let model = (new_model)();
-
-        let eval_model: extern "C" fn(*mut std::ffi::c_void) =
+        let eval_model: extern "C" fn(*mut std::ffi::c_void) -> std::ffi::c_int =
             *unsafe { library.get(concat!("ffi_V", #top_name, "_eval").as_bytes()) }
                 .expect("failed to get symbol");
+
+        let context_api = #crate_name::__reexports::verilator::context::__private::ContextApi::load(library, #top_name)
+            .expect("failed to get context symbols");
+        let checkpoint_api = #crate_name::__reexports::verilator::checkpoint::__private::CheckpointApi::load(library, #top_name);
     });
     verilated_model_init_self.push(quote! {
         eval_model,
+        context_api,
+        checkpoint_api,
         model,
+        library,
         _marker: std::marker::PhantomData
     });
 
@@ -232,6 +353,39 @@ pub fn build_verilated_struct(
                 });
                 verilated_model_init_self.push(quote! { #setter });
 
+                // inputs are read back when the state of the model is
+                // replaced, e.g., by restoring a checkpoint
+                let reader = format_ident!("read_{}", port_name);
+                struct_members.push(quote! {
+                    #[doc(hidden)]
+                    #reader: extern "C" fn(*mut std::ffi::c_void) -> #verilator_interface_port_type
+                });
+                if port_width <= 64 {
+                    read_ports_impl.push(quote! {
+                        self.#port_name_ident = (self.#reader)(self.model);
+                    });
+                } else {
+                    read_ports_impl.push(quote! {
+                        self.#port_name_ident = #port_type_without_generics::from_ptr((self.#reader)(self.model));
+                    });
+                }
+                verilated_model_init_impl.push(quote! {
+                    let #reader: extern "C" fn(*mut std::ffi::c_void) -> #verilator_interface_port_type =
+                        *unsafe { library.get(concat!("ffi_V", #top_name, "_read_", #port_name).as_bytes()) }
+                            .expect("failed to get symbol");
+                });
+                verilated_model_init_self.push(quote! { #reader });
+
+                if port_width <= 64 {
+                    dynamic_input_read_arms.push(quote! {
+                        #port_name_literal => Ok(self.#port_name_ident.into())
+                    });
+                } else {
+                    dynamic_input_read_arms.push(quote! {
+                        #port_name_literal => Ok(self.#port_name_ident.value().into())
+                    });
+                }
+
                 if port_width <= 64 {
                     dynamic_pin_arms.push(quote! {
                         #port_name_literal => {
@@ -285,15 +439,17 @@ pub fn build_verilated_struct(
                     #[doc(hidden)]
                     #getter: extern "C" fn(*mut std::ffi::c_void) -> #verilator_interface_port_type
                 });
-                if port_width <= 64 {
-                    posteval_impl.push(quote! {
+                let read_output = if port_width <= 64 {
+                    quote! {
                         self.#port_name_ident = (self.#getter)(self.model);
-                    });
+                    }
                 } else {
-                    posteval_impl.push(quote! {
+                    quote! {
                         self.#port_name_ident = #port_type_without_generics::from_ptr((self.#getter)(self.model));
-                    });
-                }
+                    }
+                };
+                posteval_impl.push(read_output.clone());
+                read_ports_impl.push(read_output);
 
                 verilated_model_init_impl.push(quote! {
                     let #getter: extern "C" fn(*mut std::ffi::c_void) -> #verilator_interface_port_type =
@@ -326,7 +482,23 @@ pub fn build_verilated_struct(
 
     struct_members.push(quote! {
         #[doc(hidden)]
-        eval_model: extern "C" fn(*mut std::ffi::c_void)
+        eval_model: extern "C" fn(*mut std::ffi::c_void) -> std::ffi::c_int
+    });
+    struct_members.push(quote! {
+        #[doc(hidden)]
+        context_api: #crate_name::__reexports::verilator::context::__private::ContextApi
+    });
+    struct_members.push(quote! {
+        #[doc(hidden)]
+        checkpoint_api: Option<#crate_name::__reexports::verilator::checkpoint::__private::CheckpointApi>
+    });
+
+    let parameter_names = parameters.iter().map(|(name, _)| name);
+    let parameter_values = parameters.iter().map(|(_, value)| value);
+    let define_names = defines.iter().map(|(name, _)| name);
+    let define_values = defines.iter().map(|(_, value)| match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
     });
 
     let struct_name = item.ident;
@@ -338,11 +510,15 @@ pub fn build_verilated_struct(
             vcd_api: Option<#crate_name::__reexports::verilator::vcd::__private::VcdApi>,
             #[doc(hidden)]
             opened_vcd: bool,
+            #[doc(hidden)]
+            covergroups: Vec<#crate_name::__reexports::verilator::covergroup::AttachedCovergroup>,
             #(#struct_members),*,
             #[doc = "# Safety\nThe Rust binding to the model will not outlive the runtime this model was created from (with lifetime `'ctx`) and is dropped when the runtime is."]
             #[doc(hidden)]
             model: *mut std::ffi::c_void,
             #[doc(hidden)]
+            library: &'ctx #crate_name::__reexports::libloading::Library,
+            #[doc(hidden)]
             _marker: std::marker::PhantomData<&'ctx ()>,
             #[doc(hidden)]
             _unsend_unsync: std::marker::PhantomData<(std::cell::Cell<()>, std::sync::MutexGuard<'static, ()>)>
@@ -388,7 +564,19 @@ pub fn build_verilated_struct(
                 &PORTS
             }
 
-            fn init_from(library: &'ctx #crate_name::__reexports::libloading::Library, tracing_enabled: bool) -> Self {
+            fn parameters() -> &'static [(&'static str, &'static str)] {
+                &[#((#parameter_names, #parameter_values)),*]
+            }
+
+            fn defines() -> &'static [(&'static str, Option<&'static str>)] {
+                &[#((#define_names, #define_values)),*]
+            }
+
+            fn include_directories() -> &'static [&'static str] {
+                &[#(#include_directories),*]
+            }
+
+            fn init_from(library: &'ctx #crate_name::__reexports::libloading::Library, tracing_enabled: bool, model: *mut std::ffi::c_void) -> Self {
                 #(#verilated_model_init_impl)*
 
                 let vcd_api =
@@ -413,6 +601,7 @@ pub fn build_verilated_struct(
                 Self {
                     vcd_api,
                     opened_vcd: false,
+                    covergroups: vec![],
                     #(#verilated_model_init_self),*,
                     _unsend_unsync: std::marker::PhantomData
                 }
@@ -421,13 +610,53 @@ pub fn build_verilated_struct(
             unsafe fn model(&self) -> *mut std::ffi::c_void {
                 self.model
             }
+
+            fn library(&self) -> &'ctx #crate_name::__reexports::libloading::Library {
+                self.library
+            }
+
+            fn read_ports(&mut self) {
+                #(#read_ports_impl)*
+            }
         }
 
         impl<'ctx> #crate_name::__reexports::verilator::AsDynamicVerilatedModel<'ctx> for #struct_name<'ctx> {
-            fn eval(&mut self) {
+            fn try_eval(&mut self) -> Result<#crate_name::__reexports::verilator::EvalStatus, #crate_name::__reexports::verilator::VerilatorRuntimeError> {
                 #(#preeval_impl)*
-                (self.eval_model)(self.model);
+                let status = (self.eval_model)(self.model);
                 #(#posteval_impl)*
+                let status = #crate_name::__reexports::verilator::context::__private::after_eval(&self.context_api, self.model, status)?;
+                #crate_name::__reexports::verilator::covergroup::__private::sample_attached(&self.covergroups, self)?;
+                Ok(status)
+            }
+
+            fn attached_covergroups(&mut self) -> &mut Vec<#crate_name::__reexports::verilator::covergroup::AttachedCovergroup> {
+                &mut self.covergroups
+            }
+
+            fn context(&mut self) -> #crate_name::__reexports::verilator::SimContext<'_> {
+                #crate_name::__reexports::verilator::context::__private::new_sim_context(&self.context_api, self.model)
+            }
+
+            fn save(
+                &mut self,
+                path: impl std::convert::AsRef<std::path::Path>,
+            ) -> Result<(), #crate_name::__reexports::verilator::VerilatorRuntimeError> {
+                use #crate_name::__reexports::verilator::AsVerilatedModel;
+
+                #crate_name::__reexports::verilator::checkpoint::__private::save(self.checkpoint_api.as_ref(), self.model, Self::name(), path.as_ref())
+            }
+
+            fn restore(
+                &mut self,
+                path: impl std::convert::AsRef<std::path::Path>,
+            ) -> Result<(), #crate_name::__reexports::verilator::VerilatorRuntimeError> {
+                use #crate_name::__reexports::verilator::AsVerilatedModel;
+
+                #crate_name::__reexports::verilator::checkpoint::__private::restore(self.checkpoint_api.as_ref(), self.model, Self::name(), path.as_ref())?;
+                // the ports are part of the restored state
+                self.read_ports();
+                Ok(())
             }
 
             fn read(
@@ -448,6 +677,20 @@ pub fn build_verilated_struct(
                 }
             }
 
+            fn read_any_port(
+                &self,
+                port: impl Into<String>,
+            ) -> Result<#crate_name::__reexports::verilator::dynamic::VerilatorValue, #crate_name::__reexports::verilator::dynamic::DynamicVerilatedModelError> {
+                use #crate_name::__reexports::verilator::AsDynamicVerilatedModel;
+
+                let port = port.into();
+
+                match port.as_str() {
+                    #(#dynamic_input_read_arms,)*
+                    _ => self.read(port)
+                }
+            }
+
             fn pin(
                 &mut self,
                 port: impl Into<String>,
@@ -476,40 +719,96 @@ pub fn build_verilated_struct(
     }
 }
 
+/// The text of a parameter override as it should be passed to Verilator with
+/// `-G<name>=<value>`. Strings become SystemVerilog string literals, with
+/// quotes, backslashes, and control characters escaped.
+pub fn format_parameter_override(value: &syn::Lit) -> String {
+    match value {
+        syn::Lit::Str(string) => {
+            let mut literal = String::from('"');
+            for c in string.value().chars() {
+                match c {
+                    '"' => literal.push_str("\\\""),
+                    '\\' => literal.push_str("\\\\"),
+                    '\n' => literal.push_str("\\n"),
+                    '\t' => literal.push_str("\\t"),
+                    c if c.is_ascii_control() => {
+                        literal.push_str(&format!("\\{:03o}", c as u8));
+                    }
+                    c => literal.push(c),
+                }
+            }
+            literal.push('"');
+            literal
+        }
+        syn::Lit::Int(int) => int.base10_digits().to_string(),
+        _ => unreachable!(
+            "MacroArgs only accepts string and integer parameter overrides"
+        ),
+    }
+}
+
+/// The text a define expands to as it should be passed to Verilator with
+/// `-D<name>=<value>`. String literals are used verbatim so that arbitrary
+/// macro text (e.g., `"8'hff"`) can be given.
+pub fn format_define_text(value: &syn::Lit) -> String {
+    match value {
+        syn::Lit::Str(string) => string.value(),
+        syn::Lit::Int(int) => int.base10_digits().to_string(),
+        _ => unreachable!("MacroArgs only accepts string and integer defines"),
+    }
+}
+
+/// Parses the ports of module `top_name`, computing port widths using
+/// `parameter_overrides` in place of the default parameter values where given.
+/// The source is preprocessed with `defines` and `include_directories`, which
+/// should match what Verilator is given.
 pub fn parse_verilog_ports(
     top_name: &syn::LitStr,
     source_path: &syn::LitStr,
     verilog_source_path: &Path,
+    parameter_overrides: &[(syn::Ident, syn::Lit)],
+    defines: &[(String, Option<String>)],
+    include_directories: &[PathBuf],
 ) -> Result<Vec<(String, usize, usize, PortDirection)>, proc_macro2::TokenStream>
 {
-    let defines = HashMap::new();
-    let (ast, _) =
-        match sv::parse_sv(verilog_source_path, &defines, &["."], false, false)
-        {
-            Ok(result) => result,
-            Err(error) => {
-                return Err(syn::Error::new_spanned(
+    let defines = defines
+        .iter()
+        .map(|(name, text)| {
+            (
+                name.clone(),
+                Some(sv::Define::new(
+                    name.clone(),
+                    vec![],
+                    text.clone().map(|text| sv::DefineText::new(text, None)),
+                )),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let mut include_paths = vec![PathBuf::from(".")];
+    include_paths.extend_from_slice(include_directories);
+    let (ast, _) = match sv::parse_sv(
+        verilog_source_path,
+        &defines,
+        &include_paths,
+        false,
+        false,
+    ) {
+        Ok(result) => result,
+        Err(error) => {
+            return Err(syn::Error::new_spanned(
                 source_path,
                 error.to_string()
                     + " (Try checking, for instance, that the file exists.)",
             )
             .into_compile_error());
-            }
-        };
+        }
+    };
 
     let Some(module) = (&ast).into_iter().find_map(|node| match node {
         RefNode::ModuleDeclarationAnsi(module) => {
-            // taken from https://github.com/dalance/sv-parser/blob/master/README.md
-            fn get_identifier(node: RefNode) -> Option<Locate> {
-                match unwrap_node!(node, SimpleIdentifier, EscapedIdentifier) {
-                    Some(RefNode::SimpleIdentifier(x)) => Some(x.nodes.0),
-                    Some(RefNode::EscapedIdentifier(x)) => Some(x.nodes.0),
-                    _ => None,
-                }
-            }
-
             let id = unwrap_node!(module, ModuleIdentifier).unwrap();
-            let id = get_identifier(id).unwrap();
+            let id = util::get_identifier(id).unwrap();
             let id = ast.get_str_trim(&id).unwrap();
             if id == top_name.value().as_str() {
                 Some(module)
@@ -530,6 +829,18 @@ pub fn parse_verilog_ports(
         .into_compile_error());
     };
 
+    let parameters = match evaluate_module_parameters(
+        &ast,
+        module,
+        top_name,
+        parameter_overrides,
+    ) {
+        Ok(parameters) => parameters,
+        Err(error) => {
+            return Err(error.into_compile_error());
+        }
+    };
+
     let port_declarations_list = module
         .nodes
         .0
@@ -599,6 +910,7 @@ pub fn parse_verilog_ports(
                     port_name,
                     dimensions,
                     port_direction_node,
+                    &parameters,
                 ) {
                     Ok(port_info) => port_info,
                     Err(error) => {
@@ -668,6 +980,7 @@ pub fn parse_verilog_ports(
                     port_name,
                     dimensions,
                     port_direction_node,
+                    &parameters,
                 ) {
                     Ok(port_info) => port_info,
                     Err(error) => {
@@ -683,12 +996,94 @@ pub fn parse_verilog_ports(
     Ok(ports)
 }
 
+/// Computes the values of the parameters declared in the parameter port list
+/// of `module`, using `parameter_overrides` in place of the defaults where
+/// given. Parameters whose values cannot be computed (e.g., strings or
+/// unsupported expressions) are omitted.
+fn evaluate_module_parameters(
+    ast: &sv::SyntaxTree,
+    module: &sv::ModuleDeclarationAnsi,
+    top_name: &syn::LitStr,
+    parameter_overrides: &[(syn::Ident, syn::Lit)],
+) -> Result<HashMap<String, usize>, syn::Error> {
+    let mut parameters = HashMap::new();
+    let mut declared_parameters = vec![];
+
+    if let Some(parameter_port_list) = &module.nodes.0.nodes.5 {
+        for node in parameter_port_list {
+            let RefNode::ParamAssignment(param_assignment) = node else {
+                continue;
+            };
+            let Some(name) = util::get_identifier(&param_assignment.nodes.0)
+                .and_then(|locate| ast.get_str_trim(&locate))
+            else {
+                continue;
+            };
+            declared_parameters.push(name.to_string());
+
+            let value = if let Some((_, value)) = parameter_overrides
+                .iter()
+                .find(|(override_name, _)| override_name == name)
+            {
+                match value {
+                    syn::Lit::Int(int) => Some(int.base10_parse::<usize>()?),
+                    _ => None,
+                }
+            } else {
+                match &param_assignment.nodes.2 {
+                    Some((
+                        _,
+                        sv::ConstantParamExpression::ConstantMintypmaxExpression(
+                            expression,
+                        ),
+                    )) => match &**expression {
+                        sv::ConstantMintypmaxExpression::Unary(expression) => {
+                            util::evaluate_numeric_constant_expression(
+                                ast,
+                                expression,
+                                &parameters,
+                            )
+                            .ok()
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            };
+
+            if let Some(value) = value {
+                parameters.insert(name.to_string(), value);
+            }
+        }
+    }
+
+    if let Some((unknown_parameter, _)) =
+        parameter_overrides.iter().find(|(override_name, _)| {
+            !declared_parameters
+                .iter()
+                .any(|declared| override_name == declared)
+        })
+    {
+        return Err(syn::Error::new_spanned(
+            unknown_parameter,
+            format!(
+                "Module `{}` has no parameter named `{}`",
+                top_name.value(),
+                unknown_parameter
+            ),
+        ));
+    }
+
+    Ok(parameters)
+}
+
 fn process_port_common(
     ast: &sv::SyntaxTree,
     top_name: &syn::LitStr,
     port_name: &str,
     dimensions: &[sv::PackedDimension],
     port_direction_node: &sv::PortDirection,
+    parameters: &HashMap<String, usize>,
 ) -> Result<(String, usize, usize, PortDirection), syn::Error> {
     if port_name.chars().any(|c| c == '\\' || c == ' ') {
         return Err(syn::Error::new_spanned(
@@ -702,10 +1097,20 @@ fn process_port_common(
         1 => match &dimensions[0] {
             sv::PackedDimension::Range(packed_dimension_range) => {
                 let range = &packed_dimension_range.nodes.0.nodes.1.nodes;
-                (
-                    util::evaluate_numeric_constant_expression(ast, &range.0),
-                    util::evaluate_numeric_constant_expression(ast, &range.2),
-                )
+                let evaluate = |expression| {
+                    util::evaluate_numeric_constant_expression(
+                        ast, expression, parameters,
+                    )
+                    .map_err(|error| {
+                        syn::Error::new_spanned(
+                            top_name,
+                            format!(
+                                "Failed to compute the width of port `{port_name}`: {error}"
+                            ),
+                        )
+                    })
+                };
+                (evaluate(&range.0)?, evaluate(&range.2)?)
             }
             _ => todo!("Unsupported dimension type"),
         },
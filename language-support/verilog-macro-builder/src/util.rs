@@ -4,71 +4,285 @@
 // v. 2.0. If a copy of the MPL was not distributed with this file, You can
 // obtain one at https://mozilla.org/MPL/2.0/.
 
-use sv_parser as sv;
+use std::collections::HashMap;
 
+use sv_parser::{self as sv, Locate, RefNode, unwrap_node};
+
+// taken from https://github.com/dalance/sv-parser/blob/master/README.md
+pub fn get_identifier<'a>(
+    node: impl IntoIterator<Item = RefNode<'a>>,
+) -> Option<Locate> {
+    match unwrap_node!(node, SimpleIdentifier, EscapedIdentifier) {
+        Some(RefNode::SimpleIdentifier(x)) => Some(x.nodes.0),
+        Some(RefNode::EscapedIdentifier(x)) => Some(x.nodes.0),
+        _ => None,
+    }
+}
+
+/// Evaluates `expression` to a number, looking up identifiers in
+/// `parameters`.
 pub fn evaluate_numeric_constant_expression(
     ast: &sv::SyntaxTree,
     expression: &sv::ConstantExpression,
-) -> usize {
+    parameters: &HashMap<String, usize>,
+) -> Result<usize, String> {
     match expression {
         sv::ConstantExpression::ConstantPrimary(constant_primary) => {
-            match &**constant_primary {
-                sv::ConstantPrimary::PrimaryLiteral(primary_literal) => {
-                    match &**primary_literal {
-                        sv::PrimaryLiteral::Number(number) => match &**number {
-                            sv::Number::IntegralNumber(integral_number) => {
-                                match &**integral_number {
-                                    sv::IntegralNumber::DecimalNumber(
-                                        decimal_number,
-                                    ) => match &**decimal_number {
-                                        sv::DecimalNumber::UnsignedNumber(
-                                            unsigned_number,
-                                        ) => ast
-                                            .get_str_trim(
-                                                &unsigned_number.nodes.0,
+            evaluate_numeric_constant_primary(ast, constant_primary, parameters)
+        }
+        sv::ConstantExpression::Unary(constant_expression_unary) => {
+            let operator = ast
+                .get_str_trim(
+                    &constant_expression_unary.nodes.0.nodes.0.nodes.0,
+                )
+                .unwrap();
+            let operand = evaluate_numeric_constant_primary(
+                ast,
+                &constant_expression_unary.nodes.2,
+                parameters,
+            )?;
+            match operator {
+                "+" => Ok(operand),
+                "!" => Ok((operand == 0) as usize),
+                other => Err(format!("Unsupported unary operator `{other}`")),
+            }
+        }
+        sv::ConstantExpression::Binary(_) => {
+            // sv-parser nests binary operators in source order without
+            // precedence, so `2 * WIDTH - 1` arrives as `2 * (WIDTH - 1)`
+            let mut operands = vec![];
+            let mut operators = vec![];
+            flatten_binary(ast, expression, &mut operands, &mut operators);
+
+            let mut values = vec![];
+            let mut pending: Vec<&str> = vec![];
+            for (index, operand) in operands.into_iter().enumerate() {
+                values.push(evaluate_numeric_constant_expression(
+                    ast, operand, parameters,
+                )?);
+                let Some(operator) = operators.get(index).copied() else {
+                    break;
+                };
+                let precedence = binary_precedence(operator)?;
+                while pending.last().is_some_and(|previous| {
+                    binary_precedence(previous)
+                        .is_ok_and(|previous| previous >= precedence)
+                }) {
+                    reduce_binary(&mut values, pending.pop().unwrap())?;
+                }
+                pending.push(operator);
+            }
+            while let Some(operator) = pending.pop() {
+                reduce_binary(&mut values, operator)?;
+            }
+            Ok(values.pop().unwrap())
+        }
+        sv::ConstantExpression::Ternary(constant_expression_ternary) => {
+            let (condition, _, _, if_true, _, if_false) =
+                &constant_expression_ternary.nodes;
+            if evaluate_numeric_constant_expression(ast, condition, parameters)?
+                != 0
+            {
+                evaluate_numeric_constant_expression(ast, if_true, parameters)
+            } else {
+                evaluate_numeric_constant_expression(ast, if_false, parameters)
+            }
+        }
+    }
+}
+
+/// Collects the operands and operators of the binary operators at the root
+/// of `expression` in source order.
+fn flatten_binary<'a>(
+    ast: &'a sv::SyntaxTree,
+    expression: &'a sv::ConstantExpression,
+    operands: &mut Vec<&'a sv::ConstantExpression>,
+    operators: &mut Vec<&'a str>,
+) {
+    if let sv::ConstantExpression::Binary(constant_expression_binary) =
+        expression
+    {
+        let (lhs, operator, _, rhs) = &constant_expression_binary.nodes;
+        flatten_binary(ast, lhs, operands, operators);
+        operators.push(ast.get_str_trim(&operator.nodes.0.nodes.0).unwrap());
+        flatten_binary(ast, rhs, operands, operators);
+    } else {
+        operands.push(expression);
+    }
+}
+
+/// The precedence of a binary `operator` in SystemVerilog, higher binding
+/// tighter.
+fn binary_precedence(operator: &str) -> Result<u8, String> {
+    Ok(match operator {
+        "**" => 10,
+        "*" | "/" | "%" => 9,
+        "+" | "-" => 8,
+        "<<" | "<<<" | ">>" | ">>>" => 7,
+        "<" | "<=" | ">" | ">=" => 6,
+        "==" | "!=" => 5,
+        "&" => 4,
+        "^" => 3,
+        "|" => 2,
+        "&&" => 1,
+        "||" => 0,
+        other => {
+            return Err(format!("Unsupported binary operator `{other}`"));
+        }
+    })
+}
+
+/// Replaces the last two of `values` with the result of applying `operator`
+/// to them.
+fn reduce_binary(
+    values: &mut Vec<usize>,
+    operator: &str,
+) -> Result<(), String> {
+    let rhs = values.pop().unwrap();
+    let lhs = values.pop().unwrap();
+    let result = match operator {
+        "+" => lhs.checked_add(rhs),
+        "-" => lhs.checked_sub(rhs),
+        "*" => lhs.checked_mul(rhs),
+        "/" => lhs.checked_div(rhs),
+        "%" => lhs.checked_rem(rhs),
+        "**" => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_pow(rhs)),
+        "<<" | "<<<" => {
+            u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs))
+        }
+        ">>" | ">>>" => {
+            u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs))
+        }
+        "&" => Some(lhs & rhs),
+        "|" => Some(lhs | rhs),
+        "^" => Some(lhs ^ rhs),
+        "==" => Some((lhs == rhs) as usize),
+        "!=" => Some((lhs != rhs) as usize),
+        "<" => Some((lhs < rhs) as usize),
+        "<=" => Some((lhs <= rhs) as usize),
+        ">" => Some((lhs > rhs) as usize),
+        ">=" => Some((lhs >= rhs) as usize),
+        "&&" => Some((lhs != 0 && rhs != 0) as usize),
+        "||" => Some((lhs != 0 || rhs != 0) as usize),
+        other => {
+            return Err(format!("Unsupported binary operator `{other}`"));
+        }
+    };
+    values.push(result.ok_or_else(|| {
+        format!(
+            "Constant expression `{lhs} {operator} {rhs}` overflows or is negative"
+        )
+    })?);
+    Ok(())
+}
+
+fn lookup_parameter(
+    name: &str,
+    parameters: &HashMap<String, usize>,
+) -> Result<usize, String> {
+    parameters.get(name).copied().ok_or_else(|| {
+        format!(
+            "Parameter `{name}` is not known or does not have a numeric value"
+        )
+    })
+}
+
+fn evaluate_numeric_constant_primary(
+    ast: &sv::SyntaxTree,
+    constant_primary: &sv::ConstantPrimary,
+    parameters: &HashMap<String, usize>,
+) -> Result<usize, String> {
+    match constant_primary {
+        sv::ConstantPrimary::PrimaryLiteral(primary_literal) => {
+            match &**primary_literal {
+                sv::PrimaryLiteral::Number(number) => match &**number {
+                    sv::Number::IntegralNumber(integral_number) => {
+                        match &**integral_number {
+                            sv::IntegralNumber::DecimalNumber(
+                                decimal_number,
+                            ) => match &**decimal_number {
+                                sv::DecimalNumber::UnsignedNumber(
+                                    unsigned_number,
+                                ) => {
+                                    let digits = ast
+                                        .get_str_trim(&unsigned_number.nodes.0)
+                                        .unwrap();
+                                    digits.replace('_', "").parse().map_err(
+                                        |_| {
+                                            format!(
+                                                "Number `{digits}` is too large"
                                             )
-                                            .unwrap()
-                                            .parse()
-                                            .unwrap(),
-                                        sv::DecimalNumber::BaseUnsigned(
-                                            _decimal_number_base_unsigned,
-                                        ) => todo!(),
-                                        sv::DecimalNumber::BaseXNumber(
-                                            _decimal_number_base_xnumber,
-                                        ) => todo!(),
-                                        sv::DecimalNumber::BaseZNumber(
-                                            _decimal_number_base_znumber,
-                                        ) => todo!(),
-                                    },
-                                    sv::IntegralNumber::OctalNumber(
-                                        _octal_number,
-                                    ) => todo!(),
-                                    sv::IntegralNumber::BinaryNumber(
-                                        _binary_number,
-                                    ) => todo!(),
-                                    sv::IntegralNumber::HexNumber(
-                                        _hex_number,
-                                    ) => todo!(),
+                                        },
+                                    )
                                 }
-                            }
-                            sv::Number::RealNumber(_real_number) => {
-                                panic!("Real number")
-                            }
-                        },
-                        _ => todo!("Other constant primary literals"),
+                                _ => {
+                                    Err("Based numbers are not supported"
+                                        .into())
+                                }
+                            },
+                            _ => Err("Based numbers are not supported".into()),
+                        }
                     }
-                }
-                _ => panic!("Not a number"),
+                    sv::Number::RealNumber(_) => {
+                        Err("Real numbers are not supported".into())
+                    }
+                },
+                _ => Err("Only numeric literals are supported".into()),
             }
         }
-        sv::ConstantExpression::Unary(_constant_expression_unary) => {
-            todo!("Constant unary expressions")
+        sv::ConstantPrimary::PsParameter(constant_primary_ps_parameter) => {
+            let name = get_identifier(&constant_primary_ps_parameter.nodes.0)
+                .and_then(|locate| ast.get_str_trim(&locate))
+                .unwrap();
+            lookup_parameter(name, parameters)
         }
-        sv::ConstantExpression::Binary(_constant_expression_binary) => {
-            todo!("Constant binary expressions")
-        }
-        sv::ConstantExpression::Ternary(_constant_expression_ternary) => {
-            todo!("Constant ternary expressions")
+        // sv-parser reads a bare identifier like `WIDTH` as a call of a
+        // function without arguments
+        sv::ConstantPrimary::ConstantFunctionCall(constant_function_call) => {
+            let sv::SubroutineCall::TfCall(tf_call) =
+                &constant_function_call.nodes.0.nodes.0
+            else {
+                return Err("Function calls are not supported".into());
+            };
+            let (identifier, attributes, arguments) = &tf_call.nodes;
+            let identifier = match identifier {
+                sv::PsOrHierarchicalTfIdentifier::PackageScope(scoped)
+                    if scoped.nodes.0.is_none() =>
+                {
+                    Some(&scoped.nodes.1.nodes.0)
+                }
+                sv::PsOrHierarchicalTfIdentifier::HierarchicalTfIdentifier(
+                    hierarchical,
+                ) => {
+                    let (root, path, identifier) = &hierarchical.nodes.0.nodes;
+                    (root.is_none() && path.is_empty()).then_some(identifier)
+                }
+                _ => None,
+            };
+            match identifier {
+                Some(identifier)
+                    if attributes.is_empty() && arguments.is_none() =>
+                {
+                    let name = get_identifier(identifier)
+                        .and_then(|locate| ast.get_str_trim(&locate))
+                        .unwrap();
+                    lookup_parameter(name, parameters)
+                }
+                _ => Err("Function calls are not supported".into()),
+            }
         }
+        sv::ConstantPrimary::MintypmaxExpression(
+            constant_primary_mintypmax_expression,
+        ) => match &constant_primary_mintypmax_expression.nodes.0.nodes.1 {
+            sv::ConstantMintypmaxExpression::Unary(expression) => {
+                evaluate_numeric_constant_expression(
+                    ast, expression, parameters,
+                )
+            }
+            sv::ConstantMintypmaxExpression::Ternary(_) => {
+                Err("Min-typ-max expressions are not supported".into())
+            }
+        },
+        _ => Err("Unsupported constant expression".into()),
     }
 }
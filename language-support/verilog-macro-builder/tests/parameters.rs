@@ -0,0 +1,102 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fs, path::PathBuf};
+
+use marlin_verilator::PortDirection;
+use marlin_verilog_macro_builder::{
+    format_parameter_override, parse_verilog_ports,
+};
+use proc_macro2::Span;
+
+fn ports_of(
+    name: &str,
+    source: &str,
+    parameter_overrides: &[(syn::Ident, syn::Lit)],
+) -> Vec<(String, usize, usize, PortDirection)> {
+    let path =
+        PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.sv"));
+    fs::write(&path, source).expect("Failed to write source file");
+    parse_verilog_ports(
+        &syn::LitStr::new(name, Span::call_site()),
+        &syn::LitStr::new(&path.to_string_lossy(), Span::call_site()),
+        &path,
+        parameter_overrides,
+        &[],
+        &[],
+    )
+    .unwrap_or_else(|error| panic!("Failed to parse ports: {error}"))
+}
+
+#[test]
+fn port_width_from_parameter_default() {
+    let ports = ports_of(
+        "parameterized",
+        "module parameterized #(
+             parameter WIDTH = 8
+         ) (
+             input[WIDTH-1:0] value,
+             output[2*WIDTH-1:0] doubled,
+             output[(WIDTH-4)*2+WIDTH%3-1:0] mixed
+         );
+             assign doubled = {value, value};
+             assign mixed = 0;
+         endmodule",
+        &[],
+    );
+    assert_eq!(
+        ports,
+        [
+            ("value".into(), 7, 0, PortDirection::Input),
+            ("doubled".into(), 15, 0, PortDirection::Output),
+            ("mixed".into(), 9, 0, PortDirection::Output),
+        ]
+    );
+
+    let ports = ports_of(
+        "parameterized_override",
+        "module parameterized_override #(
+             parameter WIDTH = 8
+         ) (
+             input[WIDTH-1:0] value
+         );
+         endmodule",
+        &[(
+            syn::Ident::new("WIDTH", Span::call_site()),
+            syn::parse_quote!(48),
+        )],
+    );
+    assert_eq!(ports, [("value".into(), 47, 0, PortDirection::Input)]);
+}
+
+#[test]
+fn unevaluated_parameter_defaults_are_skipped() {
+    let ports = ports_of(
+        "literal_defaults",
+        "module literal_defaults #(
+             parameter INIT = 8'hFF,
+             parameter NAME = \"literal_defaults\",
+             parameter SCALE = 1.5,
+             parameter WIDTH = 4
+         ) (
+             input[WIDTH-1:0] value
+         );
+         endmodule",
+        &[],
+    );
+    assert_eq!(ports, [("value".into(), 3, 0, PortDirection::Input)]);
+}
+
+#[test]
+fn string_parameter_overrides_are_escaped() {
+    assert_eq!(
+        format_parameter_override(&syn::parse_quote!(
+            "a \"quoted\" C:\\path\n"
+        )),
+        r#""a \"quoted\" C:\\path\n""#
+    );
+    assert_eq!(format_parameter_override(&syn::parse_quote!(42)), "42");
+}
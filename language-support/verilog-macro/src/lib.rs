@@ -7,7 +7,8 @@
 use std::{env, fmt, path::PathBuf};
 
 use marlin_verilog_macro_builder::{
-    MacroArgs, build_verilated_struct, parse_verilog_ports,
+    MacroArgs, build_verilated_struct, format_define_text,
+    format_parameter_override, parse_verilog_ports,
 };
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
@@ -20,10 +21,26 @@ pub fn verilog(args: TokenStream, item: TokenStream) -> TokenStream {
     let manifest_directory = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("Please compile using `cargo` or set the `CARGO_MANIFEST_DIR` environment variable"));
     let source_path = manifest_directory.join(args.source_path.value());
 
+    let defines = args
+        .defines
+        .iter()
+        .map(|(name, value)| {
+            (name.to_string(), value.as_ref().map(format_define_text))
+        })
+        .collect::<Vec<_>>();
+    let include_directories = args
+        .include
+        .iter()
+        .map(|directory| manifest_directory.join(directory.value()))
+        .collect::<Vec<_>>();
+
     let ports = match parse_verilog_ports(
         &args.name,
         &args.source_path,
         &source_path,
+        &args.parameters,
+        &defines,
+        &include_directories,
     ) {
         Ok(ports) => ports,
         Err(error) => {
@@ -31,6 +48,14 @@ pub fn verilog(args: TokenStream, item: TokenStream) -> TokenStream {
         }
     };
 
+    let parameters = args
+        .parameters
+        .iter()
+        .map(|(name, value)| {
+            (name.to_string(), format_parameter_override(value))
+        })
+        .collect::<Vec<_>>();
+
     build_verilated_struct(
         "verilog",
         args.name,
@@ -39,6 +64,12 @@ pub fn verilog(args: TokenStream, item: TokenStream) -> TokenStream {
             args.source_path.span(),
         ),
         ports,
+        &parameters,
+        &defines,
+        &include_directories
+            .iter()
+            .map(|directory| directory.to_string_lossy().into_owned())
+            .collect::<Vec<_>>(),
         item.into(),
     )
     .into()
@@ -194,6 +194,10 @@ impl VerylWalker for ModuleFinder<'_, '_> {
 pub fn veryl(args: TokenStream, item: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as MacroArgs);
 
+    if let Err(error) = args.reject_verilog_only_arguments() {
+        return error.into_compile_error().into();
+    }
+
     let manifest_directory = Utf8PathBuf::from(
         env::var("CARGO_MANIFEST_DIR").expect("Please use CARGO"),
     );
@@ -296,6 +300,9 @@ pub fn veryl(args: TokenStream, item: TokenStream) -> TokenStream {
         verilog_module_name,
         verilog_source_path,
         ports,
+        &[],
+        &[],
+        &[],
         item.into(),
     )
     .into()
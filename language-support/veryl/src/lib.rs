@@ -155,7 +155,8 @@ impl VerylRuntime {
                 &[],
                 [],
                 options.verilator_options,
-            )?,
+            )
+            .whatever_context("Failed to create the Verilator runtime")?,
         })
     }
 
@@ -164,6 +165,8 @@ impl VerylRuntime {
     pub fn create_model<'ctx, M: AsVerilatedModel<'ctx>>(
         &'ctx self,
     ) -> Result<M, Whatever> {
-        self.verilator_runtime.create_model_simple()
+        self.verilator_runtime
+            .create_model_simple()
+            .whatever_context("Failed to create the Veryl module")
     }
 }
@@ -17,21 +17,31 @@ use camino::{Utf8Path, Utf8PathBuf};
 use snafu::{Whatever, prelude::*};
 
 use crate::{
-    PortDirection, VerilatedModelConfig, VerilatorRuntimeOptions,
+    ModelGroup, PortDirection, PublicSignals, ThreadsDpi, VerilatedModelConfig,
+    VerilatorRuntimeError, VerilatorRuntimeOptions, XInit,
     compute_wdata_word_count_from_width_not_msb,
+    diagnostics::{self, BuildReport},
     dpi::DpiFunction,
+    error::{
+        InvalidConfigurationSnafu, PrebuiltUnavailableSnafu,
+        VerilatorFailedSnafu, VerilatorNotFoundSnafu,
+    },
     ffi_names::{
-        self, DPI_INIT_CALLBACK, TRACE_EVER_ON, VCD_CLOSE_AND_DELETE, VCD_DUMP,
-        VCD_FLUSH, VCD_OPEN_NEXT,
+        self, CHECKPOINT_SET_TAG, CONTEXT_CLEAR_FINISH, CONTEXT_CLEAR_OUTPUT,
+        CONTEXT_OUTPUT, CONTEXT_SEED, CONTEXT_SET_CAPTURE_OUTPUT,
+        CONTEXT_SET_COVERAGE_PATH, CONTEXT_SET_TIME, CONTEXT_STATUS,
+        CONTEXT_TIME, CONTEXT_TIME_INC, CONTEXT_WRITE_COVERAGE,
+        DPI_INIT_CALLBACK, MEMORY_LAYOUT, MEMORY_READ, MEMORY_WRITE,
+        PRINT_HEADER, SIGNAL_READ, SIGNAL_WIDTH, SIGNAL_WRITE, TRACE_EVER_ON,
+        VCD_CLOSE_AND_DELETE, VCD_DUMP, VCD_FLUSH, VCD_OPEN_NEXT, VL_PRINTF,
+        VL_VPRINTF,
     },
+    manifest::{self, BuildManifest},
     types,
+    version::VerilatorInfo,
 };
 
-fn build_ffi_for_tracing(
-    buffer: &mut String,
-    top_module: &str,
-) -> Result<(), Whatever> {
-    let open_trace = ffi_names::open_trace(top_module);
+fn build_ffi_for_tracing(buffer: &mut String) -> Result<(), Whatever> {
     writeln!(
         buffer,
         r#"
@@ -39,13 +49,6 @@ fn build_ffi_for_tracing(
         Verilated::traceEverOn(everOn);
     }}
 
-    VerilatedVcdC* {open_trace}(V{top_module}* top, const char* path) {{
-        VerilatedVcdC* vcd = new VerilatedVcdC;
-        top->trace(vcd, 99);
-        vcd->open(path);
-        return vcd;
-    }}
-
     void {VCD_DUMP}(VerilatedVcdC* vcd, uint64_t timestamp) {{
         vcd->dump(timestamp);
     }}
@@ -64,15 +67,569 @@ fn build_ffi_for_tracing(
     }}
 "#
     )
-    .whatever_context("Failed to format tracing FFI")?;
+    .whatever_context::<_, Whatever>("Failed to format tracing FFI")?;
+
+    Ok(())
+}
+
+fn build_ffi_for_top_tracing(
+    buffer: &mut String,
+    model_class: &str,
+    top: &FfiTop,
+) -> Result<(), Whatever> {
+    let open_trace = ffi_names::open_trace(top.name);
+    writeln!(
+        buffer,
+        r#"
+    VerilatedVcdC* {open_trace}({model_class}* top, const char* path) {{
+        VerilatedVcdC* vcd = new VerilatedVcdC;
+        top->trace(vcd, 99);
+        vcd->open(path);
+        return vcd;
+    }}
+"#
+    )
+    .whatever_context::<_, Whatever>("Failed to format tracing FFI")?;
+
+    Ok(())
+}
+
+/// Implementations of Verilator's (de)serialization interfaces that save to
+/// and restore from memory instead of a file, for forking models. They follow
+/// `VerilatedSave` and `VerilatedRestore`.
+const MEMORY_SERIALIZATION: &str = r#"#include <algorithm>
+#include "verilated_save.h"
+
+class MarlinMemorySave final : public VerilatedSerialize {
+public:
+    std::string data;
+
+    MarlinMemorySave() {
+        m_isOpen = true;
+        header();
+    }
+    ~MarlinMemorySave() override { close(); }
+
+    void close() override {
+        if (!isOpen()) return;
+        trailer();
+        flush();
+        m_isOpen = false;
+    }
+    void flush() override {
+        data.append(reinterpret_cast<const char*>(m_bufp), m_cp - m_bufp);
+        m_cp = m_bufp;
+    }
+};
+
+class MarlinMemoryRestore final : public VerilatedDeserialize {
+    const std::string& m_data;
+    size_t m_position = 0;
+
+public:
+    explicit MarlinMemoryRestore(const std::string& data) : m_data{data} {
+        m_isOpen = true;
+        header();
+    }
+    ~MarlinMemoryRestore() override { close(); }
+
+    void close() override {
+        if (!isOpen()) return;
+        trailer();
+        m_isOpen = false;
+    }
+    void fill() override {
+        // move the unread bytes to the start of the buffer, then refill it
+        uint8_t* rp = m_bufp;
+        for (uint8_t* sp = m_cp; sp < m_endp; *rp++ = *sp++) {}
+        m_endp = m_bufp + (m_endp - m_cp);
+        m_cp = m_bufp;
+        const size_t count = std::min<size_t>(
+            bufferSize() - (m_endp - m_bufp), m_data.size() - m_position);
+        std::memcpy(m_endp, m_data.data() + m_position, count);
+        m_position += count;
+        m_endp += count;
+    }
+};
+"#;
+
+fn build_ffi_for_checkpoints(buffer: &mut String) -> Result<(), Whatever> {
+    writeln!(
+        buffer,
+        r#"
+    // identifies the build of this library; set by Rust when it is loaded
+    static std::string marlin_checkpoint_tag;
+
+    void {CHECKPOINT_SET_TAG}(const char* tag) {{
+        marlin_checkpoint_tag = tag;
+    }}
+"#
+    )
+    .whatever_context::<_, Whatever>("Failed to format checkpoint FFI")?;
+
+    Ok(())
+}
+
+fn build_ffi_for_top_checkpoints(
+    buffer: &mut String,
+    model_class: &str,
+    top: &FfiTop,
+) -> Result<(), Whatever> {
+    let new_top = ffi_names::new_top(top.name);
+    let top_save = ffi_names::top_save(top.name);
+    let top_restore = ffi_names::top_restore(top.name);
+    let top_fork = ffi_names::top_fork(top.name);
+    writeln!(
+        buffer,
+        r#"
+    // 0 on success and 1 if the file could not be opened
+    int {top_save}({model_class}* top, const char* path) {{
+        VerilatedSave os;
+        os.open(path);
+        if (!os.isOpen()) {{
+            return 1;
+        }}
+        os << marlin_checkpoint_tag;
+        os << *top;
+        os.close();
+        return 0;
+    }}
+
+    // 0 on success, 1 if the file could not be opened, and 2 if it was saved
+    // by a different build
+    int {top_restore}({model_class}* top, const char* path) {{
+        VerilatedRestore os;
+        os.open(path);
+        if (!os.isOpen()) {{
+            return 1;
+        }}
+        std::string tag;
+        os >> tag;
+        if (tag != marlin_checkpoint_tag) {{
+            os.close();
+            return 2;
+        }}
+        os >> *top;
+        os.close();
+        return 0;
+    }}
+
+    // creates a new model, with a new context created from the same
+    // arguments, and copies the state of `top` into it through memory
+    void* {top_fork}({model_class}* top) {{
+        MarlinContext* contextp = static_cast<MarlinContext*>(top->contextp());
+        std::vector<const char*> argv;
+        for (const std::string& arg : contextp->args) {{
+            argv.push_back(arg.c_str());
+        }}
+        {model_class}* fork = static_cast<{model_class}*>({new_top}(argv.size(), argv.data()));
+        static_cast<MarlinContext*>(fork->contextp())->capture_output = contextp->capture_output;
+
+        MarlinMemorySave save;
+        save << *top;
+        save.close();
+        MarlinMemoryRestore restore{{save.data}};
+        restore >> *fork;
+        restore.close();
+        return fork;
+    }}
+"#
+    )
+    .whatever_context::<_, Whatever>("Failed to format checkpoint FFI")?;
+
+    Ok(())
+}
+
+/// Writes the header declaring the replacements for `VL_PRINTF` and
+/// `VL_VPRINTF` to `artifact_directory`, returning its path.
+fn build_print_header(
+    artifact_directory: &Utf8Path,
+) -> Result<Utf8PathBuf, Whatever> {
+    let print_header = artifact_directory.join(PRINT_HEADER);
+    let buffer = format!(
+        r#"#pragma once
+#include <cstdarg>
+
+int {VL_PRINTF}(const char* format, ...);
+int {VL_VPRINTF}(const char* format, va_list args);
+"#
+    );
+    if fs::read_to_string(&print_header)
+        .map(|current_buffer| current_buffer != buffer)
+        .unwrap_or(true)
+    {
+        fs::write(&print_header, buffer).whatever_context::<_, Whatever>(
+            "Failed to write print header file",
+        )?;
+    }
+    Ok(print_header)
+}
+
+fn build_ffi_for_context(buffer: &mut String) -> Result<(), Whatever> {
+    writeln!(
+        buffer,
+        r#"#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+#include <string>
+#include <vector>
+#include "verilated.h"
+
+// a context that collects what the design prints
+struct MarlinContext : VerilatedContext {{
+    // the arguments given to `commandArgs`, for creating forks
+    std::vector<std::string> args;
+    bool capture_output = false;
+    std::string output;
+    // lines of `output` reporting $info, $warning, $error, or $fatal
+    std::string messages;
+    // where the coverage is written when the model is deleted, if anywhere
+    std::string coverage_path;
+    // the last $error or $fatal message printed during the current eval
+    std::string last_error;
+    // the message of the assertion failure or fatal error that stopped the
+    // design, if any
+    std::string failure;
+}};
+
+// the context of the model being evaluated on this thread, if any
+static thread_local MarlinContext* marlin_printing_context = nullptr;
+
+static void marlin_record_messages(MarlinContext* contextp, const std::string& text) {{
+    static const char* const prefixes[] = {{"%Error", "%Warning", "%Fatal", "-Info"}};
+    size_t start = 0;
+    while (start < text.size()) {{
+        size_t end = text.find('\n', start);
+        if (end == std::string::npos) {{
+            end = text.size();
+        }}
+        const std::string line = text.substr(start, end - start);
+        // skip the simulation time, as in "[10] %Error: ..."
+        size_t offset = 0;
+        if (!line.empty() && line[0] == '[') {{
+            const size_t close = line.find("] ");
+            if (close != std::string::npos) {{
+                offset = close + 2;
+            }}
+        }}
+        for (const char* prefix : prefixes) {{
+            if (line.compare(offset, std::strlen(prefix), prefix) == 0) {{
+                contextp->messages += line;
+                contextp->messages += '\n';
+                // %Error or %Fatal
+                if (prefix[0] == '%' && prefix[1] != 'W') {{
+                    contextp->last_error = line;
+                }}
+                break;
+            }}
+        }}
+        start = end + 1;
+    }}
+}}
+
+int {VL_VPRINTF}(const char* format, va_list args) {{
+    MarlinContext* contextp = marlin_printing_context;
+    if (!contextp) {{
+        return std::vprintf(format, args);
+    }}
+    va_list args_copy;
+    va_copy(args_copy, args);
+    const int length = std::vsnprintf(nullptr, 0, format, args_copy);
+    va_end(args_copy);
+    if (length <= 0) {{
+        return length;
+    }}
+    std::string text(length, '\0');
+    std::vsnprintf(&text[0], length + 1, format, args);
+    contextp->output += text;
+    marlin_record_messages(contextp, text);
+    return length;
+}}
+
+static std::string& marlin_output(VerilatedContext* contextp, int kind) {{
+    MarlinContext* marlin_contextp = static_cast<MarlinContext*>(contextp);
+    switch (kind) {{
+        case 0: return marlin_contextp->output;
+        case 1: return marlin_contextp->messages;
+        default: return marlin_contextp->failure;
+    }}
+}}
+
+int {VL_PRINTF}(const char* format, ...) {{
+    va_list args;
+    va_start(args, format);
+    const int length = {VL_VPRINTF}(format, args);
+    va_end(args);
+    return length;
+}}
+
+extern "C" {{
+    uint64_t {CONTEXT_TIME}(VerilatedContext* contextp) {{
+        return contextp->time();
+    }}
+
+    void {CONTEXT_SET_TIME}(VerilatedContext* contextp, uint64_t time) {{
+        contextp->time(time);
+    }}
+
+    void {CONTEXT_TIME_INC}(VerilatedContext* contextp, uint64_t delta) {{
+        contextp->timeInc(delta);
+    }}
+
+    // 0 if running, 1 after $finish, 2 after $stop, and 3 after a failure
+    int {CONTEXT_STATUS}(VerilatedContext* contextp) {{
+        if (!contextp->gotFinish()) {{
+            return 0;
+        }}
+        if (!static_cast<MarlinContext*>(contextp)->failure.empty()) {{
+            return 3;
+        }}
+        return contextp->gotError() ? 2 : 1;
+    }}
+
+    void {CONTEXT_CLEAR_FINISH}(VerilatedContext* contextp) {{
+        contextp->gotFinish(false);
+        contextp->gotError(false);
+        static_cast<MarlinContext*>(contextp)->failure.clear();
+    }}
+
+    int {CONTEXT_SEED}(VerilatedContext* contextp) {{
+        return contextp->randSeed();
+    }}
+
+    // `kind` is 0 for all output, 1 for messages, and 2 for the failure
+    const char* {CONTEXT_OUTPUT}(VerilatedContext* contextp, int kind, size_t* length) {{
+        const std::string& output = marlin_output(contextp, kind);
+        *length = output.size();
+        return output.data();
+    }}
+
+    void {CONTEXT_CLEAR_OUTPUT}(VerilatedContext* contextp, int kind) {{
+        marlin_output(contextp, kind).clear();
+    }}
+
+    void {CONTEXT_SET_CAPTURE_OUTPUT}(VerilatedContext* contextp, bool capture_output) {{
+        static_cast<MarlinContext*>(contextp)->capture_output = capture_output;
+    }}
+}}"#
+    )
+    .whatever_context::<_, Whatever>("Failed to format context FFI")?;
 
     Ok(())
 }
 
-/// Writes `extern "C"` C++ bindings for a Verilator model with the given name
-/// (`top_module`) and signature (`ports`) to the given artifact directory
+fn build_ffi_for_coverage(buffer: &mut String) -> Result<(), Whatever> {
+    writeln!(
+        buffer,
+        r#"#include "verilated_cov.h"
+
+extern "C" {{
+    void {CONTEXT_SET_COVERAGE_PATH}(VerilatedContext* contextp, const char* path) {{
+        static_cast<MarlinContext*>(contextp)->coverage_path = path;
+    }}
+
+    void {CONTEXT_WRITE_COVERAGE}(VerilatedContext* contextp, const char* path) {{
+        contextp->coveragep()->write(path);
+    }}
+}}"#
+    )
+    .whatever_context::<_, Whatever>("Failed to format coverage FFI")?;
+
+    Ok(())
+}
+
+/// Writes FFI for reading and writing public signals by hierarchical path
+/// (e.g., `main.core.state` or `main.regfile[3]`) through VPI. Values are
+/// passed as arrays of 32-bit words, least significant first, and
+/// [`ffi_names::SIGNAL_WIDTH`] returns `-1` for a path that does not name a
+/// public signal.
+///
+/// Memories are accessed a range of addresses at a time, with the words of
+/// each address following those of the previous one.
+/// [`ffi_names::MEMORY_LAYOUT`] returns the width of each word, `-1` for a
+/// path that does not name a public signal, or `0` for a signal that is not a
+/// memory.
+///
+/// Paths are relative to the top of the model, or, in the library of a
+/// [`crate::ModelGroup`], to the generated `wrapper_module` if they are not
+/// found at the top.
+fn build_ffi_for_signals(
+    buffer: &mut String,
+    wrapper_module: Option<&str>,
+) -> Result<(), Whatever> {
+    let wrapper_prefixes = wrapper_module
+        .map(|wrapper_module| {
+            format!(", \"{wrapper_module}.\", \"TOP.{wrapper_module}.\"")
+        })
+        .unwrap_or_default();
+    writeln!(
+        buffer,
+        r#"#include <algorithm> // std::min, std::max
+#include "verilated_vpi.h"
+
+// VPI looks up names in the scopes of the current thread's context. Verilator
+// may or may not name the scopes with a leading "TOP.", so both are tried. A
+// trailing "[index]" selects a word of a memory.
+static vpiHandle marlin_signal_handle(VerilatedContext* contextp, const char* path) {{
+    Verilated::threadContextp(contextp);
+    std::string name = path;
+    std::string index;
+    const size_t open = name.rfind('[');
+    if (!name.empty() && name.back() == ']' && open != std::string::npos) {{
+        index = name.substr(open + 1, name.size() - open - 2);
+        name.erase(open);
+    }}
+    static const char* const prefixes[] = {{"", "TOP."{wrapper_prefixes}}};
+    vpiHandle handle = nullptr;
+    for (const char* prefix : prefixes) {{
+        const std::string scoped_name = prefix + name;
+        handle = vpi_handle_by_name(const_cast<PLI_BYTE8*>(scoped_name.c_str()), nullptr);
+        if (handle) {{
+            break;
+        }}
+    }}
+    if (handle && !index.empty()) {{
+        vpiHandle word = vpi_handle_by_index(handle, std::atoi(index.c_str()));
+        vpi_release_handle(handle);
+        handle = word;
+    }}
+    return handle;
+}}
+
+extern "C" {{
+    int {SIGNAL_WIDTH}(VerilatedContext* contextp, const char* path) {{
+        vpiHandle handle = marlin_signal_handle(contextp, path);
+        if (!handle) {{
+            return -1;
+        }}
+        const int width = vpi_get(vpiSize, handle);
+        vpi_release_handle(handle);
+        return width;
+    }}
+
+    void {SIGNAL_READ}(VerilatedContext* contextp, const char* path, uint32_t* words) {{
+        vpiHandle handle = marlin_signal_handle(contextp, path);
+        if (!handle) {{
+            return;
+        }}
+        const int word_count = (vpi_get(vpiSize, handle) + 31) / 32;
+        s_vpi_value value;
+        value.format = vpiVectorVal;
+        vpi_get_value(handle, &value);
+        for (int i = 0; i < word_count; i++) {{
+            words[i] = value.value.vector[i].aval;
+        }}
+        vpi_release_handle(handle);
+    }}
+
+    void {SIGNAL_WRITE}(VerilatedContext* contextp, const char* path, const uint32_t* words) {{
+        vpiHandle handle = marlin_signal_handle(contextp, path);
+        if (!handle) {{
+            return;
+        }}
+        std::vector<s_vpi_vecval> vector((vpi_get(vpiSize, handle) + 31) / 32);
+        for (size_t i = 0; i < vector.size(); i++) {{
+            vector[i].aval = words[i];
+            vector[i].bval = 0;
+        }}
+        s_vpi_value value;
+        value.format = vpiVectorVal;
+        value.value.vector = vector.data();
+        vpi_put_value(handle, &value, nullptr, vpiNoDelay);
+        vpi_release_handle(handle);
+    }}
+
+    int {MEMORY_LAYOUT}(VerilatedContext* contextp, const char* path, int* low, int* high) {{
+        vpiHandle memory = marlin_signal_handle(contextp, path);
+        if (!memory) {{
+            return -1;
+        }}
+        int width = 0;
+        const int type = vpi_get(vpiType, memory);
+        if (type == vpiMemory || type == vpiRegArray) {{
+            vpiHandle left = vpi_handle(vpiLeftRange, memory);
+            vpiHandle right = vpi_handle(vpiRightRange, memory);
+            s_vpi_value value;
+            value.format = vpiIntVal;
+            vpi_get_value(left, &value);
+            const int left_address = value.value.integer;
+            vpi_get_value(right, &value);
+            const int right_address = value.value.integer;
+            *low = std::min(left_address, right_address);
+            *high = std::max(left_address, right_address);
+            vpiHandle word = vpi_handle_by_index(memory, *low);
+            width = vpi_get(vpiSize, word);
+            vpi_release_handle(word);
+            vpi_release_handle(left);
+            vpi_release_handle(right);
+        }}
+        vpi_release_handle(memory);
+        return width;
+    }}
+
+    void {MEMORY_READ}(VerilatedContext* contextp, const char* path, int start, int count, uint32_t* words) {{
+        vpiHandle memory = marlin_signal_handle(contextp, path);
+        if (!memory) {{
+            return;
+        }}
+        for (int i = 0; i < count; i++) {{
+            vpiHandle word = vpi_handle_by_index(memory, start + i);
+            const int word_count = (vpi_get(vpiSize, word) + 31) / 32;
+            s_vpi_value value;
+            value.format = vpiVectorVal;
+            vpi_get_value(word, &value);
+            for (int j = 0; j < word_count; j++) {{
+                words[i * word_count + j] = value.value.vector[j].aval;
+            }}
+            vpi_release_handle(word);
+        }}
+        vpi_release_handle(memory);
+    }}
+
+    void {MEMORY_WRITE}(VerilatedContext* contextp, const char* path, int start, int count, const uint32_t* words) {{
+        vpiHandle memory = marlin_signal_handle(contextp, path);
+        if (!memory) {{
+            return;
+        }}
+        for (int i = 0; i < count; i++) {{
+            vpiHandle word = vpi_handle_by_index(memory, start + i);
+            std::vector<s_vpi_vecval> vector((vpi_get(vpiSize, word) + 31) / 32);
+            for (size_t j = 0; j < vector.size(); j++) {{
+                vector[j].aval = words[i * vector.size() + j];
+                vector[j].bval = 0;
+            }}
+            s_vpi_value value;
+            value.format = vpiVectorVal;
+            value.value.vector = vector.data();
+            vpi_put_value(word, &value, nullptr, vpiNoDelay);
+            vpi_release_handle(word);
+        }}
+        vpi_release_handle(memory);
+    }}
+}}"#
+    )
+    .whatever_context::<_, Whatever>("Failed to format signal FFI")?;
+
+    Ok(())
+}
+
+/// A module for which [`build_ffi`] generates bindings.
+struct FfiTop<'a> {
+    /// The name of the module, after which its FFI symbols are named.
+    name: &'a str,
+    ports: Vec<(&'a str, usize, usize, PortDirection)>,
+    /// Prepended to the name of each port to get the member of the model
+    /// class for it, which differs when the module is a member of a
+    /// [`crate::ModelGroup`] wired to the ports of the generated wrapper.
+    port_prefix: String,
+}
+
+/// Writes `extern "C"` C++ bindings for a Verilator model with the given top
+/// module (`top_module`) to the given artifact directory
 /// `artifact_directory`, returning the path to the C++ file containing the FFI
-/// wrappers.
+/// wrappers. The bindings are generated for each of the `tops`, which are
+/// either the top module itself with its signature or the members of a
+/// [`crate::ModelGroup`] instantiated in it.
 ///
 /// # Accessible Functions
 ///
@@ -81,143 +638,376 @@ fn build_ffi_for_tracing(
 /// `new` and `delete`, where the deletion is of the pointer created with `new`
 /// in the former function. See §18.6 "Dynamic memory management" of the C++14
 /// standard draft for specific semantics to translate into Rust safety
-/// comments.
+/// comments. Each model is created with its own `VerilatedContext`, which is
+/// deleted along with the model (after writing its coverage, if the model
+/// collects coverage and [`ffi_names::CONTEXT_SET_COVERAGE_PATH`] was called). The creation wrapper takes the `argc` and
+/// `argv` passed to `VerilatedContext::commandArgs` before the model is
+/// constructed, so that `+verilator+` runtime options apply to its
+/// initialization.
 ///
 /// The wrappers reading ([`ffi_names::read_port`]) and writing
 /// ([`ffi_names::pin_port`]) ports directly read and write to class members of
 /// the ppointer created with `new in the FFI creation wrapper.
 ///
-/// The wrapper for evaluating the model ([`ffi_names::top_eval`]) simply calls
-/// `eval` \[1\].
+/// The wrapper for evaluating the model ([`ffi_names::top_eval`]) calls
+/// `eval` \[1\] and returns the status of the model's context (see
+/// [`crate::context::EvalStatus::from_ffi`]). Everything the design prints
+/// during `eval` is collected in its context (a `MarlinContext`) instead of
+/// going to standard output; the status has bit `4` set if there is such
+/// output that Rust should print because it is not being captured.
 ///
 /// \[1\]: https://verilator.org/guide/latest/connecting.html#wrappers-and-model-evaluation-loop
 fn build_ffi(
     artifact_directory: &Utf8Path,
     top_module: &str,
-    ports: &[(&str, usize, usize, PortDirection)],
-    enable_tracing: bool,
+    tops: &[FfiTop],
+    config: &VerilatedModelConfig,
 ) -> Result<Utf8PathBuf, Whatever> {
     let ffi_wrappers = artifact_directory.join("ffi.cpp");
+    let model_class = format!("V{top_module}");
 
     let mut buffer = String::new();
 
-    if enable_tracing {
+    build_ffi_for_context(&mut buffer)?;
+
+    if config.enable_tracing {
         buffer.push_str("#include \"verilated_vcd_c.h\"\n");
         buffer.push_str("#include <stdint.h>\n");
     }
+    if config.savable {
+        buffer.push_str(MEMORY_SERIALIZATION);
+    }
+    if config.coverage.any() {
+        build_ffi_for_coverage(&mut buffer)?;
+    }
+    if config.public_signals.is_some() {
+        // the top module is a generated wrapper unless it is the only top
+        let wrapper_module = tops
+            .iter()
+            .any(|top| top.name != top_module)
+            .then_some(top_module);
+        build_ffi_for_signals(&mut buffer, wrapper_module)?;
+    }
+
+    let mut context_setup = String::new();
+    if let Some(threads) = config.threads {
+        context_setup += &format!("\n        contextp->threads({threads});");
+    }
+    if config.pin_threads {
+        context_setup += "\n        contextp->useNumaAssign(true);";
+    }
+    if config.enable_tracing {
+        context_setup += "\n        contextp->traceEverOn(true);";
+    }
+    if config.x_initial == Some(XInit::Unique) {
+        // randomize the initial values, which the arguments passed next can
+        // still override with +verilator+rand+reset+
+        context_setup += "\n        contextp->randReset(2);";
+    }
 
-    let new_top = ffi_names::new_top(top_module);
-    let top_eval = ffi_names::top_eval(top_module);
-    let delete_top = ffi_names::delete_top(top_module);
+    let report_failures = config.assertions;
+
+    let mut coverage_teardown = String::new();
+    if config.coverage.any() {
+        // the coverage counters live in the model, so they must be written
+        // before it is deleted
+        coverage_teardown +=
+            "\n        if (!contextp->coverage_path.empty()) {";
+        coverage_teardown += "\n            contextp->coveragep()->write(contextp->coverage_path.c_str());";
+        coverage_teardown += "\n        }";
+    }
 
     writeln!(
         &mut buffer,
         r#"
 #include <cstring> // std::memcpy
 #include "verilated.h"
-#include "V{top_module}.h"
+#include "{model_class}.h"
 
-extern "C" {{
-    void* {new_top}() {{
-        return new V{top_module}{{}};
+// compiled with VL_USER_STOP so that $stop ends the simulation like $finish
+// instead of aborting the process
+void vl_stop(const char* filename, int linenum, const char* hier) {{
+    Verilated::runFlushCallbacks();
+    MarlinContext* contextp = marlin_printing_context;
+    if ({report_failures} && contextp && !contextp->last_error.empty()) {{
+        // stopped by a failed assertion, $error, or $fatal
+        contextp->failure = contextp->last_error;
+    }} else {{
+        VL_PRINTF("- %s:%d: Verilog $stop\n", filename, linenum);
+    }}
+    Verilated::threadContextp()->gotError(true);
+    Verilated::threadContextp()->gotFinish(true);
+}}
+
+// compiled with VL_USER_FATAL so that a fatal error fails the model instead of
+// aborting the process
+void vl_fatal(const char* filename, int linenum, const char* hier, const char* msg) {{
+    Verilated::runFlushCallbacks();
+    MarlinContext* contextp = marlin_printing_context;
+    if (!contextp) {{
+        // not in an eval, so there is no model to fail
+        std::fprintf(stderr, "%%Error: %s:%d: %s\nAborting...\n", filename, linenum, msg);
+        std::abort();
+    }}
+    contextp->failure = "%Fatal: ";
+    if (filename && filename[0]) {{
+        contextp->failure += std::string(filename) + ":" + std::to_string(linenum) + ": ";
+    }}
+    contextp->failure += msg;
+    contextp->gotError(true);
+    contextp->gotFinish(true);
+}}
+
+extern "C" {{"#
+    )
+    .whatever_context::<_, Whatever>("Failed to format utility FFI")?;
+
+    if config.enable_tracing {
+        build_ffi_for_tracing(&mut buffer).whatever_context::<_, Whatever>(
+            "Failed to generate FFI bindings to Verilator tracing APIs",
+        )?;
+    }
+
+    if config.savable {
+        build_ffi_for_checkpoints(&mut buffer)
+            .whatever_context::<_, Whatever>(
+                "Failed to generate FFI bindings to Verilator save/restore APIs",
+            )?;
+    }
+
+    for top in tops {
+        let new_top = ffi_names::new_top(top.name);
+        let top_eval = ffi_names::top_eval(top.name);
+        let delete_top = ffi_names::delete_top(top.name);
+        let top_contextp = ffi_names::top_contextp(top.name);
+
+        writeln!(
+            &mut buffer,
+            r#"
+    void* {new_top}(int argc, const char** argv) {{
+        MarlinContext* contextp = new MarlinContext;{context_setup}
+        contextp->args.assign(argv, argv + argc);
+        contextp->commandArgs(argc, argv);
+        return new {model_class}{{contextp}};
     }}
 
     
-    void {top_eval}(V{top_module}* top) {{
+    int {top_eval}({model_class}* top) {{
+        MarlinContext* contextp = static_cast<MarlinContext*>(top->contextp());
+        Verilated::threadContextp(contextp);
+        marlin_printing_context = contextp;
+        contextp->last_error.clear();
         top->eval();
+        marlin_printing_context = nullptr;
+        int status = {CONTEXT_STATUS}(contextp);
+        if (!contextp->capture_output && !contextp->output.empty()) {{
+            status |= 4;
+        }}
+        return status;
     }}
 
-    void {delete_top}(V{top_module}* top) {{
+    void {delete_top}({model_class}* top) {{
+        MarlinContext* contextp = static_cast<MarlinContext*>(top->contextp());{coverage_teardown}
         delete top;
+        delete contextp;
     }}
-"#
-    )
-    .whatever_context("Failed to format utility FFI")?;
-
-    for (port, msb, lsb, direction) in ports {
-        let width = msb - lsb + 1;
-        let macro_prefix = match direction {
-            PortDirection::Input => "VL_IN",
-            PortDirection::Output => "VL_OUT",
-            PortDirection::Inout => "VL_INOUT",
-        };
-        let macro_suffix = if width <= 8 {
-            "8"
-        } else if width <= 16 {
-            "16"
-        } else if width <= 32 {
-            ""
-        } else if width <= 64 {
-            "64"
-        } else {
-            "W"
-        };
-        // Computes the C++ type for the port suitable for use in function
-        // parameters and return types.
-        let const_type_macro = |name: Option<&str>| {
-            let name_or_empty = name.unwrap_or("/* return value */");
-            if width <= 64 {
-                format!(
-                    "{macro_prefix}{macro_suffix}({name_or_empty}, {msb}, {lsb})",
-                )
-            } else {
-                format!("const WData* const {name_or_empty}")
-            }
-        };
 
-        let pin_port = ffi_names::pin_port(top_module, port);
-        let read_port = ffi_names::read_port(top_module, port);
+    VerilatedContext* {top_contextp}({model_class}* top) {{
+        return top->contextp();
+    }}
+"#
+        )
+        .whatever_context::<_, Whatever>("Failed to format utility FFI")?;
 
-        if matches!(direction, PortDirection::Input | PortDirection::Inout) {
-            let input_type = const_type_macro(Some("new_value"));
-            let pin_code = if width <= 64 {
-                format!("top->{port} = new_value;")
+        for (port, msb, lsb, direction) in &top.ports {
+            let width = msb - lsb + 1;
+            let macro_prefix = match direction {
+                PortDirection::Input => "VL_IN",
+                PortDirection::Output => "VL_OUT",
+                PortDirection::Inout => "VL_INOUT",
+            };
+            let macro_suffix = if width <= 8 {
+                "8"
+            } else if width <= 16 {
+                "16"
+            } else if width <= 32 {
+                ""
+            } else if width <= 64 {
+                "64"
             } else {
-                let word_count =
-                    compute_wdata_word_count_from_width_not_msb(width);
-                let bytes_to_copy = word_count * size_of::<types::WData>();
-                // https://en.cppreference.com/w/cpp/string/byte/memcpy
-                format!("std::memcpy(top->{port}, new_value, {bytes_to_copy});")
+                "W"
             };
+            // Computes the C++ type for the port suitable for use in function
+            // parameters and return types.
+            let const_type_macro = |name: Option<&str>| {
+                let name_or_empty = name.unwrap_or("/* return value */");
+                if width <= 64 {
+                    format!(
+                        "{macro_prefix}{macro_suffix}({name_or_empty}, {msb}, {lsb})",
+                    )
+                } else {
+                    format!("const WData* const {name_or_empty}")
+                }
+            };
+
+            let pin_port = ffi_names::pin_port(top.name, port);
+            let read_port = ffi_names::read_port(top.name, port);
+            let member = format!("{}{port}", top.port_prefix);
+
+            if matches!(direction, PortDirection::Input | PortDirection::Inout)
+            {
+                let input_type = const_type_macro(Some("new_value"));
+                let pin_code = if width <= 64 {
+                    format!("top->{member} = new_value;")
+                } else {
+                    let word_count =
+                        compute_wdata_word_count_from_width_not_msb(width);
+                    let bytes_to_copy = word_count * size_of::<types::WData>();
+                    // https://en.cppreference.com/w/cpp/string/byte/memcpy
+                    format!(
+                        "std::memcpy(top->{member}, new_value, {bytes_to_copy});"
+                    )
+                };
+                writeln!(
+                    &mut buffer,
+                    r#"
+    void {pin_port}({model_class}* top, {input_type}) {{
+        {pin_code}
+    }}
+            "#
+                )
+                .whatever_context::<_, Whatever>(
+                    "Failed to format input port FFI",
+                )?;
+            }
+
+            // every port can be read, e.g., to sample it for functional
+            // coverage
+            let to_pointer_if_wide = if width > 64 { ".data()" } else { "" };
+            let return_type = const_type_macro(None);
             writeln!(
                 &mut buffer,
                 r#"
-    void {pin_port}(V{top_module}* top, {input_type}) {{
-        {pin_code}
+    {return_type} {read_port}({model_class}* top) {{
+        return top->{member}{to_pointer_if_wide};
     }}
             "#
             )
-            .whatever_context("Failed to format input port FFI")?;
+            .whatever_context::<_, Whatever>(
+                "Failed to format port read FFI",
+            )?;
         }
 
-        if matches!(direction, PortDirection::Output | PortDirection::Inout) {
-            let to_pointer_if_wide = if width > 64 { ".data()" } else { "" };
-            let return_type = const_type_macro(None);
+        if config.enable_tracing {
+            build_ffi_for_top_tracing(&mut buffer, &model_class, top)
+                .whatever_context::<_, Whatever>(
+                "Failed to generate FFI bindings to Verilator tracing APIs",
+            )?;
+        }
+
+        if config.savable {
+            build_ffi_for_top_checkpoints(&mut buffer, &model_class, top)
+                .whatever_context::<_, Whatever>(
+                    "Failed to generate FFI bindings to Verilator save/restore APIs",
+                )?;
+        }
+
+        if config.timing {
+            let events_pending = ffi_names::top_events_pending(top.name);
+            let next_time_slot = ffi_names::top_next_time_slot(top.name);
             writeln!(
                 &mut buffer,
                 r#"
-    {return_type} {read_port}(V{top_module}* top) {{
-        return top->{port}{to_pointer_if_wide};
+    bool {events_pending}({model_class}* top) {{
+        return top->eventsPending();
     }}
-            "#
+
+    uint64_t {next_time_slot}({model_class}* top) {{
+        return top->nextTimeSlot();
+    }}
+"#
             )
-            .whatever_context("Failed to format output port FFI")?;
+            .whatever_context::<_, Whatever>("Failed to format timing FFI")?;
         }
     }
 
-    if enable_tracing {
-        build_ffi_for_tracing(&mut buffer, top_module).whatever_context(
-            "Failed to generate FFI bindings to Verilator tracing APIs",
+    writeln!(&mut buffer, "}} // extern \"C\"")
+        .whatever_context::<_, Whatever>("Failed to format ending brace")?;
+
+    // only rewrite if there's been a change
+    if fs::read_to_string(&ffi_wrappers)
+        .map(|current_buffer| current_buffer != buffer)
+        .unwrap_or(true)
+    {
+        fs::write(&ffi_wrappers, buffer).whatever_context::<_, Whatever>(
+            "Failed to write FFI wrappers file",
         )?;
     }
 
-    writeln!(&mut buffer, "}} // extern \"C\"")
-        .whatever_context("Failed to format ending brace")?;
+    Ok(ffi_wrappers)
+}
 
-    fs::write(&ffi_wrappers, buffer)
-        .whatever_context("Failed to write FFI wrappers file")?;
+/// Writes a module instantiating every member of `group` to the given
+/// artifact directory `artifact_directory`, returning its path. The module is
+/// named [`ModelGroup::wrapper_module`] and has a port for each port of each
+/// member, named by prefixing it with [`ModelGroup::wrapper_port_prefix`].
+fn build_group_wrapper(
+    artifact_directory: &Utf8Path,
+    group: &ModelGroup,
+) -> Result<Utf8PathBuf, Whatever> {
+    let wrapper_module = group.wrapper_module();
+    let wrapper_path = artifact_directory.join(format!("{wrapper_module}.sv"));
 
-    Ok(ffi_wrappers)
+    let mut wrapper_ports = vec![];
+    let mut instances = String::new();
+    for (index, member) in group.members.iter().enumerate() {
+        let port_prefix = ModelGroup::wrapper_port_prefix(index);
+        let mut connections = vec![];
+        for (port, high, low, direction) in &member.ports {
+            let direction = match direction {
+                PortDirection::Input => "input",
+                PortDirection::Output => "output",
+                PortDirection::Inout => "inout",
+            };
+            wrapper_ports.push(format!(
+                "    {direction} wire [{high}:{low}] {port_prefix}{port}"
+            ));
+            connections.push(format!("        .{port}({port_prefix}{port})"));
+        }
+        let parameters = if member.parameters.is_empty() {
+            String::new()
+        } else {
+            let overrides = member
+                .parameters
+                .iter()
+                .map(|(parameter, value)| format!(".{parameter}({value})"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(" #({overrides})")
+        };
+        writeln!(
+            &mut instances,
+            "    {name}{parameters} {name} (\n{}\n    );",
+            connections.join(",\n"),
+            name = member.name,
+        )
+        .whatever_context::<_, Whatever>("Failed to format group instance")?;
+    }
+
+    let buffer = format!(
+        "// generated for the model group {}\nmodule {wrapper_module} (\n{}\n);\n{instances}endmodule\n",
+        group.name,
+        wrapper_ports.join(",\n")
+    );
+    if fs::read_to_string(&wrapper_path)
+        .map(|current_buffer| current_buffer != buffer)
+        .unwrap_or(true)
+    {
+        fs::write(&wrapper_path, buffer).whatever_context::<_, Whatever>(
+            "Failed to write model group wrapper file",
+        )?;
+    }
+    Ok(wrapper_path)
 }
 
 /// Sets up the DPI artifacts directory and generates DPI function bindings if
@@ -312,55 +1102,37 @@ extern \"C\" void {name}({signature}) {{
     }
 
     fs::write(dpi_artifact_directory.join("dpi.cpp"), file_code)
-        .whatever_context(format!(
+        .whatever_context::<_, Whatever>(format!(
         "Failed to write DPI function wrapper code to {dpi_file_absolute_path}"
     ))?;
 
     Ok((Some(dpi_file), true))
 }
 
-/// Returns `Ok(true)` when the library doesn't exist or if any Verilog source
-/// file has been modified after last building the library.
-fn needs_verilator_rebuild(
-    source_files: &[Utf8PathBuf],
-    library_path: &Utf8Path,
-) -> Result<bool, Whatever> {
-    if !library_path.exists() {
-        return Ok(true);
-    }
-
-    let last_built = fs::metadata(library_path)
-        .whatever_context(format!(
-            "Failed to read file metadata for dynamic library {library_path}"
-        ))?
-        .modified()
-        .whatever_context(format!(
-            "Failed to determine last-modified time for dynamic library {library_path}"
-        ))?;
-
-    for source_file in source_files {
-        let last_edited = fs::metadata(source_file)
-            .whatever_context(format!(
-                "Failed to read file metadata for source file {source_file}"
-            ))?
-            .modified()
-            .whatever_context(format!(
-                "Failed to determine last-modified time for source file {source_file}"
-            ))?;
-        if last_edited > last_built {
-            return Ok(true);
-        }
-    }
-
-    Ok(false)
+/// The modules a library is built for.
+#[derive(Clone, Copy)]
+pub enum LibraryTops<'a> {
+    /// A single module, which is the top module of the verilated model.
+    Module {
+        name: &'a str,
+        ports: &'a [(&'a str, usize, usize, PortDirection)],
+    },
+    /// The members of a group, instantiated in a generated top module.
+    Group(&'a ModelGroup),
 }
 
 /// Builds a dynamic library using Verilator serving as the runtime for the
 /// specified Verilog module. If DPI functions are given, `rustc` compiles them
 /// before they are linked with the library.
 ///
-/// First, we set up the artifact directories (let us assume the top-level
-/// directory is called "artifacts"):
+/// If `options.prebuilt_only`, Verilator is never invoked: the library built
+/// previously in `artifact_directory` is returned if its build manifest is up
+/// to date, and otherwise this function fails. Otherwise, `verilator` must
+/// describe the `options.verilator_executable`, and the flags passed to it
+/// are chosen according to its version.
+///
+/// Otherwise, we first set up the artifact directories (let us assume the
+/// top-level directory is called "artifacts"):
 /// ```text
 /// artifacts/
 /// ├─ ffi/
@@ -372,16 +1144,21 @@ fn needs_verilator_rebuild(
 /// [`build_dpi_if_needed`]). It is important that this function is a nop when
 /// there no DPI functions because invoking `rustc` takes a long time.
 ///
-/// Then, if the DPI files were rebuilt, any Verilog source code has been
-/// edited, or the `options` force rebuilding, we proceed in (re)building the
-/// dynamic library. Otherwise, the function returns the library path
-/// immediately here.
+/// Next, the FFI wrappers are regenerated (see [`build_ffi`]) and the
+/// Verilator invocation is assembled.
 ///
-/// Next, the FFI wrappers are rebuilt (although we could probably be smarter
-/// about this and only rebuild if the module's source file was edited).
+/// Then, unless the `options` force rebuilding, we compare against the build
+/// manifest left by the previous build (see [`BuildManifest`]): if the
+/// Verilator version, the invocation, and the contents of every file the
+/// previous build depended on are unchanged, the function returns the library
+/// path immediately here.
 ///
-/// Finally, we invoke `verilator` and return the library path as well as
-/// whether the library was rebuilt.
+/// Finally, we invoke `verilator`, record a new manifest, and return the
+/// library path as well as a [`BuildReport`] with the diagnostics Verilator
+/// emitted.
+///
+/// For a [`ModelGroup`], the top module is a wrapper instantiating every
+/// member (see [`build_group_wrapper`]), and FFI is generated for each member.
 ///
 /// This function is not thread-safe; the `artifact_directory` must be guarded.
 ///
@@ -392,70 +1169,138 @@ pub fn build_library(
     source_files: &[Utf8PathBuf],
     include_directories: &[Utf8PathBuf],
     dpi_functions: &[&'static dyn DpiFunction],
-    top_module: &str,
-    ports: &[(&str, usize, usize, PortDirection)],
+    tops: LibraryTops,
     artifact_directory: &Utf8Path,
+    verilator: Option<&VerilatorInfo>,
     options: &VerilatorRuntimeOptions,
     config: &VerilatedModelConfig,
     verbose: bool,
     on_rebuild: impl FnOnce() -> Result<(), Whatever>,
-) -> Result<(Utf8PathBuf, bool), Whatever> {
+) -> Result<(Utf8PathBuf, BuildReport), VerilatorRuntimeError> {
     if verbose {
         log::info!("| Preparing artifacts directory");
     }
 
     let ffi_artifact_directory = artifact_directory.join("ffi");
-    fs::create_dir_all(&ffi_artifact_directory).whatever_context(
-        "Failed to create ffi/ subdirectory under artifacts directory",
-    )?;
+    fs::create_dir_all(&ffi_artifact_directory)
+        .whatever_context::<_, Whatever>(
+            "Failed to create ffi/ subdirectory under artifacts directory",
+        )?;
     let verilator_artifact_directory = artifact_directory.join("obj_dir");
     let dpi_artifact_directory = artifact_directory.join("dpi");
-    fs::create_dir_all(&dpi_artifact_directory).whatever_context(
-        "Failed to create dpi/ subdirectory under artifacts directory",
-    )?;
+    fs::create_dir_all(&dpi_artifact_directory)
+        .whatever_context::<_, Whatever>(
+            "Failed to create dpi/ subdirectory under artifacts directory",
+        )?;
+    let top_module = match tops {
+        LibraryTops::Module { name, .. } => name.to_string(),
+        LibraryTops::Group(group) => group.wrapper_module(),
+    };
+    let top_module = top_module.as_str();
     let library_name = format!("marlin_V{top_module}");
     let library_path =
         verilator_artifact_directory.join(format!("lib{library_name}.so"));
 
-    let (dpi_file, dpi_rebuilt) = bind_dpi_if_needed(
-        top_module,
-        dpi_functions,
-        &dpi_artifact_directory,
-        verbose,
-    )
-    .whatever_context("Failed to build DPI functions")?;
-
-    if !options.force_verilator_rebuild
-        && (!needs_verilator_rebuild(
-            source_files,
-            &verilator_artifact_directory,
-        )
-        .whatever_context("Failed to check if artifacts need rebuilding")?
-            && !dpi_rebuilt)
-    {
+    if options.prebuilt_only {
+        let Some(manifest) = BuildManifest::read(artifact_directory)
+            .filter(|_| library_path.exists())
+        else {
+            return PrebuiltUnavailableSnafu {
+                top_module,
+                directory: artifact_directory,
+                reason: "it was not built ahead of time with the same configuration",
+            }
+            .fail();
+        };
+        if let Some(reason) = manifest.changed_dependency() {
+            return PrebuiltUnavailableSnafu {
+                top_module,
+                directory: artifact_directory,
+                reason,
+            }
+            .fail();
+        }
         if verbose {
-            log::info!(
-                "| Skipping rebuild of verilated model due to no changes"
-            );
+            log::info!("| Using prebuilt library");
         }
-        return Ok((library_path, false));
+        return Ok((
+            library_path,
+            BuildReport {
+                dependencies: manifest.dependencies(),
+                ..Default::default()
+            },
+        ));
     }
 
-    on_rebuild()?;
+    let verilator = verilator.expect(
+        "Verilator is detected unless only prebuilt libraries are used",
+    );
 
-    let _ffi_wrappers = build_ffi(
-        &ffi_artifact_directory,
+    let (dpi_file, dpi_rebuilt) = bind_dpi_if_needed(
         top_module,
-        ports,
-        config.enable_tracing,
+        dpi_functions,
+        &dpi_artifact_directory,
+        verbose,
     )
-    .whatever_context("Failed to build FFI wrappers")?;
+    .whatever_context::<_, Whatever>("Failed to build DPI functions")?;
+
+    let (ffi_tops, group_wrapper) = match tops {
+        LibraryTops::Module { name, ports } => (
+            vec![FfiTop {
+                name,
+                ports: ports.to_vec(),
+                port_prefix: String::new(),
+            }],
+            None,
+        ),
+        LibraryTops::Group(group) => (
+            group
+                .members
+                .iter()
+                .enumerate()
+                .map(|(index, member)| FfiTop {
+                    name: &member.name,
+                    ports: member.ports(),
+                    port_prefix: ModelGroup::wrapper_port_prefix(index),
+                })
+                .collect(),
+            Some(
+                build_group_wrapper(&ffi_artifact_directory, group)
+                    .whatever_context::<_, Whatever>(
+                        "Failed to build model group wrapper",
+                    )?,
+            ),
+        ),
+    };
+    let ffi_wrappers_absolute_path =
+        build_ffi(&ffi_artifact_directory, top_module, &ffi_tops, config)
+            .whatever_context::<_, Whatever>("Failed to build FFI wrappers")?;
+    let print_header_absolute_path =
+        build_print_header(&ffi_artifact_directory)
+            .whatever_context::<_, Whatever>("Failed to build print header")?;
 
     // bug in verilator#5226 means the directory must be relative to -Mdir
     let ffi_wrappers = Utf8Path::new("../ffi/ffi.cpp");
 
-    let mut cflags = "-shared -fpic".to_string();
-    if let Some(cxx_standard) = config.cxx_standard {
+    let mut cflags = format!(
+        "-shared -fpic -DVL_USER_STOP -DVL_USER_FATAL -DVL_PRINTF={VL_PRINTF} -DVL_VPRINTF={VL_VPRINTF} -include ../ffi/{PRINT_HEADER}"
+    );
+    let mut cxx_standard = config.cxx_standard;
+    if config.timing
+        && matches!(
+            cxx_standard,
+            Some(
+                crate::CxxStandard::Cxx98
+                    | crate::CxxStandard::Cxx11
+                    | crate::CxxStandard::Cxx14
+                    | crate::CxxStandard::Cxx17
+            )
+        )
+    {
+        // the coroutines `--timing` relies on are from C++20
+        cxx_standard = Some(crate::CxxStandard::Cxx20);
+    }
+    if let Some(cxx_standard) = cxx_standard {
         cflags += " -std=";
         cflags += match cxx_standard {
             crate::CxxStandard::Cxx98 => "c++98",
@@ -468,6 +1313,8 @@ pub fn build_library(
         };
     }
 
+    // flags passed unconditionally are supported by every version of
+    // Verilator that passes the check in `VerilatorInfo`
     let mut verilator_command = Command::new(&options.verilator_executable);
     verilator_command
         .args(["--cc", "-sv", "-j", "0", "--build"])
@@ -476,11 +1323,21 @@ pub fn build_library(
         .args(["--Mdir", verilator_artifact_directory.as_str()])
         .args(["--top-module", top_module])
         .args(source_files)
+        .args(&group_wrapper)
         .arg(ffi_wrappers);
-    for include_directory in include_directories {
+    for include_directory in include_directories
+        .iter()
+        .chain(&config.include_directories)
+    {
         verilator_command.arg(format!("-I{include_directory}"));
     }
-    if let Some(dpi_file) = dpi_file {
+    for (define, text) in &config.defines {
+        match text {
+            Some(text) => verilator_command.arg(format!("-D{define}={text}")),
+            None => verilator_command.arg(format!("-D{define}")),
+        };
+    }
+    if let Some(dpi_file) = &dpi_file {
         verilator_command.arg(dpi_file);
     }
     if config.verilator_optimization != 0 {
@@ -488,30 +1345,160 @@ pub fn build_library(
         if (1..=3).contains(&level) {
             verilator_command.arg(format!("-O{level}"));
         } else {
-            whatever!("Invalid Verilator optimization level: {}", level);
+            return InvalidConfigurationSnafu {
+                message: format!(
+                    "Invalid Verilator optimization level: {level}"
+                ),
+            }
+            .fail();
         }
     }
+    for (parameter, value) in &config.parameters {
+        verilator_command.arg(format!("-G{parameter}={value}"));
+    }
     for ignored_warning in &config.ignored_warnings {
         verilator_command.arg(format!("-Wno-{ignored_warning}"));
     }
     if config.enable_tracing {
-        verilator_command.arg("--trace");
+        verilator_command.arg(verilator.trace_flag());
+    }
+    if config.savable {
+        verilator_command.arg("--savable");
+    }
+    if config.assertions {
+        verilator_command.arg("--assert");
+    }
+    if config.timing {
+        verilator_command.arg("--timing");
+    }
+    if let Some(public_signals) = config.public_signals {
+        verilator_command.arg("--vpi");
+        if public_signals == PublicSignals::All {
+            verilator_command.arg("--public-flat-rw");
+        }
+    }
+    if config.coverage.line {
+        verilator_command.arg("--coverage-line");
+    }
+    if config.coverage.toggle {
+        verilator_command.arg("--coverage-toggle");
+    }
+    if config.coverage.user {
+        verilator_command.arg("--coverage-user");
+    }
+    if let Some(threads) = config.threads {
+        if threads == 0 {
+            return InvalidConfigurationSnafu {
+                message: "The number of threads must be positive",
+            }
+            .fail();
+        }
+        verilator_command.args(["--threads", &threads.to_string()]);
     }
+    if let Some(x_assign) = config.x_assign {
+        verilator_command.args(["--x-assign", x_assign.as_flag()]);
+    }
+    if let Some(x_initial) = config.x_initial {
+        verilator_command.args(["--x-initial", x_initial.as_flag()]);
+    }
+    if let Some(threads_dpi) = config.threads_dpi {
+        verilator_command.args([
+            "--threads-dpi",
+            match threads_dpi {
+                ThreadsDpi::All => "all",
+                ThreadsDpi::None => "none",
+                ThreadsDpi::Pure => "pure",
+            },
+        ]);
+    }
+
+    let command_hash = manifest::hash_command(&verilator_command);
+
+    if !options.force_verilator_rebuild && !dpi_rebuilt && library_path.exists()
+    {
+        match BuildManifest::read(artifact_directory) {
+            Some(manifest) => {
+                match manifest
+                    .staleness(&verilator.version_string, command_hash)
+                {
+                    None => {
+                        if verbose {
+                            log::info!(
+                                "| Skipping rebuild of verilated model due to no changes"
+                            );
+                        }
+                        return Ok((
+                            library_path,
+                            BuildReport {
+                                dependencies: manifest.dependencies(),
+                                ..Default::default()
+                            },
+                        ));
+                    }
+                    Some(reason) => {
+                        if verbose {
+                            log::info!("| Rebuilding because {reason}");
+                        }
+                    }
+                }
+            }
+            None => {
+                if verbose {
+                    log::info!("| Rebuilding because there is no manifest");
+                }
+            }
+        }
+    }
+
+    on_rebuild()?;
+
+    BuildManifest::invalidate(artifact_directory)?;
+
     if verbose {
         log::info!("| Verilator invocation: {:?}", verilator_command);
     }
-    let verilator_output = verilator_command
-        .output()
-        .whatever_context("Invocation of Verilator failed")?;
+    let verilator_output =
+        verilator_command.output().context(VerilatorNotFoundSnafu {
+            executable: options.verilator_executable.to_string_lossy(),
+        })?;
+
+    let stdout = String::from_utf8(verilator_output.stdout).unwrap_or_default();
+    let stderr = String::from_utf8(verilator_output.stderr).unwrap_or_default();
+    let mut diagnostics = diagnostics::parse_diagnostics(&stderr);
+    diagnostics.extend(diagnostics::parse_diagnostics(&stdout));
 
     if !verilator_output.status.success() {
-        whatever!(
-            "Invocation of verilator failed with nonzero exit code {}\n\n--- STDOUT ---\n{}\n\n--- STDERR ---\n{}",
-            verilator_output.status,
-            String::from_utf8(verilator_output.stdout).unwrap_or_default(),
-            String::from_utf8(verilator_output.stderr).unwrap_or_default()
-        );
+        return VerilatorFailedSnafu {
+            top_module,
+            status: verilator_output.status,
+            stdout,
+            stderr,
+            diagnostics,
+        }
+        .fail();
+    }
+
+    let mut extra_dependencies =
+        vec![ffi_wrappers_absolute_path, print_header_absolute_path];
+    if dpi_file.is_some() {
+        extra_dependencies.push(dpi_artifact_directory.join("dpi.cpp"));
     }
+    extra_dependencies.extend(group_wrapper);
+    let manifest = BuildManifest::record(
+        verilator.version_string.clone(),
+        command_hash,
+        &verilator_artifact_directory.join(format!("V{top_module}__ver.d")),
+        &extra_dependencies,
+    )
+    .whatever_context::<_, Whatever>("Failed to record build manifest")?;
+    manifest.write(artifact_directory)?;
 
-    Ok((library_path, true))
+    Ok((
+        library_path,
+        BuildReport {
+            rebuilt: true,
+            diagnostics,
+            dependencies: manifest.dependencies(),
+        },
+    ))
 }
@@ -0,0 +1,293 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Inspecting and pruning the libraries cached in an artifact directory.
+//!
+//! Each library lives in its own subdirectory of the artifact directory,
+//! alongside a `.lock` file of the same name used to synchronize builds. When
+//! a library is built or reused, its key (module, source path, ports, and
+//! configuration) and the time it was last used are recorded in the
+//! subdirectory.
+
+use std::{
+    collections::HashSet,
+    fs,
+    time::{Duration, SystemTime},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use snafu::{ResultExt, Whatever};
+
+use crate::{
+    PortDirection, THREAD_LOCKS_PER_BUILD_DIR, VerilatedModelConfig,
+    VerilatorRuntimeError,
+};
+
+const KEY_FILE_NAME: &str = "marlin-key.txt";
+const LAST_USED_FILE_NAME: &str = "marlin-last-used.txt";
+
+/// What a cached library was built for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedLibraryKey {
+    /// The name of the top module.
+    pub module_name: String,
+    /// The canonicalized source path of the top module.
+    pub source_path: String,
+    /// The ports of the model, formatted with [`std::fmt::Debug`].
+    pub ports: String,
+    /// The [`VerilatedModelConfig`] of the model, formatted with
+    /// [`std::fmt::Debug`].
+    pub config: String,
+}
+
+/// A library cached in an artifact directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedLibrary {
+    /// The subdirectory of the artifact directory holding the library.
+    pub directory: Utf8PathBuf,
+    /// The key of the library, or `None` if it was not recorded (e.g., it was
+    /// built by an older version of Marlin).
+    pub key: Option<CachedLibraryKey>,
+    /// The total size in bytes of the files in [`CachedLibrary::directory`].
+    pub size: u64,
+    /// When the library was last built or loaded, falling back to the
+    /// last-modified time of [`CachedLibrary::directory`] if that was not
+    /// recorded.
+    pub last_used: Option<SystemTime>,
+}
+
+/// Which entries [`prune_cached_libraries`] should remove. An entry is removed
+/// if any of the enabled criteria apply.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneOptions {
+    /// Remove entries not used for at least this long.
+    pub older_than: Option<Duration>,
+
+    /// Remove entries whose source file no longer exists or whose key was not
+    /// recorded.
+    pub unreferenced: bool,
+}
+
+/// Records that the library in `directory` was just built or loaded.
+pub(crate) fn record_use(
+    directory: &Utf8Path,
+    name: &str,
+    source_path: &str,
+    ports: &[(&str, usize, usize, PortDirection)],
+    config: &VerilatedModelConfig,
+) -> Result<(), Whatever> {
+    let source_path = Utf8Path::new(source_path)
+        .canonicalize_utf8()
+        .unwrap_or_else(|_| source_path.into());
+    let key_path = directory.join(KEY_FILE_NAME);
+    fs::write(
+        &key_path,
+        format!(
+            "module {name}\nsource {source_path}\nports {ports:?}\nconfig {config:?}\n"
+        ),
+    )
+    .whatever_context::<_, Whatever>(format!(
+        "Failed to write cache key {key_path}"
+    ))?;
+
+    let last_used = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let last_used_path = directory.join(LAST_USED_FILE_NAME);
+    fs::write(&last_used_path, last_used.to_string()).whatever_context(format!(
+        "Failed to write last-used time {last_used_path}"
+    ))
+}
+
+fn read_key(directory: &Utf8Path) -> Option<CachedLibraryKey> {
+    let contents = fs::read_to_string(directory.join(KEY_FILE_NAME)).ok()?;
+    let mut lines = contents.lines();
+    Some(CachedLibraryKey {
+        module_name: lines.next()?.strip_prefix("module ")?.to_string(),
+        source_path: lines.next()?.strip_prefix("source ")?.to_string(),
+        ports: lines.next()?.strip_prefix("ports ")?.to_string(),
+        config: lines.next()?.strip_prefix("config ")?.to_string(),
+    })
+}
+
+fn read_last_used(directory: &Utf8Path) -> Option<SystemTime> {
+    fs::read_to_string(directory.join(LAST_USED_FILE_NAME))
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+        .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+        .or_else(|| fs::metadata(directory).ok()?.modified().ok())
+}
+
+fn directory_size(directory: &Utf8Path) -> Result<u64, Whatever> {
+    let mut size = 0;
+    for entry in
+        directory
+            .read_dir_utf8()
+            .whatever_context::<_, Whatever>(format!(
+                "Failed to read directory {directory}"
+            ))?
+    {
+        let entry = entry.whatever_context::<_, Whatever>(format!(
+            "Failed to read entry in {directory}"
+        ))?;
+        let metadata = entry.metadata().whatever_context::<_, Whatever>(
+            format!("Failed to read file metadata for {}", entry.path()),
+        )?;
+        if metadata.is_dir() {
+            size += directory_size(entry.path())?;
+        } else {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
+
+fn lockfile_path(directory: &Utf8Path) -> Utf8PathBuf {
+    directory.with_file_name(format!(
+        "{}.lock",
+        directory.file_name().unwrap_or_default()
+    ))
+}
+
+fn list(artifact_directory: &Utf8Path) -> Result<Vec<CachedLibrary>, Whatever> {
+    if !artifact_directory.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut libraries = vec![];
+    for entry in artifact_directory
+        .read_dir_utf8()
+        .whatever_context::<_, Whatever>(format!(
+            "Failed to read artifact directory {artifact_directory}"
+        ))?
+    {
+        let entry = entry.whatever_context::<_, Whatever>(format!(
+            "Failed to read entry in {artifact_directory}"
+        ))?;
+        let directory = entry.path();
+        if !directory.is_dir()
+            || !(lockfile_path(directory).exists()
+                || directory.join(KEY_FILE_NAME).exists())
+        {
+            continue;
+        }
+
+        libraries.push(CachedLibrary {
+            directory: directory.to_path_buf(),
+            key: read_key(directory),
+            size: directory_size(directory)?,
+            last_used: read_last_used(directory),
+        });
+    }
+    libraries.sort_by(|a, b| a.directory.cmp(&b.directory));
+    Ok(libraries)
+}
+
+/// Lists the libraries cached in `artifact_directory`.
+pub fn list_cached_libraries(
+    artifact_directory: &Utf8Path,
+) -> Result<Vec<CachedLibrary>, VerilatorRuntimeError> {
+    Ok(list(artifact_directory)?)
+}
+
+fn should_prune(library: &CachedLibrary, options: &PruneOptions) -> bool {
+    if let Some(older_than) = options.older_than {
+        let unused_for = library
+            .last_used
+            .and_then(|last_used| last_used.elapsed().ok())
+            .unwrap_or(Duration::MAX);
+        if unused_for >= older_than {
+            return true;
+        }
+    }
+
+    options.unreferenced
+        && library
+            .key
+            .as_ref()
+            .is_none_or(|key| !Utf8Path::new(&key.source_path).exists())
+}
+
+/// Removes the library in `directory` if no thread or process is currently
+/// building it, returning whether it was removed. The lockfile is left in
+/// place: unlinking it while holding the lock would let another process lock
+/// a new file of the same name and build concurrently with one still waiting
+/// on the old one.
+fn remove_if_unlocked(directory: &Utf8Path) -> Result<bool, Whatever> {
+    if !THREAD_LOCKS_PER_BUILD_DIR.contains_key(directory) {
+        THREAD_LOCKS_PER_BUILD_DIR
+            .insert(directory.to_path_buf(), Default::default());
+    }
+    let thread_mutex = THREAD_LOCKS_PER_BUILD_DIR
+        .get(directory)
+        .expect("We just inserted if it didn't exist");
+    let Ok(_thread_lock) = thread_mutex.try_lock() else {
+        return Ok(false);
+    };
+
+    let lockfile_path = lockfile_path(directory);
+    let lockfile = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&lockfile_path)
+        .whatever_context::<_, Whatever>(format!(
+            "Failed to open lockfile {lockfile_path} (this is not the actual lock itself, it is an I/O error)"
+        ))?;
+    let Ok(_file_lock) =
+        file_guard::try_lock(&lockfile, file_guard::Lock::Exclusive, 0, 1)
+    else {
+        return Ok(false);
+    };
+
+    if directory.exists() {
+        fs::remove_dir_all(directory).whatever_context::<_, Whatever>(
+            format!("Failed to remove cached library {directory}"),
+        )?;
+    }
+    Ok(true)
+}
+
+/// Removes the libraries cached in `artifact_directory` selected by
+/// `options`, skipping any in `in_use` (directory names) or currently locked
+/// for building. Returns the removed libraries.
+pub(crate) fn prune(
+    artifact_directory: &Utf8Path,
+    options: &PruneOptions,
+    in_use: &HashSet<String>,
+) -> Result<Vec<CachedLibrary>, Whatever> {
+    let mut removed = vec![];
+    for library in list(artifact_directory)? {
+        let in_use = library
+            .directory
+            .file_name()
+            .is_some_and(|name| in_use.contains(name));
+        if !in_use
+            && should_prune(&library, options)
+            && remove_if_unlocked(&library.directory)?
+        {
+            removed.push(library);
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Removes the libraries cached in `artifact_directory` selected by
+/// `options`, returning the removed libraries. Libraries that are currently
+/// being built are skipped.
+///
+/// This function is thread-safe and process-safe, but libraries already loaded
+/// by a [`crate::VerilatorRuntime`] may be removed; use
+/// [`crate::VerilatorRuntime::prune_cache`] to keep those.
+pub fn prune_cached_libraries(
+    artifact_directory: &Utf8Path,
+    options: &PruneOptions,
+) -> Result<Vec<CachedLibrary>, VerilatorRuntimeError> {
+    Ok(prune(artifact_directory, options, &HashSet::new())?)
+}
@@ -0,0 +1,115 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Saving and restoring the state of models built with
+//! [`crate::VerilatedModelConfig::savable`].
+//!
+//! A checkpoint is tagged with a hash of the build of the library that saved
+//! it (the Verilator version, flags, and the contents of every input file), and
+//! can only be restored into a model loaded from the same build.
+
+#[doc(hidden)]
+pub mod __private {
+    use std::{ffi, path::Path};
+
+    use libloading::Library;
+    use snafu::ensure;
+
+    use crate::{
+        VerilatorRuntimeError,
+        error::{CheckpointMismatchSnafu, CheckpointSnafu, NotSavableSnafu},
+        ffi_names,
+    };
+
+    #[derive(Clone, Copy)]
+    pub struct CheckpointApi {
+        save: extern "C" fn(*mut ffi::c_void, *const ffi::c_char) -> ffi::c_int,
+        restore:
+            extern "C" fn(*mut ffi::c_void, *const ffi::c_char) -> ffi::c_int,
+        fork: extern "C" fn(*mut ffi::c_void) -> *mut ffi::c_void,
+    }
+
+    impl CheckpointApi {
+        /// Loads the checkpoint functions for the model `top_module` from its
+        /// library, or returns `None` if it was not built to be savable.
+        pub fn load(library: &Library, top_module: &str) -> Option<Self> {
+            // SAFETY: these symbols are generated with these signatures by
+            // `build_library::build_ffi_for_checkpoints`.
+            unsafe {
+                Some(Self {
+                    save: *library
+                        .get(ffi_names::top_save(top_module).as_bytes())
+                        .ok()?,
+                    restore: *library
+                        .get(ffi_names::top_restore(top_module).as_bytes())
+                        .ok()?,
+                    fork: *library
+                        .get(ffi_names::top_fork(top_module).as_bytes())
+                        .ok()?,
+                })
+            }
+        }
+
+        /// Creates a new model with the state of `model`.
+        pub(crate) fn fork(&self, model: *mut ffi::c_void) -> *mut ffi::c_void {
+            (self.fork)(model)
+        }
+    }
+
+    fn c_path(path: &Path) -> ffi::CString {
+        ffi::CString::new(path.as_os_str().as_encoded_bytes())
+            .expect("Failed to convert provided checkpoint path to C string")
+    }
+
+    /// Saves the state of `model` to `path`.
+    pub fn save(
+        api: Option<&CheckpointApi>,
+        model: *mut ffi::c_void,
+        top_module: &str,
+        path: &Path,
+    ) -> Result<(), VerilatorRuntimeError> {
+        let Some(api) = api else {
+            return NotSavableSnafu { top_module }.fail();
+        };
+        ensure!(
+            (api.save)(model, c_path(path).as_ptr()) == 0,
+            CheckpointSnafu {
+                path,
+                reason: "the file could not be opened for writing",
+            }
+        );
+        Ok(())
+    }
+
+    /// Restores the state of `model` from `path`.
+    pub fn restore(
+        api: Option<&CheckpointApi>,
+        model: *mut ffi::c_void,
+        top_module: &str,
+        path: &Path,
+    ) -> Result<(), VerilatorRuntimeError> {
+        let Some(api) = api else {
+            return NotSavableSnafu { top_module }.fail();
+        };
+        // Verilator aborts the process if the file cannot be opened
+        ensure!(
+            path.is_file(),
+            CheckpointSnafu {
+                path,
+                reason: "the file does not exist",
+            }
+        );
+        match (api.restore)(model, c_path(path).as_ptr()) {
+            0 => Ok(()),
+            2 => CheckpointMismatchSnafu { top_module, path }.fail(),
+            _ => CheckpointSnafu {
+                path,
+                reason: "the file could not be opened for reading",
+            }
+            .fail(),
+        }
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Project-level defaults for runtimes and models, so that changing how tests
+//! build models (e.g., enabling tracing for one run) does not require editing
+//! every test.
+//!
+//! The defaults are read from a `marlin.toml` next to the `Cargo.toml` of the
+//! package, every key of which is optional:
+//!
+//! ```toml
+//! artifact-directory = "artifacts"
+//! verilator = "/opt/verilator/bin/verilator"
+//! force-rebuild = false
+//! log = true
+//! optimization = 3
+//! tracing = true
+//! ignored-warnings = ["WIDTH", "UNUSED"]
+//! ```
+//!
+//! Each key can be overridden by an environment variable, e.g.,
+//! `MARLIN_TRACE=1 cargo test`:
+//!
+//! | Key                  | Environment variable        |
+//! | -------------------- | --------------------------- |
+//! | `artifact-directory` | `MARLIN_ARTIFACT_DIRECTORY` |
+//! | `verilator`          | `MARLIN_VERILATOR`          |
+//! | `force-rebuild`      | `MARLIN_FORCE_REBUILD`      |
+//! | `log`                | `MARLIN_LOG`                |
+//! | `optimization`       | `MARLIN_OPTIMIZATION`       |
+//! | `tracing`            | `MARLIN_TRACE`              |
+//! | `ignored-warnings`   | `MARLIN_IGNORED_WARNINGS`   |
+//!
+//! Booleans are written `1`/`true` or `0`/`false` in environment variables,
+//! and `MARLIN_IGNORED_WARNINGS` is a comma-separated list.
+//!
+//! [`crate::VerilatorRuntime::from_env`] and
+//! [`crate::VerilatorRuntime::create_model_simple`] use these defaults, as do
+//! [`crate::VerilatorRuntimeOptions::from_env`] and
+//! [`crate::VerilatedModelConfig::from_env`] for callers that opt in.
+
+use std::{env, fs};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use snafu::{ResultExt, Whatever};
+
+use crate::{
+    VerilatedModelConfig, VerilatorRuntimeError, VerilatorRuntimeOptions,
+    error::InvalidConfigurationSnafu,
+};
+
+/// The name of the file [`ProjectConfig::discover`] reads.
+pub const PROJECT_CONFIG_FILE_NAME: &str = "marlin.toml";
+
+/// Defaults for runtimes and models. See the [module documentation](self).
+///
+/// Fields that are `None` keep the defaults of [`VerilatorRuntimeOptions`]
+/// and [`VerilatedModelConfig`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectConfig {
+    /// The `marlin.toml` this configuration was read from, if any.
+    pub path: Option<Utf8PathBuf>,
+    /// The artifact directory of runtimes. A relative path in a `marlin.toml`
+    /// is relative to the directory containing it.
+    pub artifact_directory: Option<Utf8PathBuf>,
+    /// See [`VerilatorRuntimeOptions::verilator_executable`].
+    pub verilator_executable: Option<String>,
+    /// See [`VerilatorRuntimeOptions::force_verilator_rebuild`].
+    pub force_verilator_rebuild: Option<bool>,
+    /// See [`VerilatorRuntimeOptions::log`].
+    pub log: Option<bool>,
+    /// See [`VerilatedModelConfig::verilator_optimization`].
+    pub verilator_optimization: Option<usize>,
+    /// See [`VerilatedModelConfig::enable_tracing`].
+    pub enable_tracing: Option<bool>,
+    /// See [`VerilatedModelConfig::ignored_warnings`].
+    pub ignored_warnings: Option<Vec<String>>,
+}
+
+fn invalid(message: String) -> VerilatorRuntimeError {
+    InvalidConfigurationSnafu { message }.build()
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool, VerilatorRuntimeError> {
+    match value {
+        "1" | "true" => Ok(true),
+        "0" | "false" => Ok(false),
+        _ => Err(invalid(format!(
+            "{name} must be 1, true, 0, or false, not {value:?}"
+        ))),
+    }
+}
+
+fn parse_optimization(
+    name: &str,
+    value: i64,
+) -> Result<usize, VerilatorRuntimeError> {
+    match value {
+        0..=3 => Ok(value as usize),
+        _ => Err(invalid(format!(
+            "{name} must be an optimization level from 0 to 3, not {value}"
+        ))),
+    }
+}
+
+/// The directory of the package being built or tested, from
+/// `CARGO_MANIFEST_DIR`, or else the current directory.
+pub(crate) fn project_directory() -> Utf8PathBuf {
+    match env::var("CARGO_MANIFEST_DIR") {
+        Ok(directory) => Utf8PathBuf::from(directory),
+        Err(_) => env::current_dir()
+            .ok()
+            .and_then(|directory| Utf8PathBuf::try_from(directory).ok())
+            .unwrap_or_else(|| Utf8PathBuf::from(".")),
+    }
+}
+
+impl ProjectConfig {
+    /// Reads the `marlin.toml` in the directory of the package being built
+    /// or tested (from `CARGO_MANIFEST_DIR`, or else the current directory),
+    /// if there is one, and applies the overrides in the environment
+    /// variables of this process.
+    pub fn discover() -> Result<Self, VerilatorRuntimeError> {
+        let path = project_directory().join(PROJECT_CONFIG_FILE_NAME);
+        let config = if path.is_file() {
+            Self::read(&path)?
+        } else {
+            Self::default()
+        };
+        config.override_with(|variable| env::var(variable).ok())
+    }
+
+    /// Reads the configuration in `path`, which has the format of a
+    /// `marlin.toml`.
+    pub fn read(
+        path: impl AsRef<Utf8Path>,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .whatever_context::<_, Whatever>(format!(
+                "Failed to read contents of {path}"
+            ))?;
+        let table: toml::Table = toml::from_str(&contents)
+            .whatever_context::<_, Whatever>(format!(
+                "Failed to parse {path} as a valid TOML file"
+            ))?;
+
+        let mut config = Self {
+            path: Some(path.to_path_buf()),
+            ..Default::default()
+        };
+        for (key, value) in &table {
+            let name = format!("`{key}` in {path}");
+            match (key.as_str(), value) {
+                ("artifact-directory", toml::Value::String(directory)) => {
+                    let base = path.parent().unwrap_or(Utf8Path::new(""));
+                    config.artifact_directory = Some(base.join(directory));
+                }
+                ("verilator", toml::Value::String(executable)) => {
+                    config.verilator_executable = Some(executable.clone());
+                }
+                ("force-rebuild", toml::Value::Boolean(force)) => {
+                    config.force_verilator_rebuild = Some(*force);
+                }
+                ("log", toml::Value::Boolean(log)) => {
+                    config.log = Some(*log);
+                }
+                ("optimization", toml::Value::Integer(level)) => {
+                    config.verilator_optimization =
+                        Some(parse_optimization(&name, *level)?);
+                }
+                ("tracing", toml::Value::Boolean(tracing)) => {
+                    config.enable_tracing = Some(*tracing);
+                }
+                ("ignored-warnings", toml::Value::Array(warnings)) => {
+                    let mut ignored_warnings = vec![];
+                    for warning in warnings {
+                        let Some(warning) = warning.as_str() else {
+                            return Err(invalid(format!(
+                                "{name} must be an array of strings"
+                            )));
+                        };
+                        ignored_warnings.push(warning.to_string());
+                    }
+                    config.ignored_warnings = Some(ignored_warnings);
+                }
+                (
+                    "artifact-directory" | "verilator" | "force-rebuild"
+                    | "log" | "optimization" | "tracing" | "ignored-warnings",
+                    _,
+                ) => {
+                    return Err(invalid(format!(
+                        "{name} cannot be a {}",
+                        value.type_str()
+                    )));
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "Unknown key `{key}` in {path}"
+                    )));
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Overrides this configuration with the environment variables (see the
+    /// [module documentation](self)) for which `variable` returns a value,
+    /// e.g., `|name| std::env::var(name).ok()`.
+    pub fn override_with(
+        mut self,
+        variable: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        if let Some(directory) = variable("MARLIN_ARTIFACT_DIRECTORY") {
+            self.artifact_directory = Some(directory.into());
+        }
+        if let Some(executable) = variable("MARLIN_VERILATOR") {
+            self.verilator_executable = Some(executable);
+        }
+        if let Some(force) = variable("MARLIN_FORCE_REBUILD") {
+            self.force_verilator_rebuild =
+                Some(parse_bool("MARLIN_FORCE_REBUILD", &force)?);
+        }
+        if let Some(log) = variable("MARLIN_LOG") {
+            self.log = Some(parse_bool("MARLIN_LOG", &log)?);
+        }
+        if let Some(level) = variable("MARLIN_OPTIMIZATION") {
+            let Ok(level) = level.parse() else {
+                return Err(invalid(format!(
+                    "MARLIN_OPTIMIZATION must be an optimization level from 0 to 3, not {level:?}"
+                )));
+            };
+            self.verilator_optimization =
+                Some(parse_optimization("MARLIN_OPTIMIZATION", level)?);
+        }
+        if let Some(tracing) = variable("MARLIN_TRACE") {
+            self.enable_tracing = Some(parse_bool("MARLIN_TRACE", &tracing)?);
+        }
+        if let Some(warnings) = variable("MARLIN_IGNORED_WARNINGS") {
+            self.ignored_warnings = Some(
+                warnings
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|warning| !warning.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            );
+        }
+        Ok(self)
+    }
+
+    /// The artifact directory of runtimes, or `default` if it is not
+    /// configured.
+    pub fn artifact_directory_or(&self, default: &Utf8Path) -> Utf8PathBuf {
+        self.artifact_directory
+            .clone()
+            .unwrap_or_else(|| default.to_path_buf())
+    }
+
+    /// The default runtime options with this configuration applied.
+    pub fn runtime_options(&self) -> VerilatorRuntimeOptions {
+        let mut options = VerilatorRuntimeOptions::default();
+        if let Some(executable) = &self.verilator_executable {
+            options.verilator_executable = executable.into();
+        }
+        if let Some(force) = self.force_verilator_rebuild {
+            options.force_verilator_rebuild = force;
+        }
+        if let Some(log) = self.log {
+            options.log = log;
+        }
+        options
+    }
+
+    /// The default model configuration with this configuration applied.
+    pub fn model_config(&self) -> VerilatedModelConfig {
+        let mut config = VerilatedModelConfig::default();
+        if let Some(level) = self.verilator_optimization {
+            config.verilator_optimization = level;
+        }
+        if let Some(tracing) = self.enable_tracing {
+            config.enable_tracing = tracing;
+        }
+        if let Some(ignored_warnings) = &self.ignored_warnings {
+            config.ignored_warnings = ignored_warnings.clone();
+        }
+        config
+    }
+}
@@ -0,0 +1,786 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Access to the `VerilatedContext` of a model.
+//!
+//! Every model is created with its own `VerilatedContext`, which holds the
+//! simulation time, whether the design has executed `$finish` or `$stop`, and
+//! the command-line arguments read by `$test$plusargs` and `$value$plusargs`.
+//!
+//! The context also collects what the design prints with `$display` and
+//! friends. By default, this output is printed with [`print!`] after each
+//! `eval`, so it is captured by the test harness like any other output of a
+//! test; see [`SimContext::set_capture_output`] to keep it for
+//! [`SimContext::take_output`] instead.
+//!
+//! For models built with [`crate::VerilatedModelConfig::public_signals`], the
+//! context also gives access to internal signals by hierarchical path; see
+//! [`SimContext::peek`] and [`SimContext::poke`], and [`crate::memory`] for
+//! memories.
+
+use std::{ffi, fmt, fs, marker::PhantomData, slice};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use snafu::{ResultExt, Whatever};
+
+use crate::{
+    VerilatorRuntimeError,
+    diagnostics::parse_location,
+    dynamic::VerilatorValue,
+    error::{
+        AssertionFailedSnafu, InvalidConfigurationSnafu,
+        MemoryOutOfBoundsSnafu, NoSuchSignalSnafu, NotAMemorySnafu,
+        SignalValueTooWideSnafu,
+    },
+    ffi_names::{
+        self, CONTEXT_CLEAR_FINISH, CONTEXT_CLEAR_OUTPUT, CONTEXT_OUTPUT,
+        CONTEXT_SEED, CONTEXT_SET_CAPTURE_OUTPUT, CONTEXT_SET_COVERAGE_PATH,
+        CONTEXT_SET_TIME, CONTEXT_STATUS, CONTEXT_TIME, CONTEXT_TIME_INC,
+        CONTEXT_WRITE_COVERAGE, MEMORY_LAYOUT, MEMORY_READ, MEMORY_WRITE,
+        SIGNAL_READ, SIGNAL_WIDTH, SIGNAL_WRITE,
+    },
+    memory::{self, MemoryFormat, MemoryLayout},
+    types,
+};
+
+/// The FFI status bit set by `eval` when there is output to print.
+const UNCAPTURED_OUTPUT: ffi::c_int = 4;
+
+/// The FFI status of a design that failed an assertion or hit a fatal error.
+const FAILED: ffi::c_int = 3;
+
+/// Arguments to the FFI functions for the output of a context.
+const OUTPUT_KIND_ALL: ffi::c_int = 0;
+const OUTPUT_KIND_MESSAGES: ffi::c_int = 1;
+const OUTPUT_KIND_FAILURE: ffi::c_int = 2;
+
+/// The state of the design after an `eval`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvalStatus {
+    /// The design has not executed `$finish` or `$stop`.
+    Running,
+    /// The design executed `$finish`.
+    Finished,
+    /// The design executed `$stop`.
+    Stopped,
+}
+
+impl EvalStatus {
+    /// Whether the design has not executed `$finish` or `$stop`.
+    pub fn is_running(self) -> bool {
+        matches!(self, Self::Running)
+    }
+
+    #[doc(hidden)]
+    pub fn from_ffi(status: ffi::c_int) -> Self {
+        match status & !UNCAPTURED_OUTPUT {
+            0 => Self::Running,
+            1 => Self::Finished,
+            _ => Self::Stopped,
+        }
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use std::ffi;
+
+    use libloading::Library;
+
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    pub struct ContextApi {
+        pub(crate) contextp:
+            extern "C" fn(*mut ffi::c_void) -> *mut ffi::c_void,
+        pub(crate) time: extern "C" fn(*mut ffi::c_void) -> u64,
+        pub(crate) set_time: extern "C" fn(*mut ffi::c_void, u64),
+        pub(crate) time_inc: extern "C" fn(*mut ffi::c_void, u64),
+        pub(crate) status: extern "C" fn(*mut ffi::c_void) -> ffi::c_int,
+        pub(crate) clear_finish: extern "C" fn(*mut ffi::c_void),
+        pub(crate) seed: extern "C" fn(*mut ffi::c_void) -> ffi::c_int,
+        pub(crate) output: extern "C" fn(
+            *mut ffi::c_void,
+            ffi::c_int,
+            *mut usize,
+        ) -> *const ffi::c_char,
+        pub(crate) clear_output: extern "C" fn(*mut ffi::c_void, ffi::c_int),
+        pub(crate) set_capture_output: extern "C" fn(*mut ffi::c_void, bool),
+        /// Only present if the model collects coverage.
+        pub(crate) coverage: Option<CoverageApi>,
+        /// Only present if the model was built with timing.
+        pub(crate) timing: Option<TimingApi>,
+        /// Only present if the model was built with public signals.
+        pub(crate) signals: Option<SignalApi>,
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct CoverageApi {
+        pub(crate) set_path:
+            extern "C" fn(*mut ffi::c_void, *const ffi::c_char),
+        pub(crate) write: extern "C" fn(*mut ffi::c_void, *const ffi::c_char),
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct TimingApi {
+        pub(crate) events_pending: extern "C" fn(*mut ffi::c_void) -> bool,
+        pub(crate) next_time_slot: extern "C" fn(*mut ffi::c_void) -> u64,
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct SignalApi {
+        pub(crate) width:
+            extern "C" fn(*mut ffi::c_void, *const ffi::c_char) -> ffi::c_int,
+        pub(crate) read:
+            extern "C" fn(*mut ffi::c_void, *const ffi::c_char, *mut u32),
+        pub(crate) write:
+            extern "C" fn(*mut ffi::c_void, *const ffi::c_char, *const u32),
+        pub(crate) memory_layout: extern "C" fn(
+            *mut ffi::c_void,
+            *const ffi::c_char,
+            *mut ffi::c_int,
+            *mut ffi::c_int,
+        ) -> ffi::c_int,
+        pub(crate) memory_read: extern "C" fn(
+            *mut ffi::c_void,
+            *const ffi::c_char,
+            ffi::c_int,
+            ffi::c_int,
+            *mut u32,
+        ),
+        pub(crate) memory_write: extern "C" fn(
+            *mut ffi::c_void,
+            *const ffi::c_char,
+            ffi::c_int,
+            ffi::c_int,
+            *const u32,
+        ),
+    }
+
+    impl ContextApi {
+        /// Loads the context functions for the model `top_module` from its
+        /// library.
+        pub fn load(
+            library: &Library,
+            top_module: &str,
+        ) -> Result<Self, libloading::Error> {
+            // SAFETY: these symbols are generated with these signatures by
+            // `build_library::build_ffi`.
+            unsafe {
+                Ok(Self {
+                    contextp: *library
+                        .get(ffi_names::top_contextp(top_module).as_bytes())?,
+                    time: *library.get(CONTEXT_TIME.as_bytes())?,
+                    set_time: *library.get(CONTEXT_SET_TIME.as_bytes())?,
+                    time_inc: *library.get(CONTEXT_TIME_INC.as_bytes())?,
+                    status: *library.get(CONTEXT_STATUS.as_bytes())?,
+                    clear_finish: *library
+                        .get(CONTEXT_CLEAR_FINISH.as_bytes())?,
+                    seed: *library.get(CONTEXT_SEED.as_bytes())?,
+                    output: *library.get(CONTEXT_OUTPUT.as_bytes())?,
+                    clear_output: *library
+                        .get(CONTEXT_CLEAR_OUTPUT.as_bytes())?,
+                    set_capture_output: *library
+                        .get(CONTEXT_SET_CAPTURE_OUTPUT.as_bytes())?,
+                    coverage: library
+                        .get(CONTEXT_SET_COVERAGE_PATH.as_bytes())
+                        .and_then(|set_path| {
+                            Ok(CoverageApi {
+                                set_path: *set_path,
+                                write: *library
+                                    .get(CONTEXT_WRITE_COVERAGE.as_bytes())?,
+                            })
+                        })
+                        .ok(),
+                    timing: library
+                        .get(
+                            ffi_names::top_events_pending(top_module)
+                                .as_bytes(),
+                        )
+                        .and_then(|events_pending| {
+                            Ok(TimingApi {
+                                events_pending: *events_pending,
+                                next_time_slot: *library.get(
+                                    ffi_names::top_next_time_slot(top_module)
+                                        .as_bytes(),
+                                )?,
+                            })
+                        })
+                        .ok(),
+                    signals: library
+                        .get(SIGNAL_WIDTH.as_bytes())
+                        .and_then(|width| {
+                            Ok(SignalApi {
+                                width: *width,
+                                read: *library.get(SIGNAL_READ.as_bytes())?,
+                                write: *library.get(SIGNAL_WRITE.as_bytes())?,
+                                memory_layout: *library
+                                    .get(MEMORY_LAYOUT.as_bytes())?,
+                                memory_read: *library
+                                    .get(MEMORY_READ.as_bytes())?,
+                                memory_write: *library
+                                    .get(MEMORY_WRITE.as_bytes())?,
+                            })
+                        })
+                        .ok(),
+                })
+            }
+        }
+    }
+
+    /// The signature of the FFI function creating a model, which takes the
+    /// command-line arguments for its `VerilatedContext` as `argc` and
+    /// `argv`.
+    pub type NewModelFn = extern "C" fn(
+        ffi::c_int,
+        *const *const ffi::c_char,
+    ) -> *mut ffi::c_void;
+
+    /// Creates a model with `new_model`, passing it `command_args`.
+    pub fn new_model(
+        new_model: NewModelFn,
+        command_args: &[String],
+    ) -> *mut ffi::c_void {
+        // by convention, the first argument is the program name
+        let command_args = std::iter::once("marlin")
+            .chain(command_args.iter().map(String::as_str))
+            .map(|argument| {
+                ffi::CString::new(argument)
+                    .expect("Command-line arguments cannot contain NUL bytes")
+            })
+            .collect::<Vec<_>>();
+        let argv = command_args
+            .iter()
+            .map(|argument| argument.as_ptr())
+            .collect::<Vec<_>>();
+        new_model(argv.len() as ffi::c_int, argv.as_ptr())
+    }
+
+    /// Converts the `status` returned by the FFI `eval` of `model`, printing
+    /// the output of the design if it is not being captured.
+    pub fn after_eval(
+        api: &ContextApi,
+        model: *mut ffi::c_void,
+        status: ffi::c_int,
+    ) -> Result<EvalStatus, VerilatorRuntimeError> {
+        let mut context = new_sim_context(api, model);
+        if status & UNCAPTURED_OUTPUT != 0 {
+            print!("{}", context.take_output());
+        }
+        if status & !UNCAPTURED_OUTPUT == FAILED {
+            let failure = context.read(OUTPUT_KIND_FAILURE);
+            let (file, line, message) = match DesignMessage::parse(&failure) {
+                Some(message) => (message.file, message.line, message.message),
+                None => (None, None, failure),
+            };
+            return AssertionFailedSnafu {
+                file,
+                line,
+                message,
+            }
+            .fail();
+        }
+        Ok(EvalStatus::from_ffi(status))
+    }
+
+    pub fn new_sim_context<'a>(
+        api: &'a ContextApi,
+        model: *mut ffi::c_void,
+    ) -> SimContext<'a> {
+        SimContext {
+            handle: (api.contextp)(model),
+            model,
+            api,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// The `VerilatedContext` of a model, obtained with
+/// [`crate::AsDynamicVerilatedModel::context`].
+pub struct SimContext<'a> {
+    handle: *mut ffi::c_void,
+    /// The model owning the context.
+    model: *mut ffi::c_void,
+    api: &'a __private::ContextApi,
+    _marker: PhantomData<&'a mut ()>,
+}
+
+impl SimContext<'_> {
+    /// The current simulation time, in units of the time precision of the
+    /// design.
+    pub fn time(&self) -> u64 {
+        (self.api.time)(self.handle)
+    }
+
+    /// Sets the current simulation time.
+    pub fn set_time(&mut self, time: u64) {
+        (self.api.set_time)(self.handle, time);
+    }
+
+    /// Advances the current simulation time by `delta`.
+    pub fn time_inc(&mut self, delta: u64) {
+        (self.api.time_inc)(self.handle, delta);
+    }
+
+    /// Whether the design has delays or event controls waiting for a later
+    /// time. Always `false` unless the model was built with
+    /// [`crate::VerilatedModelConfig::timing`].
+    pub fn events_pending(&self) -> bool {
+        self.api
+            .timing
+            .is_some_and(|timing| (timing.events_pending)(self.model))
+    }
+
+    /// The time of the next delay or event control the design is waiting for,
+    /// if any. See [`SimContext::events_pending`].
+    pub fn next_time_slot(&self) -> Option<u64> {
+        let timing = self.api.timing?;
+        (timing.events_pending)(self.model)
+            .then(|| (timing.next_time_slot)(self.model))
+    }
+
+    /// Whether the design has executed `$finish` or `$stop`.
+    pub fn status(&self) -> EvalStatus {
+        EvalStatus::from_ffi((self.api.status)(self.handle))
+    }
+
+    /// Whether the design has executed `$finish` or `$stop`. Equivalent to
+    /// `!self.status().is_running()`.
+    pub fn got_finish(&self) -> bool {
+        !self.status().is_running()
+    }
+
+    /// The seed of the random number generator Verilator uses, e.g., to
+    /// randomize initial values under [`crate::XInit::Unique`]. Rerunning a
+    /// model with this seed (see [`crate::VerilatorRuntimeOptions::seed`])
+    /// reproduces its random initial state.
+    pub fn seed(&self) -> u32 {
+        (self.api.seed)(self.handle) as u32
+    }
+
+    /// Whether the output of the design is kept until
+    /// [`SimContext::take_output`] is called (`true`) or printed after each
+    /// `eval` (`false`, the default unless
+    /// [`crate::VerilatorRuntimeOptions::capture_output`] is set).
+    pub fn set_capture_output(&mut self, capture_output: bool) {
+        (self.api.set_capture_output)(self.handle, capture_output);
+    }
+
+    fn read(&self, kind: ffi::c_int) -> String {
+        let mut length = 0;
+        let pointer = (self.api.output)(self.handle, kind, &mut length);
+        // SAFETY: the context owns `length` bytes at `pointer`, which we copy.
+        String::from_utf8_lossy(unsafe {
+            slice::from_raw_parts(pointer.cast::<u8>(), length)
+        })
+        .into_owned()
+    }
+
+    fn take(&mut self, kind: ffi::c_int) -> String {
+        let text = self.read(kind);
+        (self.api.clear_output)(self.handle, kind);
+        text
+    }
+
+    /// Removes and returns everything the design has printed (with
+    /// `$display`, `$write`, `$error`, etc.) since the output was last taken.
+    pub fn take_output(&mut self) -> String {
+        self.take(OUTPUT_KIND_ALL)
+    }
+
+    /// Removes and returns the `$info`, `$warning`, `$error`, and `$fatal`
+    /// messages the design has printed since they were last taken. Unlike
+    /// [`SimContext::take_output`], these are kept even when the output is not
+    /// captured.
+    pub fn take_messages(&mut self) -> Vec<DesignMessage> {
+        self.take(OUTPUT_KIND_MESSAGES)
+            .lines()
+            .filter_map(DesignMessage::parse)
+            .collect()
+    }
+
+    /// Whether the model collects coverage, i.e., was built with
+    /// [`crate::VerilatedModelConfig::coverage`].
+    pub fn collects_coverage(&self) -> bool {
+        self.api.coverage.is_some()
+    }
+
+    pub(crate) fn set_coverage_path(&mut self, path: &Utf8Path) {
+        if let Some(coverage) = &self.api.coverage {
+            let path = ffi::CString::new(path.as_str())
+                .expect("Coverage paths cannot contain NUL bytes");
+            (coverage.set_path)(self.handle, path.as_ptr());
+        }
+    }
+
+    /// Writes the coverage collected so far to `path` as a coverage database,
+    /// which can be read with [`crate::coverage::CoverageReport::read`] or
+    /// Verilator's `verilator_coverage`. The model still writes its coverage
+    /// to the coverage directory of its runtime when the runtime is dropped.
+    pub fn write_coverage(
+        &mut self,
+        path: impl AsRef<Utf8Path>,
+    ) -> Result<(), VerilatorRuntimeError> {
+        let path = path.as_ref();
+        let Some(coverage) = &self.api.coverage else {
+            return InvalidConfigurationSnafu {
+                message: format!(
+                    "Cannot write coverage to {path} because the model was not built with coverage enabled"
+                ),
+            }
+            .fail();
+        };
+        let path = ffi::CString::new(path.as_str())
+            .whatever_context::<_, Whatever>(format!(
+                "Coverage path {path} contains a NUL byte"
+            ))?;
+        (coverage.write)(self.handle, path.as_ptr());
+        Ok(())
+    }
+
+    /// Looks up the public signal at `path`, returning its width.
+    fn find_signal(
+        &self,
+        path: &str,
+    ) -> Result<
+        (__private::SignalApi, ffi::CString, usize),
+        VerilatorRuntimeError,
+    > {
+        let Some(signals) = self.api.signals else {
+            return InvalidConfigurationSnafu {
+                message: format!(
+                    "Cannot access signal {path} because the model was not built with `public_signals`"
+                ),
+            }
+            .fail();
+        };
+        let c_path = ffi::CString::new(path).whatever_context::<_, Whatever>(
+            format!("Signal path {path} contains a NUL byte"),
+        )?;
+        let width = (signals.width)(self.handle, c_path.as_ptr());
+        if width <= 0 {
+            return NoSuchSignalSnafu { path }.fail();
+        }
+        Ok((signals, c_path, width as usize))
+    }
+
+    /// Reads the public signal at the hierarchical `path`, which starts with
+    /// the name of the top module, e.g., `main.core.state`. A word of a
+    /// memory is selected with a trailing index, e.g., `main.regfile[3]`.
+    ///
+    /// The value has the narrowest [`VerilatorValue`] variant that fits the
+    /// width of the signal. Returns [`VerilatorRuntimeError::NoSuchSignal`] if
+    /// `path` does not name a public signal (see
+    /// [`crate::VerilatedModelConfig::public_signals`]), which includes
+    /// signals Verilator optimized away.
+    pub fn peek(
+        &self,
+        path: &str,
+    ) -> Result<VerilatorValue<'static>, VerilatorRuntimeError> {
+        let (signals, c_path, width) = self.find_signal(path)?;
+        let mut words = vec![0; width.div_ceil(32)];
+        (signals.read)(self.handle, c_path.as_ptr(), words.as_mut_ptr());
+        Ok(match width {
+            0..=8 => VerilatorValue::CData(words[0] as types::CData),
+            9..=16 => VerilatorValue::SData(words[0] as types::SData),
+            17..=32 => VerilatorValue::IData(words[0]),
+            33..=64 => VerilatorValue::QData(
+                types::QData::from(words[0])
+                    | (types::QData::from(words[1]) << 32),
+            ),
+            _ => VerilatorValue::WDataOutP(words.into()),
+        })
+    }
+
+    /// Writes `value` to the public signal at the hierarchical `path` (see
+    /// [`SimContext::peek`]), which must be writable, i.e., marked with
+    /// `/*verilator public_flat_rw*/` or built with
+    /// [`crate::PublicSignals::All`]. The design sees the new value at the
+    /// next `eval`.
+    ///
+    /// Returns [`VerilatorRuntimeError::SignalValueTooWide`] if `value` has
+    /// bits set beyond the width of the signal.
+    pub fn poke<'a>(
+        &mut self,
+        path: &str,
+        value: impl Into<VerilatorValue<'a>>,
+    ) -> Result<(), VerilatorRuntimeError> {
+        let (signals, c_path, width) = self.find_signal(path)?;
+        let mut words = match value.into() {
+            VerilatorValue::CData(value) => vec![value.into()],
+            VerilatorValue::SData(value) => vec![value.into()],
+            VerilatorValue::IData(value) => vec![value],
+            VerilatorValue::QData(value) => {
+                vec![value as types::WData, (value >> 32) as types::WData]
+            }
+            VerilatorValue::WDataInP(values) => values.to_vec(),
+            VerilatorValue::WDataOutP(values) => values.into_vec(),
+        };
+        let value_width = words
+            .iter()
+            .rposition(|word| *word != 0)
+            .map_or(0, |index| {
+                32 * (index + 1) - words[index].leading_zeros() as usize
+            });
+        if value_width > width {
+            return SignalValueTooWideSnafu { path, width }.fail();
+        }
+        words.resize(width.div_ceil(32), 0);
+        (signals.write)(self.handle, c_path.as_ptr(), words.as_ptr());
+        Ok(())
+    }
+
+    /// Looks up the memory at `path`, returning its layout.
+    fn find_memory(
+        &self,
+        path: &str,
+    ) -> Result<
+        (__private::SignalApi, ffi::CString, MemoryLayout),
+        VerilatorRuntimeError,
+    > {
+        let (signals, c_path, _) = self.find_signal(path)?;
+        let (mut low, mut high) = (0, 0);
+        let width = (signals.memory_layout)(
+            self.handle,
+            c_path.as_ptr(),
+            &mut low,
+            &mut high,
+        );
+        if width == 0 {
+            return NotAMemorySnafu { path }.fail();
+        }
+        if width > 64 {
+            return InvalidConfigurationSnafu {
+                message: format!(
+                    "Memory {path} has {width}-bit words, but only memories up to 64 bits wide are supported"
+                ),
+            }
+            .fail();
+        }
+        let layout = MemoryLayout {
+            width: width as usize,
+            low: low.into(),
+            high: high.into(),
+        };
+        Ok((signals, c_path, layout))
+    }
+
+    /// The width and address range of the memory (an unpacked array, like
+    /// `reg[31:0] ram[0:1023]`) at the hierarchical `path`, which names a
+    /// public signal like in [`SimContext::peek`]. Only memories of up to 64
+    /// bits per word are supported.
+    pub fn memory_layout(
+        &self,
+        path: &str,
+    ) -> Result<MemoryLayout, VerilatorRuntimeError> {
+        Ok(self.find_memory(path)?.2)
+    }
+
+    /// Reads every word of the memory at `path` (see
+    /// [`SimContext::memory_layout`]), from the lowest address to the
+    /// highest.
+    pub fn read_memory(
+        &self,
+        path: &str,
+    ) -> Result<Vec<u64>, VerilatorRuntimeError> {
+        let (signals, c_path, layout) = self.find_memory(path)?;
+        let word_count = layout.width.div_ceil(32);
+        let mut words = vec![0; layout.depth() * word_count];
+        (signals.memory_read)(
+            self.handle,
+            c_path.as_ptr(),
+            layout.low as ffi::c_int,
+            layout.depth() as ffi::c_int,
+            words.as_mut_ptr(),
+        );
+        Ok(words
+            .chunks_exact(word_count)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .rev()
+                    .fold(0, |value, word| (value << 32) | u64::from(*word))
+            })
+            .collect())
+    }
+
+    /// Writes `values` to consecutive words of the memory at `path` (see
+    /// [`SimContext::memory_layout`]), starting at `address`. The design sees
+    /// the new contents at the next `eval`.
+    pub fn write_memory(
+        &mut self,
+        path: &str,
+        address: i64,
+        values: &[u64],
+    ) -> Result<(), VerilatorRuntimeError> {
+        let (signals, c_path, layout) = self.find_memory(path)?;
+        let end = address + values.len() as i64 - 1;
+        let in_bounds = layout.contains(address) && layout.contains(end);
+        if !values.is_empty() && !in_bounds {
+            return MemoryOutOfBoundsSnafu {
+                path,
+                address,
+                count: values.len(),
+                low: layout.low,
+                high: layout.high,
+            }
+            .fail();
+        }
+        let word_count = layout.width.div_ceil(32);
+        let mut words = Vec::with_capacity(values.len() * word_count);
+        for (offset, value) in values.iter().enumerate() {
+            if layout.width < 64 && value >> layout.width != 0 {
+                return SignalValueTooWideSnafu {
+                    path: format!("{path}[{}]", address + offset as i64),
+                    width: layout.width,
+                }
+                .fail();
+            }
+            words.push(*value as u32);
+            if word_count == 2 {
+                words.push((value >> 32) as u32);
+            }
+        }
+        (signals.memory_write)(
+            self.handle,
+            c_path.as_ptr(),
+            address as ffi::c_int,
+            values.len() as ffi::c_int,
+            words.as_ptr(),
+        );
+        Ok(())
+    }
+
+    /// Loads the memory at `path` (see [`SimContext::memory_layout`]) from
+    /// `file` in the given `format`, like `$readmemh` does. Words not in the
+    /// file keep their current values.
+    pub fn load_memory(
+        &mut self,
+        path: &str,
+        file: impl AsRef<Utf8Path>,
+        format: MemoryFormat,
+    ) -> Result<(), VerilatorRuntimeError> {
+        let file = file.as_ref();
+        let layout = self.memory_layout(path)?;
+        let contents = fs::read(file).whatever_context::<_, Whatever>(
+            format!("Failed to read memory file {file}"),
+        )?;
+        for (address, values) in memory::parse(&contents, format, &layout)? {
+            self.write_memory(path, address, &values)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the contents of the memory at `path` (see
+    /// [`SimContext::memory_layout`]) to `file` in the given `format`, like
+    /// `$writememh` does.
+    pub fn dump_memory(
+        &self,
+        path: &str,
+        file: impl AsRef<Utf8Path>,
+        format: MemoryFormat,
+    ) -> Result<(), VerilatorRuntimeError> {
+        let file = file.as_ref();
+        let layout = self.memory_layout(path)?;
+        let contents =
+            memory::format(&self.read_memory(path)?, format, &layout);
+        fs::write(file, contents).whatever_context::<_, Whatever>(format!(
+            "Failed to write memory file {file}"
+        ))?;
+        Ok(())
+    }
+
+    /// Resets the finished/stopped status, e.g., to resume after a `$stop`.
+    /// This also clears an assertion failure (see
+    /// [`crate::VerilatedModelConfig::assertions`]), so the model can keep
+    /// running after one.
+    pub fn clear_finish(&mut self) {
+        (self.api.clear_finish)(self.handle);
+    }
+}
+
+/// The severity task that printed a [`DesignMessage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DesignSeverity {
+    /// `$info`.
+    Info,
+    /// `$warning`.
+    Warning,
+    /// `$error`, including failed immediate assertions.
+    Error,
+    /// `$fatal`.
+    Fatal,
+}
+
+impl fmt::Display for DesignSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Fatal => "fatal",
+        }
+        .fmt(f)
+    }
+}
+
+/// A message printed by `$info`, `$warning`, `$error`, or `$fatal`, obtained
+/// with [`SimContext::take_messages`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesignMessage {
+    pub severity: DesignSeverity,
+    /// The simulation time at which the message was printed, if Verilator
+    /// included it.
+    pub time: Option<u64>,
+    pub file: Option<Utf8PathBuf>,
+    pub line: Option<usize>,
+    /// The text of the message, e.g., `Assertion failed in TOP.main: ...`.
+    pub message: String,
+}
+
+impl DesignMessage {
+    /// Parses a line like `[10] %Error: main.sv:5: Assertion failed in
+    /// TOP.main`.
+    fn parse(line: &str) -> Option<Self> {
+        let (time, line) = match line
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("] "))
+        {
+            Some((time, rest)) => (time.trim().parse().ok(), rest),
+            None => (None, line),
+        };
+        let (tag, rest) = line.split_once(": ")?;
+        let severity = match tag {
+            "-Info" => DesignSeverity::Info,
+            "%Warning" => DesignSeverity::Warning,
+            "%Error" => DesignSeverity::Error,
+            "%Fatal" => DesignSeverity::Fatal,
+            _ => return None,
+        };
+        let (file, line, message) = match parse_location(rest) {
+            Some((file, line, _, message)) => (Some(file), Some(line), message),
+            None => (None, None, rest),
+        };
+        Some(Self {
+            severity,
+            time,
+            file,
+            line,
+            message: message.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for DesignMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+        if let (Some(file), Some(line)) = (&self.file, self.line) {
+            write!(f, "\n  --> {file}:{line}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Reading and exporting the coverage collected by models built with
+//! [`crate::VerilatedModelConfig::coverage`].
+//!
+//! Each such model writes its coverage database (a `coverage.dat` in
+//! Verilator's format) to the coverage directory of its runtime (see
+//! [`crate::VerilatorRuntime::coverage_directory`]) when the runtime is
+//! dropped. [`CoverageReport::read_directory`] merges all the databases in
+//! that directory into a report that can be exported as LCOV or Cobertura.
+//!
+//! By default, each runtime writes to a directory of its own. Runtimes can
+//! instead share one (with
+//! [`crate::VerilatorRuntimeOptions::coverage_directory`]), e.g., to merge
+//! the coverage from every test in a `cargo test` run, in which case the
+//! databases of earlier runs stay there until [`remove_databases`] is
+//! called.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use snafu::{ResultExt, Whatever, whatever};
+
+use crate::VerilatorRuntimeError;
+
+/// The kind of a [`CoveragePoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CoverageKind {
+    /// A block of code, enabled by [`crate::CoverageKinds::line`].
+    Line,
+    /// A branch of a conditional, enabled by [`crate::CoverageKinds::line`].
+    Branch,
+    /// A term of a condition, enabled by [`crate::CoverageKinds::line`] in
+    /// newer versions of Verilator.
+    Expression,
+    /// A bit of a signal changing, enabled by [`crate::CoverageKinds::toggle`].
+    Toggle,
+    /// A `cover property` or `cover` statement, enabled by
+    /// [`crate::CoverageKinds::user`].
+    User,
+    /// Any other kind of point.
+    Other,
+}
+
+impl CoverageKind {
+    fn from_page(page: &str) -> Self {
+        match page.split_once('/').map_or(page, |(kind, _)| kind) {
+            "v_line" => Self::Line,
+            "v_branch" => Self::Branch,
+            "v_expr" => Self::Expression,
+            "v_toggle" => Self::Toggle,
+            "v_user" => Self::User,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// A location that Verilator counts how many times was covered.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CoveragePoint {
+    pub file: Utf8PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub kind: CoverageKind,
+    /// The hierarchical name of the instance the point is in, e.g.,
+    /// `TOP.main.adder`.
+    pub hierarchy: String,
+    /// Verilator's description of the point, e.g., `if` or the name of a
+    /// toggled signal.
+    pub comment: String,
+    /// The source lines the point covers, which may be more than
+    /// [`CoveragePoint::line`] for a block of code.
+    pub lines: Vec<usize>,
+}
+
+/// Coverage counts, possibly merged from several coverage databases.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// How many times each point was covered.
+    pub counts: BTreeMap<CoveragePoint, u64>,
+}
+
+/// Parses the line ranges of a point, e.g., `15-16,18`.
+fn parse_lines(text: &str) -> Option<Vec<usize>> {
+    let mut lines = vec![];
+    for range in text.split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => {
+                lines.extend(start.parse::<usize>().ok()?..=end.parse().ok()?)
+            }
+            None => lines.push(range.parse().ok()?),
+        }
+    }
+    Some(lines)
+}
+
+/// Removes every coverage database (`*.dat` file) in `directory`, e.g., so
+/// that a shared coverage directory only collects the databases of the
+/// following run. Does nothing if `directory` does not exist.
+pub fn remove_databases(
+    directory: impl AsRef<Utf8Path>,
+) -> Result<(), VerilatorRuntimeError> {
+    let directory = directory.as_ref();
+    if !directory.exists() {
+        return Ok(());
+    }
+    for entry in
+        directory
+            .read_dir_utf8()
+            .whatever_context::<_, Whatever>(format!(
+                "Failed to read coverage directory {directory}"
+            ))?
+    {
+        let entry = entry.whatever_context::<_, Whatever>(format!(
+            "Failed to read entry in {directory}"
+        ))?;
+        if entry.path().extension() == Some("dat") {
+            fs::remove_file(entry.path()).whatever_context::<_, Whatever>(
+                format!("Failed to remove coverage database {}", entry.path()),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Escapes `text` for use in an XML attribute.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl CoverageReport {
+    /// Parses the contents of a coverage database written by Verilator.
+    pub fn parse(contents: &str) -> Result<Self, VerilatorRuntimeError> {
+        Ok(Self::parse_database(contents)?)
+    }
+
+    fn parse_database(contents: &str) -> Result<Self, Whatever> {
+        let mut report = Self::default();
+        for (index, line) in contents.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((keys, count)) = line
+                .strip_prefix("C '")
+                .and_then(|line| line.rsplit_once("' "))
+            else {
+                whatever!(
+                    "Invalid coverage database line {}: {line}",
+                    index + 1
+                );
+            };
+            let Ok(count) = count.trim().parse::<u64>() else {
+                whatever!(
+                    "Invalid count on coverage database line {}: {count}",
+                    index + 1
+                );
+            };
+
+            let mut point = CoveragePoint {
+                file: Utf8PathBuf::new(),
+                line: 0,
+                column: 0,
+                kind: CoverageKind::Other,
+                hierarchy: String::new(),
+                comment: String::new(),
+                lines: vec![],
+            };
+            // each key is preceded by \x01 and its value by \x02
+            for pair in keys.split('\x01').filter(|pair| !pair.is_empty()) {
+                let Some((key, value)) = pair.split_once('\x02') else {
+                    continue;
+                };
+                match key {
+                    "f" => point.file = value.into(),
+                    "l" => point.line = value.parse().unwrap_or_default(),
+                    "n" => point.column = value.parse().unwrap_or_default(),
+                    "page" => point.kind = CoverageKind::from_page(value),
+                    "h" => point.hierarchy = value.to_string(),
+                    "o" => point.comment = value.to_string(),
+                    "S" => point.lines = parse_lines(value).unwrap_or_default(),
+                    _ => {}
+                }
+            }
+            if point.lines.is_empty() {
+                point.lines.push(point.line);
+            }
+
+            *report.counts.entry(point).or_default() += count;
+        }
+        Ok(report)
+    }
+
+    /// Reads the coverage database at `path`.
+    pub fn read(
+        path: impl AsRef<Utf8Path>,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .whatever_context::<_, Whatever>(format!(
+                "Failed to read coverage database {path}"
+            ))?;
+        Self::parse(&contents)
+    }
+
+    /// Reads and merges every coverage database (`*.dat` file) in
+    /// `directory`. Returns an empty report if `directory` does not exist.
+    pub fn read_directory(
+        directory: impl AsRef<Utf8Path>,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        let directory = directory.as_ref();
+        let mut report = Self::default();
+        if !directory.exists() {
+            return Ok(report);
+        }
+        for entry in directory.read_dir_utf8().whatever_context::<_, Whatever>(
+            format!("Failed to read coverage directory {directory}"),
+        )? {
+            let entry = entry.whatever_context::<_, Whatever>(format!(
+                "Failed to read entry in {directory}"
+            ))?;
+            if entry.path().extension() == Some("dat") {
+                report.merge(&Self::read(entry.path())?);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Adds the counts in `other` to this report.
+    pub fn merge(&mut self, other: &CoverageReport) {
+        for (point, count) in &other.counts {
+            *self.counts.entry(point.clone()).or_default() += count;
+        }
+    }
+
+    /// The hit count of every covered line in every file, which (like
+    /// `verilator_coverage`) is the least count of the points on that line.
+    pub fn line_counts(&self) -> BTreeMap<Utf8PathBuf, BTreeMap<usize, u64>> {
+        let mut files = BTreeMap::<_, BTreeMap<_, u64>>::new();
+        for (point, count) in &self.counts {
+            let lines = files.entry(point.file.clone()).or_default();
+            for line in &point.lines {
+                lines
+                    .entry(*line)
+                    .and_modify(|existing| *existing = (*existing).min(*count))
+                    .or_insert(*count);
+            }
+        }
+        files
+    }
+
+    /// Exports this report in the LCOV tracefile format (like
+    /// `verilator_coverage --write-info`).
+    pub fn to_lcov(&self) -> String {
+        let mut buffer = String::new();
+        for (file, lines) in self.line_counts() {
+            writeln!(&mut buffer, "TN:verilator_coverage").unwrap();
+            writeln!(&mut buffer, "SF:{file}").unwrap();
+            for (line, count) in &lines {
+                writeln!(&mut buffer, "DA:{line},{count}").unwrap();
+            }
+            writeln!(&mut buffer, "LF:{}", lines.len()).unwrap();
+            writeln!(
+                &mut buffer,
+                "LH:{}",
+                lines.values().filter(|count| **count > 0).count()
+            )
+            .unwrap();
+            writeln!(&mut buffer, "end_of_record").unwrap();
+        }
+        buffer
+    }
+
+    /// Exports this report in the Cobertura XML format, with one class per
+    /// source file.
+    pub fn to_cobertura(&self) -> String {
+        let files = self.line_counts();
+        let rate = |lines: &mut dyn Iterator<Item = &u64>| {
+            let (valid, covered) =
+                lines.fold((0, 0), |(valid, covered), count| {
+                    (valid + 1, covered + usize::from(*count > 0))
+                });
+            (
+                valid,
+                covered,
+                if valid == 0 {
+                    1.0
+                } else {
+                    covered as f64 / valid as f64
+                },
+            )
+        };
+        let (lines_valid, lines_covered, line_rate) =
+            rate(&mut files.values().flat_map(BTreeMap::values));
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut buffer = String::new();
+        writeln!(&mut buffer, r#"<?xml version="1.0" ?>"#).unwrap();
+        writeln!(
+            &mut buffer,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        )
+        .unwrap();
+        writeln!(
+            &mut buffer,
+            r#"<coverage line-rate="{line_rate:.4}" branch-rate="0" lines-covered="{lines_covered}" lines-valid="{lines_valid}" branches-covered="0" branches-valid="0" complexity="0" version="marlin" timestamp="{timestamp}">"#
+        )
+        .unwrap();
+        writeln!(&mut buffer, "  <sources><source>.</source></sources>")
+            .unwrap();
+        writeln!(&mut buffer, "  <packages>").unwrap();
+        writeln!(
+            &mut buffer,
+            r#"    <package name="verilog" line-rate="{line_rate:.4}" branch-rate="0" complexity="0">"#
+        )
+        .unwrap();
+        writeln!(&mut buffer, "      <classes>").unwrap();
+        for (file, lines) in &files {
+            let (_, _, file_rate) = rate(&mut lines.values());
+            let file = escape_xml(file.as_str());
+            writeln!(
+                &mut buffer,
+                r#"        <class name="{file}" filename="{file}" line-rate="{file_rate:.4}" branch-rate="0" complexity="0">"#
+            )
+            .unwrap();
+            writeln!(&mut buffer, "          <methods/>").unwrap();
+            writeln!(&mut buffer, "          <lines>").unwrap();
+            for (line, count) in lines {
+                writeln!(
+                    &mut buffer,
+                    r#"            <line number="{line}" hits="{count}"/>"#
+                )
+                .unwrap();
+            }
+            writeln!(&mut buffer, "          </lines>").unwrap();
+            writeln!(&mut buffer, "        </class>").unwrap();
+        }
+        writeln!(&mut buffer, "      </classes>").unwrap();
+        writeln!(&mut buffer, "    </package>").unwrap();
+        writeln!(&mut buffer, "  </packages>").unwrap();
+        writeln!(&mut buffer, "</coverage>").unwrap();
+        buffer
+    }
+}
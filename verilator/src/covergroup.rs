@@ -0,0 +1,674 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Functional coverage over the ports of a model, in the spirit of
+//! SystemVerilog covergroups (which Verilator does not support).
+//!
+//! A [`Covergroup`] has a coverpoint per port of interest, each with bins of
+//! values, ranges, or sequences of values (transitions), and crosses of those
+//! coverpoints. It samples the ports of a model, either on every call to
+//! [`Covergroup::sample`] or only on the edges of a clock, and reports how
+//! many times each bin was hit and which bins were never hit (the holes).
+//!
+//! A covergroup attached to a model with
+//! [`AsDynamicVerilatedModel::attach_covergroup`] instead samples it after
+//! every evaluation, including those of
+//! [`AsDynamicVerilatedModel::run_until`].
+//!
+//! ```no_run
+//! # use marlin_verilator::{*, covergroup::*};
+//! # fn f<'ctx, M: AsVerilatedModel<'ctx> + AsDynamicVerilatedModel<'ctx>>(
+//! #     alu: &mut M,
+//! # ) -> Result<(), CovergroupError> {
+//! let mut group = Covergroup::new::<M>("alu")
+//!     .sample_on(SampleOn::PosEdge("clk".into()));
+//! group.coverpoint(
+//!     "op",
+//!     [Bin::value("add", 0), Bin::value("sub", 1), Bin::range("other", 2..=7)],
+//! )?;
+//! group.coverpoint("carry", [])?; // one bin per value
+//! group.cross("op_x_carry", ["op", "carry"])?;
+//!
+//! alu.eval();
+//! group.sample(alu)?;
+//!
+//! println!("{}", group.report());
+//! # Ok(()) }
+//! ```
+
+use std::{
+    cell::RefCell, collections::HashMap, fmt, ops::RangeInclusive, rc::Rc,
+};
+
+use snafu::{ResultExt, Snafu};
+
+use crate::{
+    AsVerilatedModel, PortDirection,
+    dynamic::{
+        AsDynamicVerilatedModel, DynamicVerilatedModelError, VerilatorValue,
+    },
+};
+
+/// The most automatic bins a coverpoint without explicit bins gets, like
+/// `auto_bin_max` in SystemVerilog.
+const AUTO_BIN_MAX: u64 = 64;
+
+/// An error from defining or sampling a [`Covergroup`].
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum CovergroupError {
+    #[snafu(display("Covergroup {covergroup} has no port {port}"))]
+    NoSuchPort { covergroup: String, port: String },
+
+    #[snafu(display(
+        "Port {port} of covergroup {covergroup} has width {width}, but coverpoints support at most 64 bits"
+    ))]
+    PortTooWide {
+        covergroup: String,
+        port: String,
+        width: usize,
+    },
+
+    #[snafu(display("Covergroup {covergroup} has no coverpoint {coverpoint}"))]
+    NoSuchCoverpoint {
+        covergroup: String,
+        coverpoint: String,
+    },
+
+    #[snafu(display("Covergroup {covergroup} already has a {what} {name}"))]
+    Duplicate {
+        covergroup: String,
+        what: &'static str,
+        name: String,
+    },
+
+    #[snafu(display("Failed to sample covergroup {covergroup}"))]
+    Sample {
+        covergroup: String,
+        source: DynamicVerilatedModelError,
+    },
+}
+
+/// When a [`Covergroup`] samples the model passed to [`Covergroup::sample`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum SampleOn {
+    /// Every time, so you can sample after each `eval`.
+    #[default]
+    Always,
+    /// Only when the given (clock) port has risen since the previous call.
+    PosEdge(String),
+    /// Only when the given (clock) port has fallen since the previous call.
+    NegEdge(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum BinKind {
+    Range(RangeInclusive<u64>),
+    Transition(Vec<u64>),
+}
+
+/// A named set of values (or sequence of values) of a coverpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bin {
+    name: String,
+    kind: BinKind,
+}
+
+impl Bin {
+    /// Hit when the port has the value `value`.
+    pub fn value(name: impl Into<String>, value: u64) -> Self {
+        Self::range(name, value..=value)
+    }
+
+    /// Hit when the value of the port is in `range`.
+    pub fn range(name: impl Into<String>, range: RangeInclusive<u64>) -> Self {
+        Self {
+            name: name.into(),
+            kind: BinKind::Range(range),
+        }
+    }
+
+    /// Hit when the port takes the values in `sequence` in consecutive
+    /// samples, e.g., `[0, 1]` for a rising bit. Like `(0 => 1)` in
+    /// SystemVerilog.
+    pub fn transition(
+        name: impl Into<String>,
+        sequence: impl IntoIterator<Item = u64>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            kind: BinKind::Transition(sequence.into_iter().collect()),
+        }
+    }
+
+    /// The name of this bin.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Coverpoint {
+    port: String,
+    bins: Vec<Bin>,
+    hits: Vec<u64>,
+    /// The most recent values, oldest first, as long as the longest
+    /// transition.
+    history: Vec<u64>,
+}
+
+#[derive(Debug, Clone)]
+struct Cross {
+    name: String,
+    /// Indices into [`Covergroup::coverpoints`].
+    coverpoints: Vec<usize>,
+    /// The hits of each combination of bins, by bin index per coverpoint.
+    hits: HashMap<Vec<usize>, u64>,
+}
+
+/// Functional coverage over the ports of a model. See the [module-level
+/// documentation](self).
+#[derive(Debug, Clone)]
+pub struct Covergroup {
+    name: String,
+    /// The name and width of each port of the model.
+    ports: Vec<(String, usize)>,
+    sample_on: SampleOn,
+    previous_clock: Option<u64>,
+    samples: u64,
+    coverpoints: Vec<Coverpoint>,
+    crosses: Vec<Cross>,
+}
+
+fn value_to_u64(value: &VerilatorValue) -> Option<u64> {
+    match value {
+        VerilatorValue::CData(value) => Some((*value).into()),
+        VerilatorValue::SData(value) => Some((*value).into()),
+        VerilatorValue::IData(value) => Some((*value).into()),
+        VerilatorValue::QData(value) => Some(*value),
+        VerilatorValue::WDataInP(_) | VerilatorValue::WDataOutP(_) => None,
+    }
+}
+
+impl Covergroup {
+    /// Creates an empty covergroup over the ports of the model `M`.
+    pub fn new<'ctx, M: AsVerilatedModel<'ctx>>(
+        name: impl Into<String>,
+    ) -> Self {
+        Self::with_ports(name, M::ports())
+    }
+
+    /// Creates an empty covergroup over `ports`, e.g., those given to
+    /// [`crate::VerilatorRuntime::create_dyn_model`].
+    pub fn with_ports(
+        name: impl Into<String>,
+        ports: &[(&str, usize, usize, PortDirection)],
+    ) -> Self {
+        Self {
+            name: name.into(),
+            ports: ports
+                .iter()
+                .map(|(port, msb, lsb, _)| (port.to_string(), msb + 1 - lsb))
+                .collect(),
+            sample_on: SampleOn::Always,
+            previous_clock: None,
+            samples: 0,
+            coverpoints: vec![],
+            crosses: vec![],
+        }
+    }
+
+    /// Sets when this covergroup samples. See [`SampleOn`].
+    pub fn sample_on(mut self, sample_on: SampleOn) -> Self {
+        self.sample_on = sample_on;
+        self
+    }
+
+    fn port_width(&self, port: &str) -> Result<usize, CovergroupError> {
+        let Some((_, width)) = self.ports.iter().find(|(name, _)| name == port)
+        else {
+            return NoSuchPortSnafu {
+                covergroup: &self.name,
+                port,
+            }
+            .fail();
+        };
+        if *width > 64 {
+            return PortTooWideSnafu {
+                covergroup: &self.name,
+                port,
+                width: *width,
+            }
+            .fail();
+        }
+        Ok(*width)
+    }
+
+    /// Adds a coverpoint over `port` with the given `bins`. If there are no
+    /// bins, each value of the port gets its own bin, or, if there are more
+    /// than 64 values, each of 64 equal ranges of values does.
+    pub fn coverpoint(
+        &mut self,
+        port: &str,
+        bins: impl IntoIterator<Item = Bin>,
+    ) -> Result<&mut Self, CovergroupError> {
+        let width = self.port_width(port)?;
+        if self
+            .coverpoints
+            .iter()
+            .any(|coverpoint| coverpoint.port == port)
+        {
+            return DuplicateSnafu {
+                covergroup: &self.name,
+                what: "coverpoint",
+                name: port,
+            }
+            .fail();
+        }
+
+        let mut bins = bins.into_iter().collect::<Vec<_>>();
+        if bins.is_empty() {
+            let max = u64::MAX >> (64 - width);
+            let count = max.saturating_add(1).min(AUTO_BIN_MAX);
+            let step = max / count + 1;
+            bins = (0..count)
+                .map(|index| {
+                    let start = index * step;
+                    let end = if index + 1 == count {
+                        max
+                    } else {
+                        start + step - 1
+                    };
+                    if start == end {
+                        Bin::value(start.to_string(), start)
+                    } else {
+                        Bin::range(format!("{start}..={end}"), start..=end)
+                    }
+                })
+                .collect();
+        }
+
+        self.coverpoints.push(Coverpoint {
+            port: port.to_string(),
+            hits: vec![0; bins.len()],
+            bins,
+            history: vec![],
+        });
+        Ok(self)
+    }
+
+    /// Adds a cross named `name` of the given coverpoints (named by their
+    /// ports), which has a bin for every combination of their bins.
+    pub fn cross<'a>(
+        &mut self,
+        name: &str,
+        coverpoints: impl IntoIterator<Item = &'a str>,
+    ) -> Result<&mut Self, CovergroupError> {
+        if self.crosses.iter().any(|cross| cross.name == name) {
+            return DuplicateSnafu {
+                covergroup: &self.name,
+                what: "cross",
+                name,
+            }
+            .fail();
+        }
+        let coverpoints = coverpoints
+            .into_iter()
+            .map(|port| {
+                self.coverpoints
+                    .iter()
+                    .position(|coverpoint| coverpoint.port == port)
+                    .ok_or_else(|| {
+                        NoSuchCoverpointSnafu {
+                            covergroup: &self.name,
+                            coverpoint: port,
+                        }
+                        .build()
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.crosses.push(Cross {
+            name: name.to_string(),
+            coverpoints,
+            hits: HashMap::new(),
+        });
+        Ok(self)
+    }
+
+    /// Samples the ports of `model` (see [`Covergroup::sample_on`]). Call it
+    /// after every `eval`.
+    pub fn sample<'ctx>(
+        &mut self,
+        model: &impl AsDynamicVerilatedModel<'ctx>,
+    ) -> Result<(), CovergroupError> {
+        let read = |port: &str| {
+            model
+                .read_any_port(port)
+                .map(|value| value_to_u64(&value).unwrap_or_default())
+                .context(SampleSnafu {
+                    covergroup: &self.name,
+                })
+        };
+
+        let (clock, rising) = match &self.sample_on {
+            SampleOn::Always => (None, true),
+            SampleOn::PosEdge(clock) => (Some(clock), true),
+            SampleOn::NegEdge(clock) => (Some(clock), false),
+        };
+        if let Some(clock) = clock {
+            let value = read(clock)? & 1;
+            let previous = self.previous_clock.replace(value);
+            let edge = if rising { (0, 1) } else { (1, 0) };
+            if previous.map(|previous| (previous, value)) != Some(edge) {
+                return Ok(());
+            }
+        }
+
+        let values = self
+            .coverpoints
+            .iter()
+            .map(|coverpoint| read(&coverpoint.port))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.samples += 1;
+
+        let mut hit_bins = vec![];
+        for (coverpoint, value) in self.coverpoints.iter_mut().zip(values) {
+            let history_length = coverpoint
+                .bins
+                .iter()
+                .map(|bin| match &bin.kind {
+                    BinKind::Transition(sequence) => sequence.len(),
+                    BinKind::Range(_) => 1,
+                })
+                .max()
+                .unwrap_or(1);
+            coverpoint.history.push(value);
+            if coverpoint.history.len() > history_length {
+                coverpoint.history.remove(0);
+            }
+
+            let mut hit = vec![];
+            for (index, bin) in coverpoint.bins.iter().enumerate() {
+                let is_hit = match &bin.kind {
+                    BinKind::Range(range) => range.contains(&value),
+                    BinKind::Transition(sequence) => {
+                        !sequence.is_empty()
+                            && coverpoint.history.ends_with(sequence)
+                    }
+                };
+                if is_hit {
+                    coverpoint.hits[index] += 1;
+                    hit.push(index);
+                }
+            }
+            hit_bins.push(hit);
+        }
+
+        for cross in &mut self.crosses {
+            let mut combinations = vec![vec![]];
+            for coverpoint in &cross.coverpoints {
+                combinations = combinations
+                    .into_iter()
+                    .flat_map(|combination: Vec<usize>| {
+                        hit_bins[*coverpoint].iter().map(move |bin| {
+                            let mut combination = combination.clone();
+                            combination.push(*bin);
+                            combination
+                        })
+                    })
+                    .collect();
+            }
+            for combination in combinations {
+                *cross.hits.entry(combination).or_default() += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resets all hit counts, e.g., to exclude a reset sequence.
+    pub fn clear(&mut self) {
+        self.samples = 0;
+        self.previous_clock = None;
+        for coverpoint in &mut self.coverpoints {
+            coverpoint.hits.fill(0);
+            coverpoint.history.clear();
+        }
+        for cross in &mut self.crosses {
+            cross.hits.clear();
+        }
+    }
+
+    /// The hit counts of every bin of this covergroup.
+    pub fn report(&self) -> CovergroupReport {
+        let coverpoints = self
+            .coverpoints
+            .iter()
+            .map(|coverpoint| CoverpointReport {
+                name: coverpoint.port.clone(),
+                bins: coverpoint
+                    .bins
+                    .iter()
+                    .zip(&coverpoint.hits)
+                    .map(|(bin, hits)| BinReport {
+                        name: bin.name.clone(),
+                        hits: *hits,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let crosses = self
+            .crosses
+            .iter()
+            .map(|cross| {
+                let mut combinations = vec![vec![]];
+                for coverpoint in &cross.coverpoints {
+                    let bin_count = self.coverpoints[*coverpoint].bins.len();
+                    combinations = combinations
+                        .into_iter()
+                        .flat_map(|combination: Vec<usize>| {
+                            (0..bin_count).map(move |bin| {
+                                let mut combination = combination.clone();
+                                combination.push(bin);
+                                combination
+                            })
+                        })
+                        .collect();
+                }
+                CoverpointReport {
+                    name: cross.name.clone(),
+                    bins: combinations
+                        .into_iter()
+                        .map(|combination| {
+                            let names = cross
+                                .coverpoints
+                                .iter()
+                                .zip(&combination)
+                                .map(|(coverpoint, bin)| {
+                                    self.coverpoints[*coverpoint].bins[*bin]
+                                        .name
+                                        .as_str()
+                                })
+                                .collect::<Vec<_>>();
+                            BinReport {
+                                name: format!("<{}>", names.join(", ")),
+                                hits: cross
+                                    .hits
+                                    .get(&combination)
+                                    .copied()
+                                    .unwrap_or_default(),
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        CovergroupReport {
+            name: self.name.clone(),
+            samples: self.samples,
+            coverpoints,
+            crosses,
+        }
+    }
+}
+
+/// A [`Covergroup`] sampled after every evaluation of the model it was
+/// attached to with [`AsDynamicVerilatedModel::attach_covergroup`]. Clones
+/// refer to the same covergroup.
+#[derive(Debug, Clone)]
+pub struct AttachedCovergroup {
+    inner: Rc<RefCell<Covergroup>>,
+}
+
+impl AttachedCovergroup {
+    pub(crate) fn new(group: Covergroup) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(group)),
+        }
+    }
+
+    /// See [`Covergroup::report`].
+    pub fn report(&self) -> CovergroupReport {
+        self.inner.borrow().report()
+    }
+
+    /// See [`Covergroup::clear`].
+    pub fn clear(&self) {
+        self.inner.borrow_mut().clear();
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+    use crate::{VerilatorRuntimeError, error::CovergroupSampleSnafu};
+
+    /// Samples `model` for each of `covergroups` attached to it.
+    pub fn sample_attached<'ctx>(
+        covergroups: &[AttachedCovergroup],
+        model: &impl AsDynamicVerilatedModel<'ctx>,
+    ) -> Result<(), VerilatorRuntimeError> {
+        for group in covergroups {
+            group
+                .inner
+                .borrow_mut()
+                .sample(model)
+                .context(CovergroupSampleSnafu)?;
+        }
+        Ok(())
+    }
+}
+
+/// The hit count of a bin in a [`CovergroupReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BinReport {
+    /// The name of the bin, or, for a cross, the names of its bins like
+    /// `<add, 1>`.
+    pub name: String,
+    pub hits: u64,
+}
+
+/// The hit counts of the bins of a coverpoint or cross.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CoverpointReport {
+    pub name: String,
+    pub bins: Vec<BinReport>,
+}
+
+impl CoverpointReport {
+    /// The bins that were never hit.
+    pub fn holes(&self) -> impl Iterator<Item = &BinReport> {
+        self.bins.iter().filter(|bin| bin.hits == 0)
+    }
+
+    /// The percentage of bins that were hit.
+    pub fn coverage(&self) -> f64 {
+        if self.bins.is_empty() {
+            return 100.0;
+        }
+        let hit = self.bins.len() - self.holes().count();
+        100.0 * hit as f64 / self.bins.len() as f64
+    }
+}
+
+/// The hit counts of a [`Covergroup`], obtained with [`Covergroup::report`].
+/// Its [`fmt::Display`] implementation prints a table of the hit counts
+/// followed by the holes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CovergroupReport {
+    pub name: String,
+    /// How many times the covergroup sampled the model.
+    pub samples: u64,
+    pub coverpoints: Vec<CoverpointReport>,
+    pub crosses: Vec<CoverpointReport>,
+}
+
+impl CovergroupReport {
+    /// The average coverage of the coverpoints and crosses, like the coverage
+    /// of a SystemVerilog covergroup.
+    pub fn coverage(&self) -> f64 {
+        let count = self.coverpoints.len() + self.crosses.len();
+        if count == 0 {
+            return 100.0;
+        }
+        self.coverpoints
+            .iter()
+            .chain(&self.crosses)
+            .map(CoverpointReport::coverage)
+            .sum::<f64>()
+            / count as f64
+    }
+
+    /// Whether every bin was hit.
+    pub fn is_complete(&self) -> bool {
+        self.coverpoints
+            .iter()
+            .chain(&self.crosses)
+            .all(|report| report.holes().next().is_none())
+    }
+}
+
+impl fmt::Display for CovergroupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "covergroup {}: {:.1}% ({} samples)",
+            self.name,
+            self.coverage(),
+            self.samples
+        )?;
+        for (kind, report) in self
+            .coverpoints
+            .iter()
+            .map(|report| ("coverpoint", report))
+            .chain(self.crosses.iter().map(|report| ("cross", report)))
+        {
+            writeln!(f, "  {kind} {}: {:.1}%", report.name, report.coverage())?;
+            for bin in &report.bins {
+                writeln!(f, "    {:>10}  {}", bin.hits, bin.name)?;
+            }
+        }
+
+        let holes = self
+            .coverpoints
+            .iter()
+            .chain(&self.crosses)
+            .flat_map(|report| {
+                report.holes().map(move |bin| (&report.name, &bin.name))
+            })
+            .collect::<Vec<_>>();
+        if !holes.is_empty() {
+            writeln!(f, "  holes:")?;
+            for (report, bin) in holes {
+                writeln!(f, "    {report}: {bin}")?;
+            }
+        }
+        Ok(())
+    }
+}
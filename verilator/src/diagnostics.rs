@@ -0,0 +1,162 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Warnings and errors emitted by Verilator while building a model.
+
+use std::fmt;
+
+use camino::Utf8PathBuf;
+use owo_colors::OwoColorize;
+
+/// How severe a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => "warning".fmt(f),
+            Self::Error => "error".fmt(f),
+        }
+    }
+}
+
+/// A single `%Warning` or `%Error` message from Verilator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The warning or error code, e.g., `WIDTH` for `%Warning-WIDTH`.
+    pub code: Option<String>,
+    pub file: Option<Utf8PathBuf>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Formats the location of this diagnostic as `file:line:column`, if it
+    /// has one.
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_ref()?;
+        Some(match (self.line, self.column) {
+            (Some(line), Some(column)) => format!("{file}:{line}:{column}"),
+            (Some(line), None) => format!("{file}:{line}"),
+            _ => file.to_string(),
+        })
+    }
+
+    /// Renders this diagnostic like `rustc` does, with colors.
+    pub(crate) fn render(&self) -> String {
+        let mut header = match self.severity {
+            Severity::Warning => "warning".bold().yellow().to_string(),
+            Severity::Error => "error".bold().red().to_string(),
+        };
+        if let Some(code) = &self.code {
+            let code = format!("[{code}]");
+            header += &match self.severity {
+                Severity::Warning => code.bold().yellow().to_string(),
+                Severity::Error => code.bold().red().to_string(),
+            };
+        }
+        let mut rendered =
+            format!("{header}{} {}", ":".bold(), self.message.bold());
+        if let Some(location) = self.location() {
+            rendered += &format!("\n  {} {location}", "-->".bold().blue());
+        }
+        rendered
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.severity)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{code}]")?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(location) = self.location() {
+            write!(f, "\n  --> {location}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits `file:line:column: message` (or `file:line: message`) into its
+/// parts.
+pub(crate) fn parse_location(
+    text: &str,
+) -> Option<(Utf8PathBuf, usize, Option<usize>, &str)> {
+    let (location, message) = text.split_once(": ")?;
+    let (rest, last) = location.rsplit_once(':')?;
+    let last = last.parse().ok()?;
+    match rest
+        .rsplit_once(':')
+        .and_then(|(file, line)| Some((file, line.parse().ok()?)))
+    {
+        Some((file, line)) => Some((file.into(), line, Some(last), message)),
+        None => Some((rest.into(), last, None, message)),
+    }
+}
+
+/// Parses the `%Warning-CODE: file:line:column: message` and `%Error: ...`
+/// lines in the output of Verilator. Continuation lines (source excerpts and
+/// notes) and the final `Exiting due to ...` summary are skipped.
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for line in output.lines() {
+        let Some(line) = line.strip_prefix('%') else {
+            continue;
+        };
+        let Some((tag, rest)) = line.split_once(": ") else {
+            continue;
+        };
+        let (severity, code) = match tag.split_once('-') {
+            Some((severity, code)) => (severity, Some(code.to_string())),
+            None => (tag, None),
+        };
+        let severity = match severity {
+            "Warning" => Severity::Warning,
+            "Error" => Severity::Error,
+            _ => continue,
+        };
+        if rest.starts_with("Exiting due to") {
+            continue;
+        }
+
+        let (file, line, column, message) = match parse_location(rest) {
+            Some((file, line, column, message)) => {
+                (Some(file), Some(line), column, message)
+            }
+            None => (None, None, None, rest),
+        };
+        diagnostics.push(Diagnostic {
+            severity,
+            code,
+            file,
+            line,
+            column,
+            message: message.trim().to_string(),
+        });
+    }
+    diagnostics
+}
+
+/// What happened when building the library for a model.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildReport {
+    /// Whether Verilator was invoked, as opposed to an up-to-date library
+    /// being reused.
+    pub rebuilt: bool,
+
+    /// The warnings Verilator emitted, if it was invoked.
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// The files the build depended on.
+    pub dependencies: Vec<Utf8PathBuf>,
+}
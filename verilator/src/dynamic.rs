@@ -6,13 +6,23 @@
 
 //! Support for dynamic models.
 
-use std::{collections::HashMap, ffi, fmt, slice};
+use std::{collections::HashMap, ffi, fmt, path::Path, slice};
 
+use camino::Utf8Path;
 use libloading::Library;
 use snafu::Snafu;
 
 use crate::{
-    PortDirection, WideOut, compute_approx_width_from_wdata_word_count, types,
+    PortDirection, VerilatorRuntimeError, WideOut,
+    checkpoint::{self, __private::CheckpointApi},
+    compute_approx_width_from_wdata_word_count,
+    context::{
+        self, __private::ContextApi, DesignMessage, EvalStatus, SimContext,
+    },
+    covergroup::{self, AttachedCovergroup, Covergroup},
+    elf::{ElfImage, MemoryRegion},
+    memory::MemoryFormat,
+    types,
 };
 
 /// See [`types`].
@@ -102,8 +112,175 @@ impl<const WORDS: usize> From<WideOut<WORDS>> for VerilatorValue<'_> {
 
 /// Access model ports at runtime.
 pub trait AsDynamicVerilatedModel<'ctx>: 'ctx {
-    /// Equivalent to the Verilator `eval` method.
-    fn eval(&mut self);
+    /// Equivalent to the Verilator `eval` method. Returns whether the design
+    /// has executed `$finish` or `$stop`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the design failed an assertion or hit a fatal error. Use
+    /// [`AsDynamicVerilatedModel::try_eval`] to handle the failure instead.
+    fn eval(&mut self) -> EvalStatus {
+        match self.try_eval() {
+            Ok(status) => status,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Like [`AsDynamicVerilatedModel::eval`], but returns
+    /// [`VerilatorRuntimeError::AssertionFailed`] if the design failed an
+    /// assertion (see [`crate::VerilatedModelConfig::assertions`]) or hit a
+    /// fatal error. The model keeps failing until the failure is cleared with
+    /// [`SimContext::clear_finish`].
+    fn try_eval(&mut self) -> Result<EvalStatus, VerilatorRuntimeError>;
+
+    /// The `VerilatedContext` of this model.
+    fn context(&mut self) -> SimContext<'_>;
+
+    /// Advances the simulation time to the next delay or event control the
+    /// design is waiting for (see [`SimContext::next_time_slot`]) and
+    /// evaluates the model there, returning the new time. Returns `None`
+    /// without evaluating if nothing is pending.
+    fn step_to_next_event(
+        &mut self,
+    ) -> Result<Option<u64>, VerilatorRuntimeError> {
+        let Some(time) = self.context().next_time_slot() else {
+            return Ok(None);
+        };
+        self.context().set_time(time);
+        self.try_eval()?;
+        Ok(Some(time))
+    }
+
+    /// Evaluates the model at the current time and then at every later time
+    /// the design is waiting for up to and including `time`, after which the
+    /// simulation time is `time`. Stops early if the design executes `$finish`
+    /// or `$stop`, returning the status of the last evaluation.
+    ///
+    /// This is the event loop for models built with
+    /// [`crate::VerilatedModelConfig::timing`], e.g., to run clock generators
+    /// and bus-functional models written in SystemVerilog. Pin inputs between
+    /// calls.
+    fn run_until(
+        &mut self,
+        time: u64,
+    ) -> Result<EvalStatus, VerilatorRuntimeError> {
+        let mut status = self.try_eval()?;
+        while status.is_running() {
+            match self.context().next_time_slot() {
+                Some(next) if next <= time => {
+                    self.context().set_time(next);
+                    status = self.try_eval()?;
+                }
+                _ => break,
+            }
+        }
+        if status.is_running() && self.context().time() < time {
+            self.context().set_time(time);
+        }
+        Ok(status)
+    }
+
+    /// Saves the state of this model, including its simulation time, to a
+    /// checkpoint at `path`. The model must have been built with
+    /// [`crate::VerilatedModelConfig::savable`].
+    fn save(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), VerilatorRuntimeError>;
+
+    /// Restores the state of this model from the checkpoint at `path`, which
+    /// must have been saved by a model from the same build. See
+    /// [`AsDynamicVerilatedModel::save`].
+    fn restore(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), VerilatorRuntimeError>;
+
+    /// Removes and returns what the design has printed. See
+    /// [`SimContext::take_output`].
+    fn take_output(&mut self) -> String {
+        self.context().take_output()
+    }
+
+    /// Removes and returns the `$info`, `$warning`, `$error`, and `$fatal`
+    /// messages the design has printed. See [`SimContext::take_messages`].
+    fn take_messages(&mut self) -> Vec<DesignMessage> {
+        self.context().take_messages()
+    }
+
+    /// Reads the public internal signal at the hierarchical `path`, e.g.,
+    /// `main.core.state`. See [`SimContext::peek`].
+    fn peek(
+        &mut self,
+        path: &str,
+    ) -> Result<VerilatorValue<'static>, VerilatorRuntimeError> {
+        self.context().peek(path)
+    }
+
+    /// Writes `value` to the public internal signal at the hierarchical
+    /// `path`. See [`SimContext::poke`].
+    fn poke<'a>(
+        &mut self,
+        path: &str,
+        value: impl Into<VerilatorValue<'a>>,
+    ) -> Result<(), VerilatorRuntimeError> {
+        self.context().poke(path, value)
+    }
+
+    /// Reads every word of the memory at the hierarchical `path`, e.g.,
+    /// `main.ram`. See [`SimContext::read_memory`].
+    fn read_memory(
+        &mut self,
+        path: &str,
+    ) -> Result<Vec<u64>, VerilatorRuntimeError> {
+        self.context().read_memory(path)
+    }
+
+    /// Writes `values` to the memory at the hierarchical `path`, starting at
+    /// `address`. See [`SimContext::write_memory`].
+    fn write_memory(
+        &mut self,
+        path: &str,
+        address: i64,
+        values: &[u64],
+    ) -> Result<(), VerilatorRuntimeError> {
+        self.context().write_memory(path, address, values)
+    }
+
+    /// Loads the memory at the hierarchical `path` from `file`. See
+    /// [`SimContext::load_memory`].
+    fn load_memory(
+        &mut self,
+        path: &str,
+        file: impl AsRef<Utf8Path>,
+        format: MemoryFormat,
+    ) -> Result<(), VerilatorRuntimeError> {
+        self.context().load_memory(path, file, format)
+    }
+
+    /// Writes the contents of the memory at the hierarchical `path` to
+    /// `file`. See [`SimContext::dump_memory`].
+    fn dump_memory(
+        &mut self,
+        path: &str,
+        file: impl AsRef<Utf8Path>,
+        format: MemoryFormat,
+    ) -> Result<(), VerilatorRuntimeError> {
+        self.context().dump_memory(path, file, format)
+    }
+
+    /// Reads the ELF file at `file` and writes its segments into the memories
+    /// of this model, returning its entry point and symbol table. See
+    /// [`ElfImage::load_into`].
+    fn load_elf(
+        &mut self,
+        file: impl AsRef<Utf8Path>,
+        regions: &[MemoryRegion],
+    ) -> Result<ElfImage, VerilatorRuntimeError> {
+        let image = ElfImage::read(file)?;
+        image.load_into(&mut self.context(), regions)?;
+        Ok(image)
+    }
 
     /// If `port` is a valid port name for this model, returns the current value
     /// of the port.
@@ -112,6 +289,34 @@ pub trait AsDynamicVerilatedModel<'ctx>: 'ctx {
         port: impl Into<String>,
     ) -> Result<VerilatorValue<'_>, DynamicVerilatedModelError>;
 
+    /// Samples `group` after every evaluation of this model from now on, e.g.,
+    /// in [`AsDynamicVerilatedModel::run_until`], instead of after each call
+    /// to [`Covergroup::sample`]. Returns a handle to the covergroup for
+    /// reading its report. A sampling failure is returned as
+    /// [`VerilatorRuntimeError::CovergroupSample`] by
+    /// [`AsDynamicVerilatedModel::try_eval`].
+    ///
+    /// Forks of this model (see [`crate::VerilatorRuntime::fork`]) start
+    /// without covergroups.
+    fn attach_covergroup(&mut self, group: Covergroup) -> AttachedCovergroup {
+        let group = AttachedCovergroup::new(group);
+        self.attached_covergroups().push(group.clone());
+        group
+    }
+
+    /// The covergroups sampled after every evaluation.
+    #[doc(hidden)]
+    fn attached_covergroups(&mut self) -> &mut Vec<AttachedCovergroup>;
+
+    /// Like [`AsDynamicVerilatedModel::read`], but input ports can be read
+    /// too, e.g., to sample them for a [`crate::covergroup::Covergroup`]. An
+    /// input reads as the value it was last pinned to.
+    #[doc(hidden)]
+    fn read_any_port(
+        &self,
+        port: impl Into<String>,
+    ) -> Result<VerilatorValue<'_>, DynamicVerilatedModelError>;
+
     /// If `port` is a valid port name for this model, and the port's width is
     /// `<=` `value.into().width()`, sets the port to `value`.
     fn pin(
@@ -134,7 +339,10 @@ pub struct DynamicVerilatedModel<'ctx> {
     pub(crate) ports: HashMap<String, DynamicPortInfo>,
     pub(crate) name: String,
     pub(crate) main: *mut ffi::c_void,
-    pub(crate) eval_main: extern "C" fn(*mut ffi::c_void),
+    pub(crate) eval_main: extern "C" fn(*mut ffi::c_void) -> ffi::c_int,
+    pub(crate) context_api: ContextApi,
+    pub(crate) checkpoint_api: Option<CheckpointApi>,
+    pub(crate) covergroups: Vec<AttachedCovergroup>,
     pub(crate) library: &'ctx Library,
 }
 
@@ -172,8 +380,47 @@ pub enum DynamicVerilatedModelError {
 }
 
 impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
-    fn eval(&mut self) {
-        (self.eval_main)(self.main);
+    fn try_eval(&mut self) -> Result<EvalStatus, VerilatorRuntimeError> {
+        let status = (self.eval_main)(self.main);
+        let status = context::__private::after_eval(
+            &self.context_api,
+            self.main,
+            status,
+        )?;
+        covergroup::__private::sample_attached(&self.covergroups, self)?;
+        Ok(status)
+    }
+
+    fn attached_covergroups(&mut self) -> &mut Vec<AttachedCovergroup> {
+        &mut self.covergroups
+    }
+
+    fn context(&mut self) -> SimContext<'_> {
+        context::__private::new_sim_context(&self.context_api, self.main)
+    }
+
+    fn save(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), VerilatorRuntimeError> {
+        checkpoint::__private::save(
+            self.checkpoint_api.as_ref(),
+            self.main,
+            &self.name,
+            path.as_ref(),
+        )
+    }
+
+    fn restore(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), VerilatorRuntimeError> {
+        checkpoint::__private::restore(
+            self.checkpoint_api.as_ref(),
+            self.main,
+            &self.name,
+            path.as_ref(),
+        )
     }
 
     fn read(
@@ -181,14 +428,13 @@ impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
         port: impl Into<String>,
     ) -> Result<VerilatorValue<'_>, DynamicVerilatedModelError> {
         let port: String = port.into();
-        let DynamicPortInfo { width, direction } = *self
-            .ports
-            .get(&port)
-            .ok_or(DynamicVerilatedModelError::NoSuchPort {
+        let DynamicPortInfo { direction, .. } = *self.ports.get(&port).ok_or(
+            DynamicVerilatedModelError::NoSuchPort {
                 top_module: self.name.clone(),
                 port: port.clone(),
                 source: None,
-            })?;
+            },
+        )?;
 
         if !matches!(direction, PortDirection::Output | PortDirection::Inout,) {
             return Err(DynamicVerilatedModelError::InvalidPortDirection {
@@ -199,6 +445,22 @@ impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
             });
         }
 
+        self.read_any_port(port)
+    }
+
+    fn read_any_port(
+        &self,
+        port: impl Into<String>,
+    ) -> Result<VerilatorValue<'_>, DynamicVerilatedModelError> {
+        let port: String = port.into();
+        let DynamicPortInfo { width, .. } = *self.ports.get(&port).ok_or(
+            DynamicVerilatedModelError::NoSuchPort {
+                top_module: self.name.clone(),
+                port: port.clone(),
+                source: None,
+            },
+        )?;
+
         macro_rules! read_value {
             ($self:ident, $port:expr, $value_type:ty) => {{
                 let symbol: libloading::Symbol<
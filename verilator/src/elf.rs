@@ -0,0 +1,374 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Loading ELF program images into the memories of a design, e.g., to run a
+//! compiled test program on a CPU.
+//!
+//! An [`ElfImage`] holds the loadable segments, entry point, and symbol table
+//! of an ELF file. [`ElfImage::load_into`] writes its segments into the
+//! memories of a model (see [`crate::memory`]) according to a list of
+//! [`MemoryRegion`]s, which say where each memory is in the address space of
+//! the program. [`crate::AsDynamicVerilatedModel::load_elf`] does both at
+//! once.
+
+use std::{collections::BTreeMap, fs, ops::Range};
+
+use camino::Utf8Path;
+use snafu::{FromString, ResultExt, Whatever, whatever};
+
+use crate::{SimContext, VerilatorRuntimeError};
+
+/// A loadable (`PT_LOAD`) segment of an ELF file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfSegment {
+    /// The physical address the segment is loaded at, like `objcopy` uses.
+    pub address: u64,
+    /// The contents of the segment, including the zeros that fill its memory
+    /// size past its file size (e.g., `.bss`).
+    pub data: Vec<u8>,
+}
+
+/// The parts of an ELF file needed to run it on a design.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfImage {
+    /// The address of the first instruction to execute.
+    pub entry: u64,
+    /// Whether the file is little-endian, which is also the byte order used
+    /// to pack its bytes into memory words.
+    pub little_endian: bool,
+    pub segments: Vec<ElfSegment>,
+    /// The address of every named symbol in the symbol table, e.g., `tohost`
+    /// or `_end`.
+    pub symbols: BTreeMap<String, u64>,
+}
+
+/// Where a memory of the design is in the address space of a program: the
+/// byte at `base` is the first byte of the lowest address of the memory at
+/// the hierarchical `path` (see [`SimContext::memory_layout`]).
+///
+/// The words of the memory must be a whole number of bytes wide. Consecutive
+/// addresses of the memory hold consecutive words of the program.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MemoryRegion {
+    pub path: String,
+    pub base: u64,
+}
+
+impl MemoryRegion {
+    pub fn new(path: impl Into<String>, base: u64) -> Self {
+        Self {
+            path: path.into(),
+            base,
+        }
+    }
+}
+
+const PT_LOAD: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const STT_SECTION: u8 = 3;
+const STT_FILE: u8 = 4;
+
+/// Reads the fields of an ELF file, which are sized and ordered according to
+/// its class and data encoding.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    is_64: bool,
+    little_endian: bool,
+}
+
+impl Reader<'_> {
+    fn read<const N: usize>(&self, offset: u64) -> Result<[u8; N], Whatever> {
+        let Some(bytes) = usize::try_from(offset)
+            .ok()
+            .and_then(|offset| self.bytes.get(offset..offset.checked_add(N)?))
+        else {
+            whatever!("ELF file is truncated at offset {offset:#x}");
+        };
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        if !self.little_endian {
+            array.reverse();
+        }
+        Ok(array)
+    }
+
+    fn u8(&self, offset: u64) -> Result<u8, Whatever> {
+        Ok(self.read::<1>(offset)?[0])
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16, Whatever> {
+        Ok(u16::from_le_bytes(self.read(offset)?))
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32, Whatever> {
+        Ok(u32::from_le_bytes(self.read(offset)?))
+    }
+
+    fn u64(&self, offset: u64) -> Result<u64, Whatever> {
+        Ok(u64::from_le_bytes(self.read(offset)?))
+    }
+
+    /// Reads an address or offset, which has the width of the class.
+    fn word(&self, offset: u64) -> Result<u64, Whatever> {
+        if self.is_64 {
+            self.u64(offset)
+        } else {
+            self.u32(offset).map(u64::from)
+        }
+    }
+
+    fn slice(&self, offset: u64, length: u64) -> Result<&[u8], Whatever> {
+        let Some(bytes) = usize::try_from(offset).ok().and_then(|offset| {
+            self.bytes.get(
+                offset..offset.checked_add(usize::try_from(length).ok()?)?,
+            )
+        }) else {
+            whatever!(
+                "ELF file is truncated reading {length} bytes at offset {offset:#x}"
+            );
+        };
+        Ok(bytes)
+    }
+
+    /// Reads a NUL-terminated string.
+    fn string(&self, offset: u64) -> Result<String, Whatever> {
+        let Some(bytes) = usize::try_from(offset)
+            .ok()
+            .and_then(|offset| self.bytes.get(offset..))
+        else {
+            whatever!("ELF file is truncated at offset {offset:#x}");
+        };
+        let Some(length) = bytes.iter().position(|byte| *byte == 0) else {
+            whatever!("Unterminated string in ELF file at offset {offset:#x}");
+        };
+        Ok(String::from_utf8_lossy(&bytes[..length]).into_owned())
+    }
+}
+
+impl ElfImage {
+    /// Parses the contents of a 32-bit or 64-bit ELF file of either byte
+    /// order.
+    pub fn parse(bytes: &[u8]) -> Result<Self, VerilatorRuntimeError> {
+        Ok(Self::parse_elf(bytes)?)
+    }
+
+    fn parse_elf(bytes: &[u8]) -> Result<Self, Whatever> {
+        if !bytes.starts_with(b"\x7fELF") || bytes.len() < 6 {
+            whatever!("Not an ELF file");
+        }
+        let is_64 = match bytes[4] {
+            1 => false,
+            2 => true,
+            class => whatever!("Unknown ELF class {class}"),
+        };
+        let little_endian = match bytes[5] {
+            1 => true,
+            2 => false,
+            encoding => whatever!("Unknown ELF data encoding {encoding}"),
+        };
+        let reader = Reader {
+            bytes,
+            is_64,
+            little_endian,
+        };
+
+        // the offsets of the fields in the headers of each class
+        let (entry, program_headers, section_headers, header_sizes) = if is_64 {
+            (24, 32, 40, 54)
+        } else {
+            (24, 28, 32, 42)
+        };
+        let entry = reader.word(entry)?;
+        let program_header_offset = reader.word(program_headers)?;
+        let section_header_offset = reader.word(section_headers)?;
+        let program_header_size = u64::from(reader.u16(header_sizes)?);
+        let program_header_count = u64::from(reader.u16(header_sizes + 2)?);
+        let section_header_size = u64::from(reader.u16(header_sizes + 4)?);
+        let section_header_count = u64::from(reader.u16(header_sizes + 6)?);
+
+        let mut segments = vec![];
+        for index in 0..program_header_count {
+            let header = program_header_offset + index * program_header_size;
+            if reader.u32(header)? != PT_LOAD {
+                continue;
+            }
+            let (offset, address, file_size, memory_size) = if is_64 {
+                (
+                    reader.word(header + 8)?,
+                    reader.word(header + 24)?,
+                    reader.word(header + 32)?,
+                    reader.word(header + 40)?,
+                )
+            } else {
+                (
+                    reader.word(header + 4)?,
+                    reader.word(header + 12)?,
+                    reader.word(header + 16)?,
+                    reader.word(header + 20)?,
+                )
+            };
+            if memory_size == 0 {
+                continue;
+            }
+            if file_size > memory_size {
+                whatever!(
+                    "ELF segment at {address:#x} has more bytes in the file than in memory"
+                );
+            }
+            let mut data = reader.slice(offset, file_size)?.to_vec();
+            data.resize(memory_size as usize, 0);
+            segments.push(ElfSegment { address, data });
+        }
+
+        let mut symbols = BTreeMap::new();
+        for index in 0..section_header_count {
+            let header = section_header_offset + index * section_header_size;
+            if reader.u32(header + 4)? != SHT_SYMTAB {
+                continue;
+            }
+            let (offset, size, link, entry_size) = if is_64 {
+                (
+                    reader.word(header + 24)?,
+                    reader.word(header + 32)?,
+                    reader.u32(header + 40)?,
+                    reader.word(header + 56)?,
+                )
+            } else {
+                (
+                    reader.word(header + 16)?,
+                    reader.word(header + 20)?,
+                    reader.u32(header + 24)?,
+                    reader.word(header + 36)?,
+                )
+            };
+            if entry_size == 0 {
+                whatever!("ELF symbol table has entries of size 0");
+            }
+            // the names are in the string table linked to the symbol table
+            let strings = section_header_offset
+                + u64::from(link) * section_header_size
+                + if is_64 { 24 } else { 16 };
+            let strings = reader.word(strings)?;
+
+            for symbol in (offset..offset + size).step_by(entry_size as usize) {
+                let (value, info) = if is_64 {
+                    (reader.word(symbol + 8)?, reader.u8(symbol + 4)?)
+                } else {
+                    (reader.word(symbol + 4)?, reader.u8(symbol + 12)?)
+                };
+                if matches!(info & 0xf, STT_SECTION | STT_FILE) {
+                    continue;
+                }
+                let name =
+                    reader.string(strings + u64::from(reader.u32(symbol)?))?;
+                if !name.is_empty() {
+                    symbols.entry(name).or_insert(value);
+                }
+            }
+        }
+
+        Ok(Self {
+            entry,
+            little_endian,
+            segments,
+            symbols,
+        })
+    }
+
+    /// Reads and parses the ELF file at `path`.
+    pub fn read(
+        path: impl AsRef<Utf8Path>,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).whatever_context::<_, Whatever>(format!(
+            "Failed to read ELF file {path}"
+        ))?;
+        Self::parse(&bytes)
+    }
+
+    /// The address of the symbol `name`, e.g., `tohost`.
+    pub fn symbol(&self, name: &str) -> Option<u64> {
+        self.symbols.get(name).copied()
+    }
+
+    /// Writes every segment of this image into the memories of the model
+    /// owning `context`, which must have been built with
+    /// [`crate::VerilatedModelConfig::public_signals`]. Each byte of a
+    /// segment must fall in one of the `regions`. Words only partly covered
+    /// by a segment keep their other bytes.
+    pub fn load_into(
+        &self,
+        context: &mut SimContext<'_>,
+        regions: &[MemoryRegion],
+    ) -> Result<(), VerilatorRuntimeError> {
+        struct Contents {
+            range: Range<u64>,
+            word_bytes: u64,
+            low: i64,
+            words: Vec<u64>,
+            changed: bool,
+        }
+
+        // each region is read, patched with the segments, and written back
+        let mut contents = Vec::with_capacity(regions.len());
+        for region in regions {
+            let layout = context.memory_layout(&region.path)?;
+            if layout.width % 8 != 0 {
+                return Err(Whatever::without_source(format!(
+                    "Memory {} has {}-bit words, which are not a whole number of bytes",
+                    region.path, layout.width
+                ))
+                .into());
+            }
+            let word_bytes = (layout.width / 8) as u64;
+            let size = layout.depth() as u64 * word_bytes;
+            contents.push(Contents {
+                range: region.base..region.base + size,
+                word_bytes,
+                low: layout.low,
+                words: context.read_memory(&region.path)?,
+                changed: false,
+            });
+        }
+
+        for segment in &self.segments {
+            for (offset, byte) in segment.data.iter().enumerate() {
+                let address = segment.address + offset as u64;
+                let Some(region) = contents
+                    .iter_mut()
+                    .find(|region| region.range.contains(&address))
+                else {
+                    return Err(Whatever::without_source(format!(
+                        "Address {address:#x} of the ELF segment at {:#x} is not in any memory region",
+                        segment.address
+                    ))
+                    .into());
+                };
+                let offset = address - region.range.start;
+                let word =
+                    &mut region.words[(offset / region.word_bytes) as usize];
+                let mut byte_index = offset % region.word_bytes;
+                if !self.little_endian {
+                    byte_index = region.word_bytes - 1 - byte_index;
+                }
+                *word &= !(0xff << (8 * byte_index));
+                *word |= u64::from(*byte) << (8 * byte_index);
+                region.changed = true;
+            }
+        }
+
+        for (region, contents) in regions.iter().zip(contents) {
+            if contents.changed {
+                context.write_memory(
+                    &region.path,
+                    contents.low,
+                    &contents.words,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
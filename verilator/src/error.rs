@@ -0,0 +1,212 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The error type returned by the [`crate::VerilatorRuntime`].
+
+use std::{io, path::PathBuf, process::ExitStatus};
+
+use camino::Utf8PathBuf;
+use snafu::{Snafu, Whatever};
+
+use crate::{
+    covergroup::CovergroupError, diagnostics::Diagnostic,
+    version::VerilatorVersion,
+};
+
+/// An error from building, loading, or instantiating a model.
+///
+/// This type does not convert into a [`Whatever`]; to propagate it from a
+/// function returning `Result<_, Whatever>`, attach a message with
+/// [`snafu::ResultExt::whatever_context`].
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[non_exhaustive]
+pub enum VerilatorRuntimeError {
+    /// The Verilator executable could not be run, e.g., because it is not
+    /// installed.
+    #[snafu(display(
+        "Failed to invoke `{executable}` (is Verilator installed and on the `PATH`?)"
+    ))]
+    VerilatorNotFound {
+        executable: String,
+        source: io::Error,
+    },
+
+    /// The Verilator invoked is older than Marlin supports, or its version
+    /// could not be determined. See [`crate::version::VerilatorInfo`].
+    #[snafu(display(
+        "Unsupported Verilator version {version}, need >= {minimum}"
+    ))]
+    UnsupportedVerilator {
+        /// The version, or the output of `verilator --version` if it could
+        /// not be parsed.
+        version: String,
+        minimum: VerilatorVersion,
+    },
+
+    /// `verilator --version` exited unsuccessfully, e.g., because the
+    /// Verilator installation is broken.
+    #[snafu(display(
+        "`{executable} --version` failed with nonzero exit code {status}\n\n--- STDERR ---\n{stderr}"
+    ))]
+    VerilatorVersionFailed {
+        executable: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+
+    /// Verilator exited unsuccessfully, e.g., because the Verilog source code
+    /// has a syntax error.
+    #[snafu(display(
+        "Invocation of verilator for module {top_module} failed with nonzero exit code {status}\n\n--- STDOUT ---\n{stdout}\n\n--- STDERR ---\n{stderr}"
+    ))]
+    VerilatorFailed {
+        top_module: String,
+        status: ExitStatus,
+        stdout: String,
+        stderr: String,
+        /// The warnings and errors parsed from `stdout` and `stderr`.
+        diagnostics: Vec<Diagnostic>,
+    },
+
+    /// A source file given to the runtime does not exist.
+    #[snafu(display(
+        "Source file {path} does not exist or is not a file. Note that if it's a relative path, you must be in the correct directory"
+    ))]
+    MissingSourceFile { path: Utf8PathBuf },
+
+    /// The source file defining a model was not given to the runtime.
+    #[snafu(display(
+        "Module `{top_module}` requires source file {source_path}, which was not provided to the runtime"
+    ))]
+    SourceFileNotProvided {
+        top_module: String,
+        source_path: String,
+    },
+
+    /// A built library could not be loaded.
+    #[snafu(display("Failed to load verilator dynamic library {path}"))]
+    LibraryLoad {
+        path: Utf8PathBuf,
+        source: libloading::Error,
+    },
+
+    /// A symbol is missing from a loaded library.
+    #[snafu(display("Failed to load symbol {symbol} for module {top_module}"))]
+    SymbolNotFound {
+        top_module: String,
+        symbol: String,
+        source: libloading::Error,
+    },
+
+    /// The lock synchronizing builds in an artifact directory could not be
+    /// acquired.
+    #[snafu(display(
+        "Failed to acquire file lock for artifacts directory {directory}"
+    ))]
+    Lock {
+        directory: Utf8PathBuf,
+        source: io::Error,
+    },
+
+    /// The runtime only loads prebuilt libraries, but the library for a model
+    /// is missing or out of date.
+    #[snafu(display(
+        "No up-to-date prebuilt library for module {top_module} in {directory}: {reason}"
+    ))]
+    PrebuiltUnavailable {
+        top_module: String,
+        directory: Utf8PathBuf,
+        reason: String,
+    },
+
+    /// A library loaded with [`crate::VerilatorRuntime::load_prebuilt`] was
+    /// built by a different version of Marlin or for a different model than
+    /// the one being created, or its metadata is missing or invalid.
+    #[snafu(display("Cannot use prebuilt library {path}: {reason}"))]
+    PrebuiltMismatch { path: Utf8PathBuf, reason: String },
+
+    /// A model was saved or restored without being built with
+    /// [`crate::VerilatedModelConfig::savable`].
+    #[snafu(display(
+        "Module {top_module} was not built with `savable` enabled, so it cannot be saved or restored"
+    ))]
+    NotSavable { top_module: String },
+
+    /// A checkpoint could not be saved or restored.
+    #[snafu(display("Failed to save or restore checkpoint {}: {reason}", path.display()))]
+    Checkpoint { path: PathBuf, reason: String },
+
+    /// A checkpoint was saved by a different build of a model than the one it
+    /// is being restored into, e.g., because the Verilog source code or the
+    /// configuration of the model changed.
+    #[snafu(display(
+        "Checkpoint {} was saved by a different build of module {top_module}",
+        path.display()
+    ))]
+    CheckpointMismatch { top_module: String, path: PathBuf },
+
+    /// A model built with [`crate::VerilatedModelConfig::assertions`] failed
+    /// an assertion (or executed `$error` or `$fatal`), or any model hit a
+    /// fatal error in the Verilator runtime.
+    #[snafu(display("{message}{}", format_location(file, line)))]
+    AssertionFailed {
+        file: Option<Utf8PathBuf>,
+        line: Option<usize>,
+        message: String,
+    },
+
+    /// A hierarchical path passed to [`crate::AsDynamicVerilatedModel::peek`]
+    /// or [`crate::AsDynamicVerilatedModel::poke`] does not name a public
+    /// signal.
+    #[snafu(display(
+        "No public signal {path}: check the path, mark the signal with `/*verilator public*/` or build with `PublicSignals::All`, and make sure Verilator did not optimize it away"
+    ))]
+    NoSuchSignal { path: String },
+
+    /// A value poked into a signal does not fit in the signal.
+    #[snafu(display("Value does not fit in signal {path} of width {width}"))]
+    SignalValueTooWide { path: String, width: usize },
+
+    /// A path passed to [`crate::SimContext::memory_layout`] and friends names
+    /// a public signal that is not a memory.
+    #[snafu(display("Signal {path} is not a memory"))]
+    NotAMemory { path: String },
+
+    /// Words were written to addresses outside of a memory.
+    #[snafu(display(
+        "Cannot write {count} words at address {address} of memory {path}, whose addresses are {low} to {high}"
+    ))]
+    MemoryOutOfBounds {
+        path: String,
+        address: i64,
+        count: usize,
+        low: i64,
+        high: i64,
+    },
+
+    /// A covergroup attached to a model with
+    /// [`crate::AsDynamicVerilatedModel::attach_covergroup`] failed to sample
+    /// it after an evaluation.
+    #[snafu(display("{source}"))]
+    CovergroupSample { source: CovergroupError },
+
+    /// A model's interface or configuration is invalid.
+    #[snafu(display("{message}"))]
+    InvalidConfiguration { message: String },
+
+    /// Any other failure, e.g., an I/O error in the artifact directory.
+    #[snafu(transparent)]
+    Other { source: Whatever },
+}
+
+fn format_location(file: &Option<Utf8PathBuf>, line: &Option<usize>) -> String {
+    match (file, line) {
+        (Some(file), Some(line)) => format!("\n  --> {file}:{line}"),
+        (Some(file), None) => format!("\n  --> {file}"),
+        _ => String::new(),
+    }
+}
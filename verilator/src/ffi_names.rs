@@ -31,6 +31,60 @@ pub fn delete_top(top_module: &str) -> String {
     format!("ffi_delete_V{top_module}")
 }
 
+pub fn top_contextp(top_module: &str) -> String {
+    format!("ffi_V{top_module}_contextp")
+}
+
+pub const CONTEXT_TIME: &str = "ffi_VerilatedContext_time";
+pub const CONTEXT_SET_TIME: &str = "ffi_VerilatedContext_set_time";
+pub const CONTEXT_TIME_INC: &str = "ffi_VerilatedContext_time_inc";
+pub const CONTEXT_STATUS: &str = "ffi_VerilatedContext_status";
+pub const CONTEXT_CLEAR_FINISH: &str = "ffi_VerilatedContext_clear_finish";
+pub const CONTEXT_SEED: &str = "ffi_VerilatedContext_seed";
+pub const CONTEXT_OUTPUT: &str = "ffi_VerilatedContext_output";
+pub const CONTEXT_CLEAR_OUTPUT: &str = "ffi_VerilatedContext_clear_output";
+pub const CONTEXT_SET_CAPTURE_OUTPUT: &str =
+    "ffi_VerilatedContext_set_capture_output";
+pub const CONTEXT_SET_COVERAGE_PATH: &str =
+    "ffi_VerilatedContext_set_coverage_path";
+pub const CONTEXT_WRITE_COVERAGE: &str = "ffi_VerilatedContext_write_coverage";
+
+/// The header declaring the replacements for `VL_PRINTF` and `VL_VPRINTF`,
+/// which is included in every file Verilator compiles.
+pub const PRINT_HEADER: &str = "marlin_print.h";
+pub const VL_PRINTF: &str = "marlin_vl_printf";
+pub const VL_VPRINTF: &str = "marlin_vl_vprintf";
+
+pub const CHECKPOINT_SET_TAG: &str = "ffi_marlin_set_checkpoint_tag";
+
+/// Access to public internal signals by hierarchical path, through VPI.
+pub const SIGNAL_WIDTH: &str = "ffi_marlin_signal_width";
+pub const SIGNAL_READ: &str = "ffi_marlin_signal_read";
+pub const SIGNAL_WRITE: &str = "ffi_marlin_signal_write";
+pub const MEMORY_LAYOUT: &str = "ffi_marlin_memory_layout";
+pub const MEMORY_READ: &str = "ffi_marlin_memory_read";
+pub const MEMORY_WRITE: &str = "ffi_marlin_memory_write";
+
+pub fn top_save(top_module: &str) -> String {
+    format!("ffi_V{top_module}_save")
+}
+
+pub fn top_restore(top_module: &str) -> String {
+    format!("ffi_V{top_module}_restore")
+}
+
+pub fn top_fork(top_module: &str) -> String {
+    format!("ffi_V{top_module}_fork")
+}
+
+pub fn top_events_pending(top_module: &str) -> String {
+    format!("ffi_V{top_module}_events_pending")
+}
+
+pub fn top_next_time_slot(top_module: &str) -> String {
+    format!("ffi_V{top_module}_next_time_slot")
+}
+
 pub fn pin_port(top_module: &str, port: &str) -> String {
     format!("ffi_V{top_module}_pin_{port}")
 }
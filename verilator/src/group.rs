@@ -0,0 +1,192 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Building several top modules into one library, so that a source tree with
+//! many unit-level tops is verilated and compiled once instead of once per
+//! top.
+//!
+//! A [`ModelGroup`] lists the modules to build together. Once it is added to
+//! a runtime with [`crate::VerilatorRuntime::add_model_group`], creating a
+//! model of any member builds (or reuses) the library of the whole group.
+//! The library is verilated from a generated wrapper module that instantiates
+//! every member, with the instance named after its module, and has separate
+//! FFI symbols for each member.
+//!
+//! Every model created from the library therefore simulates all of the
+//! members, but only the ports of its own module are accessible through it.
+//! Since the members share one verilated model, they also share one
+//! configuration: the parameters of each member are fixed when it is added to
+//! the group, and everything else must be the same when creating any of them.
+
+use crate::{AsVerilatedModel, PortDirection};
+
+/// A module built into the library of a [`ModelGroup`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct GroupMember {
+    pub(crate) name: String,
+    pub(crate) source_path: String,
+    pub(crate) ports: Vec<(String, usize, usize, PortDirection)>,
+    pub(crate) parameters: Vec<(String, String)>,
+}
+
+impl GroupMember {
+    pub(crate) fn new(
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        parameters: &[(&str, &str)],
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            source_path: source_path.to_string(),
+            ports: ports
+                .iter()
+                .map(|(port, high, low, direction)| {
+                    (port.to_string(), *high, *low, *direction)
+                })
+                .collect(),
+            parameters: parameters
+                .iter()
+                .map(|(parameter, value)| {
+                    (parameter.to_string(), value.to_string())
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn ports(&self) -> Vec<(&str, usize, usize, PortDirection)> {
+        self.ports
+            .iter()
+            .map(|(port, high, low, direction)| {
+                (port.as_str(), *high, *low, *direction)
+            })
+            .collect()
+    }
+}
+
+/// A set of top modules built into one shared library. See the
+/// [module documentation](self).
+///
+/// ```no_run
+/// # use marlin_verilator::*;
+/// # let mut runtime = VerilatorRuntime::new("".as_ref(), &[], &[], [], Default::default()).unwrap();
+/// # || -> Result<(), VerilatorRuntimeError> {
+/// runtime.add_model_group(
+///     ModelGroup::new("units")
+///         .with_dyn_model(
+///             "adder",
+///             "src/adder.sv",
+///             &[
+///                 ("a", 7, 0, PortDirection::Input),
+///                 ("b", 7, 0, PortDirection::Input),
+///                 ("sum", 7, 0, PortDirection::Output),
+///             ],
+///             &[],
+///         )
+///         .with_dyn_model(
+///             "counter",
+///             "src/counter.sv",
+///             &[
+///                 ("clk", 0, 0, PortDirection::Input),
+///                 ("count", 3, 0, PortDirection::Output),
+///             ],
+///             &[("WIDTH", "4")],
+///         ),
+/// )?;
+/// # Ok(()) };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModelGroup {
+    pub(crate) name: String,
+    pub(crate) members: Vec<GroupMember>,
+}
+
+impl ModelGroup {
+    /// Creates an empty group. The `name` identifies the library of the group
+    /// in the artifact directory and must be a valid Verilog identifier.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            members: vec![],
+        }
+    }
+
+    /// Adds the module bound to `M`, with the parameters fixed by its
+    /// bindings.
+    pub fn with_model<'ctx, M: AsVerilatedModel<'ctx>>(self) -> Self {
+        self.with_dyn_model(
+            M::name(),
+            M::source_path(),
+            M::ports(),
+            M::parameters(),
+        )
+    }
+
+    /// Adds the module `name` defined in `source_path`, like
+    /// [`crate::VerilatorRuntime::create_dyn_model`], instantiated with the
+    /// given `parameters`. Only the `ports` listed here can be accessed by
+    /// models of the module.
+    pub fn with_dyn_model(
+        mut self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        parameters: &[(&str, &str)],
+    ) -> Self {
+        self.members.push(GroupMember::new(
+            name,
+            source_path,
+            ports,
+            parameters,
+        ));
+        self
+    }
+
+    /// The name of this group.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the modules in this group, in the order they were added.
+    pub fn members(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|member| member.name.as_str())
+    }
+
+    pub(crate) fn member(&self, name: &str) -> Option<&GroupMember> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// This group with the source paths of its members relative to the
+    /// project directory, as they are in the key of its library.
+    pub(crate) fn project_relative(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            members: self
+                .members
+                .iter()
+                .map(|member| GroupMember {
+                    source_path: crate::project_relative_path(
+                        &member.source_path,
+                    ),
+                    ..member.clone()
+                })
+                .collect(),
+        }
+    }
+
+    /// The name of the generated module instantiating every member, which is
+    /// the top module of the library.
+    pub(crate) fn wrapper_module(&self) -> String {
+        format!("marlin_group_{}", self.name)
+    }
+
+    /// The prefix of the names of the wrapper ports connected to the ports of
+    /// the member at `index`. Double underscores are avoided because
+    /// Verilator escapes them in the names of the model's members.
+    pub(crate) fn wrapper_port_prefix(index: usize) -> String {
+        format!("m{index}_")
+    }
+}
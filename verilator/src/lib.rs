@@ -13,38 +13,75 @@
 //! which just wraps [`VerilatorRuntime`].
 
 use std::{
-    cell::RefCell,
-    collections::{HashMap, hash_map::Entry},
+    cell::{OnceCell, RefCell},
+    collections::{HashMap, HashSet, hash_map::Entry},
     ffi::{self, OsString},
     fmt, fs,
-    hash::{self, Hash, Hasher},
-    slice,
-    sync::{LazyLock, Mutex},
-    time::Instant,
+    hash::{self, BuildHasher, Hash, Hasher},
+    process, slice,
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Instant, SystemTime},
 };
 
 use boxcar::Vec as BoxcarVec;
-use build_library::build_library;
+use build_library::{LibraryTops, build_library};
+use cache::{CachedLibrary, PruneOptions};
 use camino::{Utf8Path, Utf8PathBuf};
 use dashmap::DashMap;
+use diagnostics::BuildReport;
 use dpi::DpiFunction;
 use dynamic::DynamicVerilatedModel;
+pub use error::VerilatorRuntimeError;
+use error::{
+    InvalidConfigurationSnafu, LibraryLoadSnafu, LockSnafu,
+    MissingSourceFileSnafu, NotSavableSnafu, PrebuiltMismatchSnafu,
+    SourceFileNotProvidedSnafu, SymbolNotFoundSnafu,
+};
 use libloading::Library;
 use owo_colors::OwoColorize;
-use snafu::{ResultExt, Whatever, whatever};
+use snafu::{FromString, OptionExt, ResultExt, Whatever, ensure};
 
 mod build_library;
+pub mod cache;
+pub mod checkpoint;
+pub mod config;
+pub mod context;
+pub mod coverage;
+pub mod covergroup;
+pub mod diagnostics;
 pub mod dpi;
 pub mod dynamic;
+pub mod elf;
+pub mod error;
 pub mod ffi_names;
+pub mod group;
+mod manifest;
+pub mod memory;
 pub mod nocapture;
+mod prebuilt;
+mod stable_hash;
 pub mod vcd;
+pub mod version;
 
+pub use config::ProjectConfig;
+pub use context::{DesignMessage, DesignSeverity, EvalStatus, SimContext};
 pub use dynamic::AsDynamicVerilatedModel;
+pub use group::ModelGroup;
+pub use version::VerilatorInfo;
 
 use crate::{
+    checkpoint::__private::CheckpointApi,
+    context::__private::{ContextApi, NewModelFn, new_model, new_sim_context},
     dynamic::DynamicPortInfo,
-    ffi_names::{DPI_INIT_CALLBACK, TRACE_EVER_ON},
+    ffi_names::{CHECKPOINT_SET_TAG, DPI_INIT_CALLBACK, TRACE_EVER_ON},
+    group::GroupMember,
+    manifest::BuildManifest,
+    prebuilt::LibraryMetadata,
+    stable_hash::StableHasher,
+    version::VerilatorVersion,
 };
 
 /// Verilator-defined types for C FFI.
@@ -126,6 +163,17 @@ impl<const WORDS: usize> WideIn<WORDS> {
     pub fn as_ptr(&self) -> types::WDataInP {
         self.inner.as_ptr()
     }
+
+    /// # Safety
+    ///
+    /// `slice::from_raw_parts(raw, WORDS)` must be defined.
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    #[doc(hidden)]
+    pub fn from_ptr(raw: types::WDataInP) -> Self {
+        let mut inner = [0; WORDS];
+        inner.copy_from_slice(unsafe { slice::from_raw_parts(raw, WORDS) });
+        Self { inner }
+    }
 }
 
 impl<const WORDS: usize> Default for WideIn<WORDS> {
@@ -200,6 +248,88 @@ pub enum CxxStandard {
     Cxx26,
 }
 
+/// Which DPI functions Verilator may call from the worker threads of a
+/// multithreaded model. See the documentation for Verilator's `--threads-dpi`
+/// flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThreadsDpi {
+    /// All DPI functions may be called from any thread.
+    All,
+    /// DPI functions are only called from the main thread.
+    None,
+    /// Only pure DPI functions may be called from any thread.
+    Pure,
+}
+
+/// How Verilator assigns values that Verilog leaves unknown (`X`), since the
+/// simulation is two-state. See the documentation for Verilator's
+/// `--x-assign` and `--x-initial` flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XInit {
+    /// Whatever is best for performance.
+    Fast,
+    /// All zeros.
+    Zero,
+    /// A random value, chosen when the model is created from the seed of its
+    /// context (see [`VerilatorRuntimeOptions::seed`]).
+    Unique,
+}
+
+impl XInit {
+    pub(crate) fn as_flag(self) -> &'static str {
+        match self {
+            Self::Fast => "fast",
+            Self::Zero => "0",
+            Self::Unique => "unique",
+        }
+    }
+}
+
+/// Which kinds of coverage a model collects. See the documentation for
+/// Verilator's `--coverage` flags and [`coverage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CoverageKinds {
+    /// Whether to count how many times each block and branch of code runs
+    /// (`--coverage-line`).
+    pub line: bool,
+    /// Whether to count how many times each bit of each signal changes
+    /// (`--coverage-toggle`).
+    pub toggle: bool,
+    /// Whether to count how many times each `cover property` and `cover`
+    /// statement is satisfied (`--coverage-user`).
+    pub user: bool,
+}
+
+impl CoverageKinds {
+    /// Every kind of coverage, like Verilator's `--coverage` flag.
+    pub fn all() -> Self {
+        Self {
+            line: true,
+            toggle: true,
+            user: true,
+        }
+    }
+
+    /// Whether any kind of coverage is collected.
+    pub fn any(&self) -> bool {
+        self.line || self.toggle || self.user
+    }
+}
+
+/// Which internal signals of a model can be read and written by hierarchical
+/// path with [`AsDynamicVerilatedModel::peek`] and
+/// [`AsDynamicVerilatedModel::poke`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PublicSignals {
+    /// Only signals marked with `/*verilator public*/` (read-only) or
+    /// `/*verilator public_flat_rw*/` (read-write) in the Verilog source code.
+    Marked,
+    /// Every signal, as if marked with `/*verilator public_flat_rw*/`
+    /// (`--public-flat-rw`). This keeps Verilator from optimizing away
+    /// signals, so the model may run noticeably slower.
+    All,
+}
+
 /// Configuration for a particular [`VerilatedModel`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VerilatedModelConfig {
@@ -217,6 +347,89 @@ pub struct VerilatedModelConfig {
 
     /// Optionally specify the C++ standard used by Verilator.
     pub cxx_standard: Option<CxxStandard>,
+
+    /// Overrides for the top-level parameters of the module, as `(name,
+    /// value)` pairs passed to Verilator as `-G<name>=<value>`. The value is
+    /// Verilog literal text, so string values must include quotes.
+    ///
+    /// Models created with a bridging macro already carry the overrides given
+    /// in the macro (see [`AsVerilatedModel::parameters`]), so you only need
+    /// to set this for dynamic models.
+    pub parameters: Vec<(String, String)>,
+
+    /// Preprocessor defines, as `(name, text)` pairs passed to Verilator as
+    /// `-D<name>` or `-D<name>=<text>`.
+    ///
+    /// As with [`VerilatedModelConfig::parameters`], the defines given in a
+    /// bridging macro (see [`AsVerilatedModel::defines`]) are added for you.
+    pub defines: Vec<(String, Option<String>)>,
+
+    /// Directories searched for `` `include ``d files in addition to those
+    /// the [`VerilatorRuntime`] was created with.
+    ///
+    /// As with [`VerilatedModelConfig::parameters`], the include directories
+    /// given in a bridging macro (see
+    /// [`AsVerilatedModel::include_directories`]) are added for you.
+    pub include_directories: Vec<Utf8PathBuf>,
+
+    /// If `Some`, the model is verilated with `--threads <count>` and created
+    /// with a `VerilatedContext` running that many threads. Multithreading
+    /// only pays off for large designs.
+    pub threads: Option<usize>,
+
+    /// If `Some`, passes `--threads-dpi` to Verilator. Only meaningful when
+    /// [`VerilatedModelConfig::threads`] is set.
+    pub threads_dpi: Option<ThreadsDpi>,
+
+    /// Whether to pin the worker threads of the model to CPU cores (through
+    /// `VerilatedContext::useNumaAssign`, which requires Verilator 5.018 or
+    /// newer). The assignment can be tuned at runtime with the
+    /// `VERILATOR_NUMA_STRATEGY` environment variable.
+    pub pin_threads: bool,
+
+    /// If `Some`, passes `--x-assign` to Verilator, which decides the value of
+    /// explicit `X` assignments.
+    pub x_assign: Option<XInit>,
+
+    /// If `Some`, passes `--x-initial` to Verilator, which decides the
+    /// initial value of variables that are not explicitly initialized. Use
+    /// [`XInit::Unique`] to catch registers that are read before being reset.
+    pub x_initial: Option<XInit>,
+
+    /// Whether the model is verilated with `--savable`, so that its state can
+    /// be saved to and restored from a checkpoint with
+    /// [`AsDynamicVerilatedModel::save`] and
+    /// [`AsDynamicVerilatedModel::restore`].
+    pub savable: bool,
+
+    /// Which kinds of coverage the model collects. A model collecting any
+    /// coverage writes it to the coverage directory of its runtime (see
+    /// [`VerilatorRuntimeOptions::coverage_directory`]) when the runtime is
+    /// dropped, and it can be written sooner with
+    /// [`context::SimContext::write_coverage`].
+    pub coverage: CoverageKinds,
+
+    /// Whether the model is verilated with `--assert`, enabling SystemVerilog
+    /// assertions. A failed assertion (or `$error` or `$fatal`) then fails the
+    /// model: [`AsDynamicVerilatedModel::try_eval`] returns
+    /// [`VerilatorRuntimeError::AssertionFailed`] and
+    /// [`AsDynamicVerilatedModel::eval`] panics. Without this option, they
+    /// stop the model like `$stop`.
+    pub assertions: bool,
+
+    /// Whether the model is verilated with `--timing`, so that delays (e.g.,
+    /// `#10`) and event controls (e.g., `@(posedge clk)`) in the design are
+    /// scheduled. Drive such a model with
+    /// [`AsDynamicVerilatedModel::run_until`] and
+    /// [`AsDynamicVerilatedModel::step_to_next_event`]. Requires C++20, so
+    /// an older [`VerilatedModelConfig::cxx_standard`] is raised to C++20.
+    pub timing: bool,
+
+    /// If `Some`, the model is verilated with `--vpi` so that its public
+    /// internal signals can be accessed by hierarchical path with
+    /// [`AsDynamicVerilatedModel::peek`] and
+    /// [`AsDynamicVerilatedModel::poke`].
+    pub public_signals: Option<PublicSignals>,
 }
 
 impl Default for VerilatedModelConfig {
@@ -226,10 +439,108 @@ impl Default for VerilatedModelConfig {
             ignored_warnings: Default::default(),
             enable_tracing: Default::default(),
             cxx_standard: Some(CxxStandard::Cxx14),
+            parameters: Default::default(),
+            defines: Default::default(),
+            include_directories: Default::default(),
+            threads: Default::default(),
+            threads_dpi: Default::default(),
+            pin_threads: Default::default(),
+            x_assign: Default::default(),
+            x_initial: Default::default(),
+            savable: Default::default(),
+            coverage: Default::default(),
+            assertions: Default::default(),
+            timing: Default::default(),
+            public_signals: Default::default(),
         }
     }
 }
 
+impl VerilatedModelConfig {
+    /// The [`Default`] configuration with the project's `marlin.toml` and the
+    /// `MARLIN_*` environment variables applied. See [`config`].
+    pub fn from_env() -> Result<Self, VerilatorRuntimeError> {
+        Ok(ProjectConfig::discover()?.model_config())
+    }
+
+    /// Adds the parameters, defines, and include directories fixed by the
+    /// bridging macro for `M` to this configuration, failing if the
+    /// configuration already sets one of them to a different value.
+    fn with_model_settings<'ctx, M: AsVerilatedModel<'ctx>>(
+        &self,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        self.with_settings(
+            M::name(),
+            M::parameters(),
+            M::defines(),
+            M::include_directories(),
+        )
+    }
+
+    /// See [`VerilatedModelConfig::with_model_settings`]. Exposed for building
+    /// the same configuration ahead of time.
+    #[doc(hidden)]
+    pub fn with_settings(
+        &self,
+        name: &str,
+        parameters: &[(&str, &str)],
+        defines: &[(&str, Option<&str>)],
+        include_directories: &[&str],
+    ) -> Result<Self, VerilatorRuntimeError> {
+        let mut config = self.clone();
+        for (parameter, value) in parameters {
+            match config
+                .parameters
+                .iter()
+                .find(|(existing, _)| existing == parameter)
+            {
+                Some((_, existing_value)) if existing_value != value => {
+                    return InvalidConfigurationSnafu {
+                        message: format!(
+                            "Parameter {parameter} on module {name} is fixed to {value} by its bindings but was configured as {existing_value}"
+                        ),
+                    }
+                    .fail();
+                }
+                Some(_) => {}
+                None => config
+                    .parameters
+                    .push((parameter.to_string(), value.to_string())),
+            }
+        }
+        for (define, text) in defines {
+            match config
+                .defines
+                .iter()
+                .find(|(existing, _)| existing == define)
+            {
+                Some((_, existing_text))
+                    if existing_text.as_deref() != *text =>
+                {
+                    return InvalidConfigurationSnafu {
+                        message: format!(
+                            "Define {define} on module {name} is fixed to {text:?} by its bindings but was configured as {existing_text:?}"
+                        ),
+                    }
+                    .fail();
+                }
+                Some(_) => {}
+                None => config.defines.push((
+                    define.to_string(),
+                    text.map(|text| text.to_string()),
+                )),
+            }
+        }
+        for include_directory in include_directories {
+            let include_directory = Utf8PathBuf::from(include_directory);
+            if !config.include_directories.contains(&include_directory) {
+                config.include_directories.push(include_directory);
+            }
+        }
+        Ok(config)
+    }
+}
+
 /// You should not implement this `trait` manually. Instead, use a procedural
 /// macro like `#[verilog(...)]` to derive it for you.
 pub trait AsVerilatedModel<'ctx>: 'ctx {
@@ -243,11 +554,34 @@ pub trait AsVerilatedModel<'ctx>: 'ctx {
     /// port_direction)`.
     fn ports() -> &'static [(&'static str, usize, usize, PortDirection)];
 
+    /// The parameter overrides the module's interface was computed with; each
+    /// element is `(parameter_name, value)`.
+    fn parameters() -> &'static [(&'static str, &'static str)];
+
+    /// The preprocessor defines the module's interface was computed with;
+    /// each element is `(define_name, text)`.
+    fn defines() -> &'static [(&'static str, Option<&'static str>)];
+
+    /// The include directories the module's interface was computed with.
+    fn include_directories() -> &'static [&'static str];
+
     #[doc(hidden)]
-    fn init_from(library: &'ctx Library, tracing_enabled: bool) -> Self;
+    fn init_from(
+        library: &'ctx Library,
+        tracing_enabled: bool,
+        model: *mut ffi::c_void,
+    ) -> Self;
 
     #[doc(hidden)]
     unsafe fn model(&self) -> *mut ffi::c_void;
+
+    #[doc(hidden)]
+    fn library(&self) -> &'ctx Library;
+
+    /// Reads every port of the C++ model back into this binding, e.g., after
+    /// its state is replaced.
+    #[doc(hidden)]
+    fn read_ports(&mut self);
 }
 
 /// Optional configuration for creating a [`VerilatorRuntime`]. Usually, you can
@@ -262,8 +596,51 @@ pub struct VerilatorRuntimeOptions {
     /// source files or DPI functions change.
     pub force_verilator_rebuild: bool,
 
+    /// Whether to only load libraries built ahead of time (e.g., by the
+    /// `marlin-build` crate from a build script) instead of invoking
+    /// Verilator. Creating a model whose library is missing or out of date
+    /// then fails.
+    pub prebuilt_only: bool,
+
     /// Whether to use the log crate.
     pub log: bool,
+
+    /// Command-line arguments passed to the `VerilatedContext` of every model
+    /// this runtime creates, e.g., `+TESTNAME=smoke` for
+    /// `$value$plusargs` or Verilator runtime options like
+    /// `+verilator+seed+<value>` and `+verilator+rand+reset+<value>`. They
+    /// follow the arguments given to
+    /// [`VerilatorRuntime::create_model_with_plusargs`], so those take
+    /// precedence.
+    ///
+    /// Unlike [`VerilatedModelConfig`], these do not affect how models are
+    /// built, so changing them never causes a rebuild.
+    pub plusargs: Vec<String>,
+
+    /// The seed for the random values of models configured with
+    /// [`XInit::Unique`], passed as `+verilator+seed+<seed>` (so plusargs can
+    /// override it). Must be positive and fit in an `i32`.
+    ///
+    /// If `None`, each such model gets a fresh seed, which is printed to
+    /// standard error (or logged, if [`VerilatorRuntimeOptions::log`]) so
+    /// that a failing test can be reproduced. The seed of a model can also be
+    /// read with [`context::SimContext::seed`].
+    pub seed: Option<u32>,
+
+    /// Whether models keep what the design prints until it is taken with
+    /// [`AsDynamicVerilatedModel::take_output`] instead of printing it after
+    /// each `eval`. See [`context::SimContext::set_capture_output`].
+    pub capture_output: bool,
+
+    /// The directory to which models built with
+    /// [`VerilatedModelConfig::coverage`] write their coverage databases, each
+    /// in its own file. Defaults to a fresh subdirectory of the `coverage`
+    /// subdirectory of the artifact directory for each runtime (see
+    /// [`VerilatorRuntime::coverage_directory`]). Databases are never removed
+    /// from a directory set here, so it accumulates those of every run until
+    /// it is cleared with [`coverage::remove_databases`]. Read the databases
+    /// with [`coverage::CoverageReport::read_directory`].
+    pub coverage_directory: Option<Utf8PathBuf>,
 }
 
 impl Default for VerilatorRuntimeOptions {
@@ -271,7 +648,12 @@ impl Default for VerilatorRuntimeOptions {
         Self {
             verilator_executable: "verilator".into(),
             force_verilator_rebuild: false,
+            prebuilt_only: false,
             log: false,
+            plusargs: vec![],
+            seed: None,
+            capture_output: false,
+            coverage_directory: None,
         }
     }
 }
@@ -285,6 +667,12 @@ impl VerilatorRuntimeOptions {
             ..Default::default()
         }
     }
+
+    /// The [`Default`] options with the project's `marlin.toml` and the
+    /// `MARLIN_*` environment variables applied. See [`config`].
+    pub fn from_env() -> Result<Self, VerilatorRuntimeError> {
+        Ok(ProjectConfig::discover()?.runtime_options())
+    }
 }
 
 #[derive(PartialEq, Eq, Hash, Clone)]
@@ -294,6 +682,83 @@ struct LibraryArenaKey {
     hash: u64,
 }
 
+/// `path` relative to the project directory if it is inside it, so that the
+/// keys of libraries, which are shipped with prebuilt ones, do not depend on
+/// where the project is checked out.
+fn project_relative_path(path: &str) -> String {
+    Utf8Path::new(path)
+        .strip_prefix(config::project_directory())
+        .map(|relative| relative.to_string())
+        .unwrap_or_else(|_| path.to_owned())
+}
+
+/// `config` with its include directories relative to the project directory.
+/// See [`project_relative_path`].
+fn project_relative_config(
+    config: &VerilatedModelConfig,
+) -> VerilatedModelConfig {
+    VerilatedModelConfig {
+        include_directories: config
+            .include_directories
+            .iter()
+            .map(|directory| project_relative_path(directory.as_str()).into())
+            .collect(),
+        ..config.clone()
+    }
+}
+
+impl LibraryArenaKey {
+    fn new(
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Self {
+        let mut hasher = StableHasher::new();
+        ports.hash(&mut hasher);
+        project_relative_config(config).hash(&mut hasher);
+        Self {
+            name: name.to_owned(),
+            source_path: project_relative_path(source_path),
+            hash: hasher.finish(),
+        }
+    }
+
+    fn for_group(group: &ModelGroup, config: &VerilatedModelConfig) -> Self {
+        let mut hasher = StableHasher::new();
+        group.project_relative().hash(&mut hasher);
+        project_relative_config(config).hash(&mut hasher);
+        Self {
+            name: group.name.clone(),
+            source_path: String::new(),
+            hash: hasher.finish(),
+        }
+    }
+
+    /// The name of the subdirectory of the artifact directory the library is
+    /// built in by Verilator `version`, which is part of the name so that
+    /// libraries built by different versions do not replace each other.
+    fn directory_name(&self, version: VerilatorVersion) -> String {
+        format!("{}_v{version}", self.directory_prefix())
+    }
+
+    /// The part of [`LibraryArenaKey::directory_name`] before the version.
+    fn directory_prefix(&self) -> String {
+        format!(
+            "{}_{}_{}",
+            self.name,
+            self.source_path.replace("_", "__").replace("/", "_"),
+            self.hash
+        )
+    }
+}
+
+/// A library loaded with [`VerilatorRuntime::load_prebuilt`].
+struct PrebuiltLibrary {
+    path: Utf8PathBuf,
+    metadata: LibraryMetadata,
+}
+
 struct ModelDeallocator {
     model: *mut ffi::c_void,
     deallocator: extern "C" fn(*mut ffi::c_void),
@@ -302,6 +767,8 @@ struct ModelDeallocator {
 /// Runtime for (System)Verilog code.
 pub struct VerilatorRuntime {
     artifact_directory: Utf8PathBuf,
+    /// See [`VerilatorRuntime::coverage_directory`].
+    coverage_directory: Utf8PathBuf,
     source_files: Vec<Utf8PathBuf>,
     include_directories: Vec<Utf8PathBuf>,
     dpi_functions: Vec<&'static dyn DpiFunction>,
@@ -310,7 +777,14 @@ pub struct VerilatorRuntime {
     /// implementations.
     library_map: RefCell<HashMap<LibraryArenaKey, usize>>,
     /// Verilator implementations arena.
-    library_arena: BoxcarVec<Library>,
+    library_arena: BoxcarVec<LoadedLibrary>,
+    /// Groups of modules built into one library, which are disjoint.
+    model_groups: Vec<ModelGroup>,
+    /// Libraries built elsewhere, whose modules are never built by this
+    /// runtime.
+    prebuilt_libraries: Vec<PrebuiltLibrary>,
+    /// The Verilator this runtime invokes, detected when it is first needed.
+    verilator_info: OnceCell<VerilatorInfo>,
     /// SAFETY: These are dropped when the runtime is dropped. They will not be
     /// "borrowed mutably" because the models created for this runtime must
     /// not outlive it and thus will be all gone before these are dropped.
@@ -328,6 +802,14 @@ impl Drop for VerilatorRuntime {
     }
 }
 
+/// A library in the arena of a [`VerilatorRuntime`].
+struct LoadedLibrary {
+    library: Library,
+    /// The [`VerilatedModelConfig::enable_tracing`] the library was built
+    /// with.
+    tracing_enabled: bool,
+}
+
 /* <Forgive me father for I have sinned> */
 
 #[derive(Default)]
@@ -346,12 +828,15 @@ fn one_time_library_setup(
     library: &Library,
     dpi_functions: &[&'static dyn DpiFunction],
     tracing_enabled: bool,
+    checkpoint_tag: Option<u64>,
     options: &VerilatorRuntimeOptions,
 ) -> Result<(), Whatever> {
     if !dpi_functions.is_empty() {
         let dpi_init_callback: extern "C" fn(*const *const ffi::c_void) =
             *unsafe { library.get(DPI_INIT_CALLBACK.as_bytes()) }
-                .whatever_context("Failed to load DPI initializer")?;
+                .whatever_context::<_, Whatever>(
+                    "Failed to load DPI initializer",
+                )?;
 
         // order is important here. the function pointers will be
         // initialized in the same order that they
@@ -372,7 +857,7 @@ fn one_time_library_setup(
     if tracing_enabled {
         let trace_ever_on_callback: extern "C" fn(bool) =
             *unsafe { library.get(TRACE_EVER_ON.as_bytes()) }
-                .whatever_context(
+                .whatever_context::<_, Whatever>(
                     "Model was not configured with tracing enabled",
                 )?;
         trace_ever_on_callback(true);
@@ -382,6 +867,18 @@ fn one_time_library_setup(
         }
     }
 
+    if let Some(checkpoint_tag) = checkpoint_tag {
+        let set_checkpoint_tag: extern "C" fn(*const ffi::c_char) =
+            *unsafe { library.get(CHECKPOINT_SET_TAG.as_bytes()) }
+                .whatever_context::<_, Whatever>(
+                    "Model was not configured to be savable",
+                )?;
+        let checkpoint_tag =
+            ffi::CString::new(format!("{checkpoint_tag:016x}"))
+                .expect("Hexadecimal strings do not contain NUL bytes");
+        set_checkpoint_tag(checkpoint_tag.as_ptr());
+    }
+
     Ok(())
 }
 
@@ -394,21 +891,37 @@ impl VerilatorRuntime {
         include_directories: &[&Utf8Path],
         dpi_functions: impl IntoIterator<Item = &'static dyn DpiFunction>,
         options: VerilatorRuntimeOptions,
-    ) -> Result<Self, Whatever> {
+    ) -> Result<Self, VerilatorRuntimeError> {
         if options.log {
             log::info!("Validating source files");
         }
         for source_file in source_files {
-            if !source_file.is_file() {
-                whatever!(
-                    "Source file {} does not exist or is not a file. Note that if it's a relative path, you must be in the correct directory",
-                    source_file
-                );
-            }
+            ensure!(
+                source_file.is_file(),
+                MissingSourceFileSnafu { path: *source_file }
+            );
         }
 
+        let coverage_directory =
+            options.coverage_directory.clone().unwrap_or_else(|| {
+                static RUNTIME_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+                // unique across runs, so that the databases of earlier runs
+                // are not merged into those of this one
+                let started = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis();
+                artifact_directory.join("coverage").join(format!(
+                    "{started}-{}-{}",
+                    process::id(),
+                    RUNTIME_COUNT.fetch_add(1, Ordering::Relaxed)
+                ))
+            });
+
         Ok(Self {
             artifact_directory: artifact_directory.to_owned(),
+            coverage_directory,
             source_files: source_files
                 .iter()
                 .map(|path| path.to_path_buf())
@@ -421,18 +934,274 @@ impl VerilatorRuntime {
             options,
             library_map: RefCell::new(HashMap::new()),
             library_arena: BoxcarVec::new(),
+            model_groups: vec![],
+            prebuilt_libraries: vec![],
+            verilator_info: OnceCell::new(),
             model_deallocators: RefCell::new(vec![]),
         })
     }
 
-    /// Constructs a new model. Uses lazy and incremental building for
-    /// efficiency.
+    /// Like [`VerilatorRuntime::new`], but with the project's defaults (see
+    /// [`config`]): the artifact directory is the configured one or else
+    /// `default_artifact_directory`, and the options are
+    /// [`VerilatorRuntimeOptions::from_env`].
+    pub fn from_env(
+        default_artifact_directory: &Utf8Path,
+        source_files: &[&Utf8Path],
+        include_directories: &[&Utf8Path],
+        dpi_functions: impl IntoIterator<Item = &'static dyn DpiFunction>,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        let project = ProjectConfig::discover()?;
+        Self::new(
+            &project.artifact_directory_or(default_artifact_directory),
+            source_files,
+            include_directories,
+            dpi_functions,
+            project.runtime_options(),
+        )
+    }
+
+    /// The version of the Verilator this runtime invokes, i.e.,
+    /// [`VerilatorRuntimeOptions::verilator_executable`]. It is detected
+    /// with `verilator --version` the first time a library is built or this
+    /// is called, and fails if Verilator cannot be run or is not supported.
+    pub fn verilator_info(
+        &self,
+    ) -> Result<&VerilatorInfo, VerilatorRuntimeError> {
+        if let Some(verilator_info) = self.verilator_info.get() {
+            return Ok(verilator_info);
+        }
+        let verilator_info =
+            VerilatorInfo::detect(&self.options.verilator_executable)?;
+        if self.options.log {
+            log::info!("Detected {}", verilator_info.version_string);
+        }
+        Ok(self.verilator_info.get_or_init(|| verilator_info))
+    }
+
+    /// The name of the subdirectory of the artifact directory holding the
+    /// library for `library_key`. If only prebuilt libraries are used,
+    /// Verilator is not run to detect its version, so the library built by
+    /// the newest version is used instead.
+    fn library_directory_name(
+        &self,
+        library_key: &LibraryArenaKey,
+    ) -> Result<String, VerilatorRuntimeError> {
+        if !self.options.prebuilt_only {
+            return Ok(
+                library_key.directory_name(self.verilator_info()?.version)
+            );
+        }
+
+        let prefix = format!("{}_v", library_key.directory_prefix());
+        let newest = fs::read_dir(&self.artifact_directory)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                let (major, minor) =
+                    name.strip_prefix(&prefix)?.split_once('.')?;
+                Some(VerilatorVersion::new(
+                    major.parse().ok()?,
+                    minor.parse().ok()?,
+                ))
+            })
+            .max();
+        // if there is no library, building fails with the directory it
+        // would have been in for the oldest supported version
+        Ok(library_key.directory_name(
+            newest.unwrap_or(version::MINIMUM_VERILATOR_VERSION),
+        ))
+    }
+
+    /// Builds the modules in `group` into one library from now on, so that
+    /// the sources they share are verilated and compiled once for all of them
+    /// instead of once per module. See [`group`] for how models of the
+    /// members behave.
+    ///
+    /// A module can be in only one group. Its models must be created with a
+    /// subset of the ports and parameters it was added with.
+    pub fn add_model_group(
+        &mut self,
+        group: ModelGroup,
+    ) -> Result<(), VerilatorRuntimeError> {
+        ensure!(
+            !group.name.is_empty()
+                && group
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            InvalidConfigurationSnafu {
+                message: format!(
+                    "Model group name {:?} is not a valid identifier",
+                    group.name
+                )
+            }
+        );
+        ensure!(
+            !group.members.is_empty(),
+            InvalidConfigurationSnafu {
+                message: format!("Model group {} has no members", group.name)
+            }
+        );
+        for (index, member) in group.members.iter().enumerate() {
+            self.validate_module(
+                &member.name,
+                &member.source_path,
+                &member.ports(),
+            )?;
+            let other_group = self
+                .model_groups
+                .iter()
+                .find(|other| other.member(&member.name).is_some());
+            if let Some(other_group) = other_group {
+                return InvalidConfigurationSnafu {
+                    message: format!(
+                        "Module {} is already in model group {}",
+                        member.name, other_group.name
+                    ),
+                }
+                .fail();
+            }
+            ensure!(
+                group.members[..index]
+                    .iter()
+                    .all(|other| other.name != member.name),
+                InvalidConfigurationSnafu {
+                    message: format!(
+                        "Module {} is in model group {} more than once",
+                        member.name, group.name
+                    )
+                }
+            );
+        }
+
+        self.model_groups.push(group);
+        Ok(())
+    }
+
+    /// Loads a library built by another runtime (e.g., in an earlier CI stage)
+    /// from `library`, so that models of the modules it was built for are
+    /// created from it instead of being built. Neither Verilator nor the
+    /// source files of the modules are needed.
+    ///
+    /// Every library Marlin builds, `libmarlin_V<top>.so` under the
+    /// `obj_dir` subdirectory of its directory in the artifact directory, is
+    /// accompanied by a `libmarlin_V<top>.so.metadata` file describing what
+    /// it was built for, which must be given as `metadata`. Loading fails if
+    /// the library was built by a different version of Marlin, and creating
+    /// a model of one of its modules fails if the ports or configuration of
+    /// the model differ from those the library was built with. Source paths
+    /// and include directories inside the project directory
+    /// (`CARGO_MANIFEST_DIR`, or else the current directory) are compared
+    /// relative to it, so the library may be built in a different checkout.
+    pub fn load_prebuilt(
+        &mut self,
+        library: impl AsRef<Utf8Path>,
+        metadata: impl AsRef<Utf8Path>,
+    ) -> Result<(), VerilatorRuntimeError> {
+        let library = library.as_ref();
+        let mismatch = |reason: String| PrebuiltMismatchSnafu {
+            path: library,
+            reason,
+        };
+
+        let metadata = match LibraryMetadata::read(metadata.as_ref()) {
+            Ok(metadata) => metadata,
+            Err(error) => return mismatch(error.to_string()).fail(),
+        };
+        let marlin_version = env!("CARGO_PKG_VERSION");
+        ensure!(
+            metadata.marlin_version == marlin_version,
+            mismatch(format!(
+                "it was built by Marlin {}, but this is Marlin {marlin_version}",
+                metadata.marlin_version
+            ))
+        );
+        for module in &metadata.modules {
+            let provided_elsewhere = self
+                .model_groups
+                .iter()
+                .any(|group| group.member(&module.name).is_some())
+                || self.prebuilt_libraries.iter().any(|prebuilt| {
+                    prebuilt
+                        .metadata
+                        .modules
+                        .iter()
+                        .any(|other| other.name == module.name)
+                });
+            ensure!(
+                !provided_elsewhere,
+                mismatch(format!(
+                    "module {} is already in a model group or prebuilt library",
+                    module.name
+                ))
+            );
+        }
+
+        let library_key = match &metadata.group {
+            Some(group) => LibraryArenaKey {
+                name: group.clone(),
+                source_path: String::new(),
+                hash: metadata.hash,
+            },
+            None => LibraryArenaKey {
+                name: metadata.modules[0].name.clone(),
+                source_path: metadata.modules[0].source_path.clone(),
+                hash: metadata.hash,
+            },
+        };
+        ensure!(
+            !self.library_map.borrow().contains_key(&library_key),
+            mismatch(
+                "this runtime already loaded a library with the same key"
+                    .into()
+            )
+        );
+
+        if self.options.log {
+            log::info!("Opening the prebuilt dynamic library {library}");
+        }
+        let loaded = unsafe { Library::new(library) }
+            .context(LibraryLoadSnafu { path: library })?;
+        one_time_library_setup(
+            &loaded,
+            &self.dpi_functions,
+            metadata.tracing,
+            metadata.checkpoint_tag,
+            &self.options,
+        )?;
+        let library_idx = self.library_arena.push(LoadedLibrary {
+            library: loaded,
+            tracing_enabled: metadata.tracing,
+        });
+        self.library_map
+            .borrow_mut()
+            .insert(library_key, library_idx);
+
+        // models of the members then find the library of the group
+        if let Some(group) = &metadata.group {
+            self.model_groups.push(ModelGroup {
+                name: group.clone(),
+                members: metadata.modules.clone(),
+            });
+        }
+        self.prebuilt_libraries.push(PrebuiltLibrary {
+            path: library.to_path_buf(),
+            metadata,
+        });
+        Ok(())
+    }
+
+    /// Constructs a new model with the project's default configuration, i.e.,
+    /// [`VerilatedModelConfig::from_env`], so that, e.g., `MARLIN_TRACE=1`
+    /// enables tracing. Uses lazy and incremental building for efficiency.
     ///
     /// See also: [`VerilatorRuntime::create_dyn_model`]
     pub fn create_model_simple<'ctx, M: AsVerilatedModel<'ctx>>(
         &'ctx self,
-    ) -> Result<M, Whatever> {
-        self.create_model(&VerilatedModelConfig::default())
+    ) -> Result<M, VerilatorRuntimeError> {
+        self.create_model(&VerilatedModelConfig::from_env()?)
     }
 
     /// Constructs a new model. Uses lazy and incremental building for
@@ -442,34 +1211,160 @@ impl VerilatorRuntime {
     pub fn create_model<'ctx, M: AsVerilatedModel<'ctx>>(
         &'ctx self,
         config: &VerilatedModelConfig,
-    ) -> Result<M, Whatever> {
-        let library = self
-            .build_or_retrieve_library(
-                M::name(),
-                M::source_path(),
-                M::ports(),
-                config,
-            )
-            .whatever_context(
-                "Failed to build or retrieve verilator dynamic library. Try removing the build directory if it is corrupted.",
-            )?;
-
-        let delete_model: extern "C" fn(*mut ffi::c_void) = *unsafe {
-            library.get(format!("ffi_delete_V{}", M::name()).as_bytes())
-        }
-        .expect("failed to get symbol");
+    ) -> Result<M, VerilatorRuntimeError> {
+        self.create_model_with_plusargs(config, &[])
+    }
 
-        let model = M::init_from(library, config.enable_tracing);
+    /// Like [`VerilatorRuntime::create_model`], but also passes `plusargs`
+    /// (e.g., `+verbose` or `+verilator+seed+7`) to the model's
+    /// `VerilatedContext` before the model is constructed. The same library is
+    /// used regardless of `plusargs`. See also
+    /// [`VerilatorRuntimeOptions::plusargs`].
+    pub fn create_model_with_plusargs<'ctx, M: AsVerilatedModel<'ctx>>(
+        &'ctx self,
+        config: &VerilatedModelConfig,
+        plusargs: &[&str],
+    ) -> Result<M, VerilatorRuntimeError> {
+        let config = config.with_model_settings::<M>()?;
+
+        let (library, _) = self.build_or_retrieve_library(
+            M::name(),
+            M::source_path(),
+            M::ports(),
+            &config,
+        )?;
+
+        let symbol = |symbol: String| SymbolNotFoundSnafu {
+            top_module: M::name(),
+            symbol,
+        };
+        let new_model_name = format!("ffi_new_V{}", M::name());
+        let new_model_fn: NewModelFn =
+            *unsafe { library.get(new_model_name.as_bytes()) }
+                .context(symbol(new_model_name))?;
+        let delete_model_name = format!("ffi_delete_V{}", M::name());
+        let delete_model =
+            *unsafe { library.get(delete_model_name.as_bytes()) }
+                .context(symbol(delete_model_name))?;
+        let context_api = ContextApi::load(library, M::name())
+            .context(symbol(ffi_names::top_contextp(M::name())))?;
+
+        let command_args = self.command_args(M::name(), &config, plusargs)?;
+        let model = new_model(new_model_fn, &command_args);
 
         self.model_deallocators.borrow_mut().push(ModelDeallocator {
             // SAFETY: The `model` cannot outlive the runtime, and it is the
             // model's responsibility to deallocate (because models
             // themselves do not deallocate on `Drop`).
-            model: unsafe { model.model() },
+            model,
             deallocator: delete_model,
         });
+        self.set_up_context(M::name(), &context_api, model)?;
 
-        Ok(model)
+        Ok(M::init_from(library, config.enable_tracing, model))
+    }
+
+    /// Creates an independent copy of `model`, which must have been created by
+    /// this runtime, in its current state, e.g., to explore different stimuli
+    /// from the same point in a simulation. The state is copied in memory
+    /// (without any file I/O) into a new instance from the same library, so
+    /// `model` must have been built with [`VerilatedModelConfig::savable`].
+    /// The ports of the copy hold their values as of the last evaluation of
+    /// `model`, since inputs set after it have not reached the C++ model.
+    ///
+    /// See also: [`VerilatorRuntime::fork_dyn_model`]
+    pub fn fork<'ctx, M: AsVerilatedModel<'ctx>>(
+        &'ctx self,
+        model: &M,
+    ) -> Result<M, VerilatorRuntimeError> {
+        let library = model.library();
+        let fork = self.fork_raw(
+            library,
+            M::name(),
+            // SAFETY: `model` is alive for the duration of this call.
+            unsafe { model.model() },
+        )?;
+
+        let tracing_enabled = self
+            .loaded_library(library)
+            .is_some_and(|loaded| loaded.tracing_enabled);
+        let mut fork = M::init_from(library, tracing_enabled, fork);
+        fork.read_ports();
+        Ok(fork)
+    }
+
+    /// Like [`VerilatorRuntime::fork`], but for dynamic models.
+    pub fn fork_dyn_model<'ctx>(
+        &'ctx self,
+        model: &DynamicVerilatedModel<'ctx>,
+    ) -> Result<DynamicVerilatedModel<'ctx>, VerilatorRuntimeError> {
+        let fork = self.fork_raw(model.library, &model.name, model.main)?;
+
+        Ok(DynamicVerilatedModel {
+            ports: model.ports.clone(),
+            name: model.name.clone(),
+            main: fork,
+            eval_main: model.eval_main,
+            context_api: model.context_api,
+            checkpoint_api: model.checkpoint_api,
+            covergroups: vec![],
+            library: model.library,
+        })
+    }
+
+    /// The library in this runtime's arena at the address `library`, if any.
+    fn loaded_library(&self, library: &Library) -> Option<&LoadedLibrary> {
+        self.library_arena
+            .iter()
+            .map(|(_, loaded)| loaded)
+            .find(|loaded| std::ptr::eq(&loaded.library, library))
+    }
+
+    /// Forks the model `main` of module `name` loaded from `library`,
+    /// returning the new model, which is deallocated with this runtime.
+    fn fork_raw(
+        &self,
+        library: &Library,
+        name: &str,
+        main: *mut ffi::c_void,
+    ) -> Result<*mut ffi::c_void, VerilatorRuntimeError> {
+        // the deallocator must not outlive its library
+        ensure!(
+            self.loaded_library(library).is_some(),
+            InvalidConfigurationSnafu {
+                message: format!(
+                    "Cannot fork a model of module {name} created by a different runtime"
+                )
+            }
+        );
+
+        let Some(checkpoint_api) = CheckpointApi::load(library, name) else {
+            return NotSavableSnafu { top_module: name }.fail();
+        };
+        let delete_model_name = format!("ffi_delete_V{name}");
+        let delete_model =
+            *unsafe { library.get(delete_model_name.as_bytes()) }.context(
+                SymbolNotFoundSnafu {
+                    top_module: name,
+                    symbol: delete_model_name,
+                },
+            )?;
+
+        let context_api =
+            ContextApi::load(library, name).context(SymbolNotFoundSnafu {
+                top_module: name,
+                symbol: ffi_names::top_contextp(name),
+            })?;
+
+        // the fork keeps the output settings of `main` but not where it
+        // writes its coverage
+        let fork = checkpoint_api.fork(main);
+        self.model_deallocators.borrow_mut().push(ModelDeallocator {
+            model: fork,
+            deallocator: delete_model,
+        });
+        self.set_up_coverage(name, &context_api, fork)?;
+        Ok(fork)
     }
 
     // TODO: should this be unified with the normal create_model by having
@@ -487,7 +1382,7 @@ impl VerilatorRuntime {
     /// # use marlin_verilator::*;
     /// # use marlin_verilator::dynamic::*;
     /// # let runtime = VerilatorRuntime::new("".as_ref(), &[], &[], [], Default::default()).unwrap();
-    /// # || -> Result<(), snafu::Whatever> {
+    /// # || -> Result<(), VerilatorRuntimeError> {
     /// let mut main = runtime.create_dyn_model(
     ///    "main",
     ///    "src/main.sv",
@@ -507,30 +1402,55 @@ impl VerilatorRuntime {
         source_path: &str,
         ports: &[(&str, usize, usize, PortDirection)],
         config: VerilatedModelConfig,
-    ) -> Result<DynamicVerilatedModel<'ctx>, Whatever> {
-        let library = self
-            .build_or_retrieve_library(name, source_path, ports, &config)
-            .whatever_context(
-                "Failed to build or retrieve verilator dynamic library. Try removing the build directory if it is corrupted.",
-            )?;
-
-        let new_main: extern "C" fn() -> *mut ffi::c_void =
-            *unsafe { library.get(format!("ffi_new_V{name}").as_bytes()) }
-                .whatever_context(format!(
-                    "Failed to load constructor for module {name}"
-                ))?;
-        let delete_main =
-            *unsafe { library.get(format!("ffi_delete_V{name}").as_bytes()) }
-                .whatever_context(format!(
-                "Failed to load destructor for module {name}"
-            ))?;
-        let eval_main =
-            *unsafe { library.get(format!("ffi_V{name}_eval").as_bytes()) }
-                .whatever_context(format!(
-                    "Failed to load evalulator for module {name}"
-                ))?;
+    ) -> Result<DynamicVerilatedModel<'ctx>, VerilatorRuntimeError> {
+        self.create_dyn_model_with_plusargs(
+            name,
+            source_path,
+            ports,
+            config,
+            &[],
+        )
+    }
 
-        let main = new_main();
+    /// Like [`VerilatorRuntime::create_dyn_model`], but also passes `plusargs`
+    /// to the model's `VerilatedContext`. See
+    /// [`VerilatorRuntime::create_model_with_plusargs`].
+    pub fn create_dyn_model_with_plusargs<'ctx>(
+        &'ctx self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: VerilatedModelConfig,
+        plusargs: &[&str],
+    ) -> Result<DynamicVerilatedModel<'ctx>, VerilatorRuntimeError> {
+        let (library, _) =
+            self.build_or_retrieve_library(name, source_path, ports, &config)?;
+
+        let symbol = |symbol: String| SymbolNotFoundSnafu {
+            top_module: name,
+            symbol,
+        };
+        let new_main_name = format!("ffi_new_V{name}");
+        let new_main: NewModelFn =
+            *unsafe { library.get(new_main_name.as_bytes()) }
+                .context(symbol(new_main_name))?;
+        let delete_main_name = format!("ffi_delete_V{name}");
+        let delete_main = *unsafe { library.get(delete_main_name.as_bytes()) }
+            .context(symbol(delete_main_name))?;
+        let eval_main_name = format!("ffi_V{name}_eval");
+        let eval_main = *unsafe { library.get(eval_main_name.as_bytes()) }
+            .context(symbol(eval_main_name))?;
+        let context_api = ContextApi::load(library, name)
+            .context(symbol(ffi_names::top_contextp(name)))?;
+        let checkpoint_api = CheckpointApi::load(library, name);
+
+        let main =
+            new_model(new_main, &self.command_args(name, &config, plusargs)?);
+        self.model_deallocators.borrow_mut().push(ModelDeallocator {
+            model: main,
+            deallocator: delete_main,
+        });
+        self.set_up_context(name, &context_api, main)?;
 
         let ports = ports
             .iter()
@@ -546,26 +1466,312 @@ impl VerilatorRuntime {
             })
             .collect();
 
-        self.model_deallocators.borrow_mut().push(ModelDeallocator {
-            model: main,
-            deallocator: delete_main,
-        });
-
         Ok(DynamicVerilatedModel {
             ports,
             name: name.to_string(),
             main,
             eval_main,
+            context_api,
+            checkpoint_api,
+            covergroups: vec![],
             library,
         })
     }
 
+    /// Applies the options of this runtime to the context of the newly created
+    /// `model` of module `name`.
+    fn set_up_context(
+        &self,
+        name: &str,
+        context_api: &ContextApi,
+        model: *mut ffi::c_void,
+    ) -> Result<(), VerilatorRuntimeError> {
+        if self.options.capture_output {
+            new_sim_context(context_api, model).set_capture_output(true);
+        }
+        self.set_up_coverage(name, context_api, model)
+    }
+
+    /// Gives the context of `model`, if it collects coverage, a fresh file in
+    /// the coverage directory to write it to.
+    fn set_up_coverage(
+        &self,
+        name: &str,
+        context_api: &ContextApi,
+        model: *mut ffi::c_void,
+    ) -> Result<(), VerilatorRuntimeError> {
+        let mut context = new_sim_context(context_api, model);
+        if context.collects_coverage() {
+            static COVERAGE_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+            let coverage_directory = &self.coverage_directory;
+            fs::create_dir_all(coverage_directory)
+                .whatever_context::<_, Whatever>(format!(
+                    "Failed to create coverage directory {coverage_directory}"
+                ))?;
+            // unique across the models of all runtimes and processes sharing
+            // the directory, e.g., the tests run by `cargo test`
+            let path = coverage_directory.join(format!(
+                "{name}-{}-{}.dat",
+                process::id(),
+                COVERAGE_FILE_COUNT.fetch_add(1, Ordering::Relaxed)
+            ));
+            context.set_coverage_path(&path);
+        }
+
+        Ok(())
+    }
+
+    /// The directory to which models write their coverage databases, which
+    /// is [`VerilatorRuntimeOptions::coverage_directory`] if set and
+    /// otherwise a directory only this runtime writes to, e.g.,
+    /// `artifacts/coverage/1760659200000-4242-0`.
+    pub fn coverage_directory(&self) -> &Utf8Path {
+        &self.coverage_directory
+    }
+
+    /// The arguments for the `VerilatedContext` of a model `name` created with
+    /// `config` and `plusargs`.
+    fn command_args(
+        &self,
+        name: &str,
+        config: &VerilatedModelConfig,
+        plusargs: &[&str],
+    ) -> Result<Vec<String>, VerilatorRuntimeError> {
+        let mut command_args = vec![];
+
+        let randomized = config.x_assign == Some(XInit::Unique)
+            || config.x_initial == Some(XInit::Unique);
+        let seed = match self.options.seed {
+            Some(seed) if seed == 0 || seed > i32::MAX as u32 => {
+                return InvalidConfigurationSnafu {
+                    message: format!(
+                        "Seed {seed} must be positive and fit in an i32"
+                    ),
+                }
+                .fail();
+            }
+            Some(seed) => Some(seed),
+            None if randomized => {
+                let seed = (hash::RandomState::new().build_hasher().finish()
+                    % i32::MAX as u64) as u32
+                    + 1;
+                Some(seed)
+            }
+            None => None,
+        };
+        if let Some(seed) = seed {
+            command_args.push(format!("+verilator+seed+{seed}"));
+        }
+
+        // `$value$plusargs` takes the first match, but Verilator applies its
+        // `+verilator+` options in order, so the per-model arguments go after
+        // the runtime's `+verilator+` options and before the rest
+        let (verilator_options, user_plusargs): (Vec<_>, Vec<_>) = self
+            .options
+            .plusargs
+            .iter()
+            .cloned()
+            .partition(|plusarg| plusarg.starts_with("+verilator+"));
+        command_args.extend(verilator_options);
+        command_args.extend(plusargs.iter().map(|plusarg| plusarg.to_string()));
+        command_args.extend(user_plusargs);
+
+        // print a fresh seed so that the run can be reproduced, or the seed
+        // from the plusargs replacing it, since Verilator uses the last one
+        if randomized && self.options.seed.is_none() {
+            let seed = command_args
+                .iter()
+                .rev()
+                .find_map(|argument| argument.strip_prefix("+verilator+seed+"))
+                .expect("A seed was just generated");
+            if self.options.log {
+                log::info!("Randomizing module {name} with seed {seed}");
+            } else {
+                eprintln_nocapture!(
+                    "{} {name} with seed {seed}",
+                    " Randomizing".bold().green()
+                )?;
+            }
+        }
+        Ok(command_args)
+    }
+
+    /// Lists the libraries cached in this runtime's artifact directory. See
+    /// [`cache::list_cached_libraries`].
+    pub fn cached_libraries(
+        &self,
+    ) -> Result<Vec<CachedLibrary>, VerilatorRuntimeError> {
+        cache::list_cached_libraries(&self.artifact_directory)
+    }
+
+    /// Removes the libraries cached in this runtime's artifact directory
+    /// selected by `options`, except those this runtime has loaded. Returns
+    /// the removed libraries. See [`cache::prune_cached_libraries`].
+    pub fn prune_cache(
+        &self,
+        options: &PruneOptions,
+    ) -> Result<Vec<CachedLibrary>, VerilatorRuntimeError> {
+        // libraries are only built after Verilator is detected, so otherwise
+        // every loaded library is from outside the artifact directory
+        let in_use = if self.options.prebuilt_only
+            || self.verilator_info.get().is_some()
+        {
+            self.library_map
+                .borrow()
+                .keys()
+                .map(|library_key| self.library_directory_name(library_key))
+                .collect::<Result<_, _>>()?
+        } else {
+            HashSet::new()
+        };
+        Ok(cache::prune(&self.artifact_directory, options, &in_use)?)
+    }
+
+    /// Builds the library for the model [`VerilatorRuntime::create_dyn_model`]
+    /// would create with the same arguments, without instantiating it.
+    /// Returns the diagnostics Verilator emitted (if it was invoked) and the
+    /// files the build depended on.
+    ///
+    /// This is intended for building models ahead of time from a build script;
+    /// see the `marlin-build` crate.
+    pub fn build_dyn_model_library(
+        &self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Result<BuildReport, VerilatorRuntimeError> {
+        if let (_, Some(report)) =
+            self.build_or_retrieve_library(name, source_path, ports, config)?
+        {
+            return Ok(report);
+        }
+
+        // the library was already loaded by this runtime
+        let library_key = match self.model_group(name, ports, config)? {
+            Some((group, group_config)) => {
+                LibraryArenaKey::for_group(group, &group_config)
+            }
+            None => LibraryArenaKey::new(name, source_path, ports, config),
+        };
+        let library_directory = self
+            .artifact_directory
+            .join(self.library_directory_name(&library_key)?);
+        Ok(BuildReport {
+            dependencies: BuildManifest::read(&library_directory)
+                .map(|manifest| manifest.dependencies())
+                .unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+
+    /// Checks that the module `name` with signature `ports` can be built from
+    /// `source_path`.
+    fn validate_module(
+        &self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+    ) -> Result<(), VerilatorRuntimeError> {
+        ensure!(
+            !name.chars().any(|c| c == '\\' || c == ' '),
+            InvalidConfigurationSnafu {
+                message: "Escaped module names are not supported"
+            }
+        );
+
+        if self.options.log {
+            log::info!("Validating model source file");
+        }
+        if !self.source_files.iter().any(|source_file| {
+            match (
+                source_file.canonicalize_utf8(),
+                Utf8Path::new(source_path).canonicalize_utf8(),
+            ) {
+                (Ok(lhs), Ok(rhs)) => lhs == rhs,
+                _ => false,
+            }
+        }) {
+            return SourceFileNotProvidedSnafu {
+                top_module: name,
+                source_path,
+            }
+            .fail();
+        }
+
+        if let Some((port, _, _, _)) =
+            ports.iter().find(|(_, high, low, _)| high < low)
+        {
+            return InvalidConfigurationSnafu {
+                message: format!(
+                    "Port {port} on module {name} was specified with the high bit less than the low bit"
+                ),
+            }
+            .fail();
+        }
+
+        Ok(())
+    }
+
+    /// The group the module `name` is in, if any, along with the
+    /// configuration its library is built with, which is `config` without the
+    /// parameters fixed when the module was added to the group. Fails if
+    /// `ports` or `config` are not compatible with the group.
+    fn model_group(
+        &self,
+        name: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Result<
+        Option<(&ModelGroup, VerilatedModelConfig)>,
+        VerilatorRuntimeError,
+    > {
+        let Some((group, member)) = self
+            .model_groups
+            .iter()
+            .find_map(|group| group.member(name).map(|member| (group, member)))
+        else {
+            return Ok(None);
+        };
+
+        if let Some((port, _, _, _)) =
+            ports.iter().find(|port| !member.ports().contains(port))
+        {
+            return InvalidConfigurationSnafu {
+                message: format!(
+                    "Port {port} on module {name} does not match a port it was added to model group {} with",
+                    group.name
+                ),
+            }
+            .fail();
+        }
+
+        // the parameters of every member are overridden in the wrapper
+        // instead of the library being built with them
+        let mut group_config = config.clone();
+        for parameter in std::mem::take(&mut group_config.parameters) {
+            ensure!(
+                member.parameters.contains(&parameter),
+                InvalidConfigurationSnafu {
+                    message: format!(
+                        "Parameter {} on module {name} must be given when adding the module to model group {}",
+                        parameter.0, group.name
+                    )
+                }
+            );
+        }
+
+        Ok(Some((group, group_config)))
+    }
+
     /// Invokes verilator to build a dynamic library for the Verilog module
     /// named `name` defined in the file `source_path` and with signature
     /// `ports`.
     ///
     /// If the library is already cached for the given module name/source path
-    /// pair, then it is returned immediately.
+    /// pair, then it is returned immediately. If the module is in a
+    /// [`ModelGroup`], the library is that of the whole group.
     ///
     /// It is required that the `ports` signature matches a subset of the ports
     /// defined on the Verilog module exactly.
@@ -574,11 +1780,14 @@ impl VerilatorRuntime {
     /// be rebuilt. Otherwise, it is only rebuilt on (a conservative
     /// definition) of change:
     ///
-    /// - Edits to Verilog source code
+    /// - Edits to Verilog source code, including `` `include ``d files
     /// - Edits to DPI functions
+    /// - Changes to the Verilator version or to any flag passed to Verilator
     ///
     /// Then, if this is the first time building the library, and there are DPI
     /// functions, the library will be initialized with the DPI functions.
+    /// The returned [`BuildReport`] is `None` if the library was already
+    /// loaded by this runtime.
     ///
     /// See [`build_library::build_library`] for more information.
     ///
@@ -591,61 +1800,62 @@ impl VerilatorRuntime {
         source_path: &str,
         ports: &[(&str, usize, usize, PortDirection)],
         config: &VerilatedModelConfig,
-    ) -> Result<&Library, Whatever> {
-        if name.chars().any(|c| c == '\\' || c == ' ') {
-            whatever!("Escaped module names are not supported");
-        }
-
-        if self.options.log {
-            log::info!("Validating model source file");
-        }
-        if !self.source_files.iter().any(|source_file| {
-            match (
-                source_file.canonicalize_utf8(),
-                Utf8Path::new(source_path).canonicalize_utf8(),
-            ) {
-                (Ok(lhs), Ok(rhs)) => lhs == rhs,
-                _ => false,
-            }
-        }) {
-            whatever!(
-                "Module `{}` requires source file {}, which was not provided to the runtime",
-                name,
-                source_path
-            );
-        }
-
-        if let Some((port, _, _, _)) =
-            ports.iter().find(|(_, high, low, _)| high < low)
-        {
-            whatever!(
-                "Port {} on module {} was specified with the high bit less than the low bit",
-                port,
-                name
-            );
-        }
-
-        let mut hasher = hash::DefaultHasher::new();
-        ports.hash(&mut hasher);
-        config.hash(&mut hasher);
-        let library_key = LibraryArenaKey {
-            name: name.to_owned(),
-            source_path: source_path.to_owned(),
-            hash: hasher.finish(),
+    ) -> Result<(&Library, Option<BuildReport>), VerilatorRuntimeError> {
+        let model_group = self.model_group(name, ports, config)?;
+        let (library_key, config, tops) = match &model_group {
+            Some((group, group_config)) => (
+                LibraryArenaKey::for_group(group, group_config),
+                group_config,
+                LibraryTops::Group(group),
+            ),
+            None => (
+                LibraryArenaKey::new(name, source_path, ports, config),
+                config,
+                LibraryTops::Module { name, ports },
+            ),
         };
 
-        let library_idx = match self
+        let (library_idx, report) = match self
             .library_map
             .borrow_mut()
             .entry(library_key.clone())
         {
-            Entry::Occupied(entry) => *entry.get(),
+            Entry::Occupied(entry) => (*entry.get(), None),
             Entry::Vacant(entry) => {
-                let local_directory_name = format!(
-                    "{name}_{}_{}",
-                    source_path.replace("_", "__").replace("/", "_"),
-                    library_key.hash
-                );
+                if let Some(prebuilt) =
+                    self.prebuilt_libraries.iter().find(|prebuilt| {
+                        prebuilt
+                            .metadata
+                            .modules
+                            .iter()
+                            .any(|module| module.name == name)
+                    })
+                {
+                    let module = prebuilt
+                        .metadata
+                        .modules
+                        .iter()
+                        .find(|module| module.name == name)
+                        .expect("We just found the module");
+                    return PrebuiltMismatchSnafu {
+                        path: &prebuilt.path,
+                        reason: if module.ports() != ports {
+                            format!(
+                                "module {name} was built with ports {:?}, not {ports:?}",
+                                module.ports()
+                            )
+                        } else {
+                            format!(
+                                "module {name} was built with a different configuration"
+                            )
+                        },
+                    }
+                    .fail();
+                }
+                self.validate_module(name, source_path, ports)?;
+
+                let local_directory_name =
+                    self.library_directory_name(&library_key)?;
                 let local_artifacts_directory =
                     self.artifact_directory.join(&local_directory_name);
 
@@ -656,7 +1866,7 @@ impl VerilatorRuntime {
                     );
                 }
                 fs::create_dir_all(&local_artifacts_directory)
-                    .whatever_context(format!(
+                    .whatever_context::<_, Whatever>(format!(
                         "Failed to create artifacts directory {local_artifacts_directory}",
                     ))?;
 
@@ -691,9 +1901,11 @@ impl VerilatorRuntime {
                         "    Blocking".bold().green(),
                     )?;
                     let Ok(_thread_lock) = thread_mutex.lock() else {
-                        whatever!(
+                        return Err(Whatever::without_source(
                             "Failed to acquire thread-local lock for artifacts directory"
-                        );
+                                .into(),
+                        )
+                        .into());
                     };
                     _thread_lock
                 };
@@ -710,7 +1922,7 @@ impl VerilatorRuntime {
                     .create(true)
                     .truncate(true)
                     .open(self.artifact_directory.join(format!("{local_directory_name}.lock")))
-                    .whatever_context(
+                    .whatever_context::<_, Whatever>(
                         "Failed to open lockfile for artifacts directory (this is not the actual lock itself, it is an I/O error)",
                     )?;
 
@@ -720,9 +1932,9 @@ impl VerilatorRuntime {
                     0,
                     1,
                 )
-                .whatever_context(
-                    "Failed to acquire file lock for artifacts directory",
-                )?;
+                .context(LockSnafu {
+                    directory: &local_artifacts_directory,
+                })?;
                 //eprintln_nocapture!(
                 //    "lockfile for {:?} succeeded",
                 //    std::thread::current().id()
@@ -733,13 +1945,13 @@ impl VerilatorRuntime {
                 if self.options.log {
                     log::info!("Building the dynamic library with verilator");
                 }
-                let (library_path, was_rebuilt) = build_library(
+                let build_result = build_library(
                     &self.source_files,
                     &self.include_directories,
                     &self.dpi_functions,
-                    name,
-                    ports,
+                    tops,
                     &local_artifacts_directory,
+                    self.verilator_info.get(),
                     &self.options,
                     config,
                     self.options.log,
@@ -747,38 +1959,110 @@ impl VerilatorRuntime {
                         eprintln_nocapture!(
                             "{} {}#{} ({})",
                             "   Compiling".bold().green(),
-                            name,
+                            library_key.name,
                             library_key.hash,
-                            source_path
+                            match tops {
+                                LibraryTops::Module { .. } => source_path,
+                                LibraryTops::Group(_) => "model group",
+                            }
                         )
                     },
-                )
-                .whatever_context(
-                    "Failed to build verilator dynamic library",
-                )?;
+                );
+                let diagnostics = match &build_result {
+                    Ok((_, report)) => report.diagnostics.as_slice(),
+                    Err(VerilatorRuntimeError::VerilatorFailed {
+                        diagnostics,
+                        ..
+                    }) => diagnostics.as_slice(),
+                    Err(_) => &[],
+                };
+                for diagnostic in diagnostics {
+                    eprintln_nocapture!("{}", diagnostic.render())?;
+                }
+                let (library_path, report) = build_result?;
+
+                match tops {
+                    LibraryTops::Module { name, ports } => cache::record_use(
+                        &local_artifacts_directory,
+                        name,
+                        source_path,
+                        ports,
+                        config,
+                    )?,
+                    // pruned as unreferenced once the source of the first
+                    // member is gone
+                    LibraryTops::Group(group) => cache::record_use(
+                        &local_artifacts_directory,
+                        &group.name,
+                        &group.members[0].source_path,
+                        &[],
+                        config,
+                    )?,
+                }
 
                 if self.options.log {
                     log::info!("Opening the dynamic library");
                 }
-                let library = unsafe { Library::new(library_path) }
-                    .whatever_context(
-                        "Failed to load verilator dynamic library",
-                    )?;
+                let library = unsafe { Library::new(&library_path) }.context(
+                    LibraryLoadSnafu {
+                        path: &library_path,
+                    },
+                )?;
 
+                let checkpoint_tag = if config.savable {
+                    let manifest =
+                        BuildManifest::read(&local_artifacts_directory)
+                            .whatever_context::<_, Whatever>(format!(
+                                "Failed to read the build manifest of savable module {name}"
+                            ))?;
+                    Some(manifest.hash())
+                } else {
+                    None
+                };
                 one_time_library_setup(
                     &library,
                     &self.dpi_functions,
                     config.enable_tracing,
+                    checkpoint_tag,
                     &self.options,
                 )?;
 
-                let library_idx = self.library_arena.push(library);
+                // so that the library can be shipped and loaded elsewhere
+                LibraryMetadata {
+                    marlin_version: env!("CARGO_PKG_VERSION").to_string(),
+                    hash: library_key.hash,
+                    tracing: config.enable_tracing,
+                    checkpoint_tag,
+                    group: match tops {
+                        LibraryTops::Module { .. } => None,
+                        LibraryTops::Group(group) => Some(group.name.clone()),
+                    },
+                    modules: match tops {
+                        LibraryTops::Module { name, ports } => {
+                            vec![GroupMember::new(
+                                name,
+                                &library_key.source_path,
+                                ports,
+                                &[],
+                            )]
+                        }
+                        LibraryTops::Group(group) => {
+                            group.project_relative().members
+                        }
+                    },
+                }
+                .write(&prebuilt::metadata_path(&library_path))?;
+
+                let library_idx = self.library_arena.push(LoadedLibrary {
+                    library,
+                    tracing_enabled: config.enable_tracing,
+                });
                 entry.insert(library_idx);
 
                 let end = Instant::now();
                 let duration = end - start;
 
-                if was_rebuilt {
+                if report.rebuilt {
                     eprintln_nocapture!(
                         "{} `verilator-{}` profile [{}] target in {}.{:02}s",
                         "    Finished".bold().green(),
@@ -797,13 +2081,17 @@ impl VerilatorRuntime {
                     )?;
                 }
 
-                library_idx
+                (library_idx, Some(report))
             }
         };
 
-        Ok(self
-            .library_arena
-            .get(library_idx)
-            .expect("bug: We just inserted the library"))
+        Ok((
+            &self
+                .library_arena
+                .get(library_idx)
+                .expect("bug: We just inserted the library")
+                .library,
+            report,
+        ))
     }
 }
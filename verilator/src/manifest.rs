@@ -0,0 +1,232 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Content-hash manifests for deciding whether a verilated library is stale.
+//!
+//! After a successful build, we record in the artifact directory:
+//!
+//! - the output of `verilator --version`,
+//! - a hash of the full Verilator invocation (every flag and input), and
+//! - a hash of the contents of every file the build depended on, taken from
+//!   the dependency file Verilator generates (which includes `` `include ``d
+//!   headers) plus the generated FFI and DPI wrappers.
+//!
+//! A library is reused only if all of these still match.
+
+use std::{
+    fmt::Write,
+    fs,
+    hash::{Hash, Hasher},
+    process::Command,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use snafu::{ResultExt, Whatever};
+
+use crate::stable_hash::StableHasher;
+
+const MANIFEST_FILE_NAME: &str = "marlin-manifest.txt";
+
+#[derive(Debug)]
+pub struct BuildManifest {
+    verilator_version: String,
+    command_hash: u64,
+    file_hashes: Vec<(Utf8PathBuf, u64)>,
+}
+
+fn hash_file(path: &Utf8Path) -> Result<u64, Whatever> {
+    let contents = fs::read(path).whatever_context::<_, Whatever>(format!(
+        "Failed to read build dependency {path}"
+    ))?;
+    let mut hasher = StableHasher::new();
+    hasher.write(&contents);
+    Ok(hasher.finish())
+}
+
+/// Hashes the program and arguments of `command`.
+pub fn hash_command(command: &Command) -> u64 {
+    let mut hasher = StableHasher::new();
+    command.get_program().hash(&mut hasher);
+    for argument in command.get_args() {
+        argument.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Reads the files listed as prerequisites in the Make-style dependency file
+/// at `path`.
+fn read_dependency_file(path: &Utf8Path) -> Result<Vec<Utf8PathBuf>, Whatever> {
+    let contents = fs::read_to_string(path).whatever_context::<_, Whatever>(
+        format!("Failed to read Verilator dependency file {path}"),
+    )?;
+
+    let mut dependencies = vec![];
+    for rule in contents.replace("\\\n", " ").lines() {
+        let Some((_, prerequisites)) = rule.split_once(": ") else {
+            continue;
+        };
+        let mut current = String::new();
+        let mut escaped = false;
+        for c in prerequisites.chars() {
+            if escaped {
+                current.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c.is_whitespace() {
+                if !current.is_empty() {
+                    dependencies.push(Utf8PathBuf::from(&current));
+                    current.clear();
+                }
+            } else {
+                current.push(c);
+            }
+        }
+        if !current.is_empty() {
+            dependencies.push(Utf8PathBuf::from(current));
+        }
+    }
+    Ok(dependencies)
+}
+
+impl BuildManifest {
+    /// Creates a manifest for a build that just completed, hashing the
+    /// prerequisites listed in `dependency_file` along with
+    /// `extra_dependencies`.
+    pub fn record(
+        verilator_version: String,
+        command_hash: u64,
+        dependency_file: &Utf8Path,
+        extra_dependencies: &[Utf8PathBuf],
+    ) -> Result<Self, Whatever> {
+        let mut dependencies = read_dependency_file(dependency_file)?;
+        dependencies.extend_from_slice(extra_dependencies);
+
+        let mut file_hashes = vec![];
+        for dependency in dependencies {
+            let dependency =
+                dependency.canonicalize_utf8().unwrap_or(dependency);
+            if file_hashes.iter().any(|(path, _)| path == &dependency) {
+                continue;
+            }
+            let hash = hash_file(&dependency)?;
+            file_hashes.push((dependency, hash));
+        }
+
+        Ok(Self {
+            verilator_version,
+            command_hash,
+            file_hashes,
+        })
+    }
+
+    /// Reads the manifest stored in `artifact_directory`, if there is a valid
+    /// one.
+    pub fn read(artifact_directory: &Utf8Path) -> Option<Self> {
+        let contents =
+            fs::read_to_string(artifact_directory.join(MANIFEST_FILE_NAME))
+                .ok()?;
+        let mut lines = contents.lines();
+
+        let verilator_version =
+            lines.next()?.strip_prefix("verilator ")?.to_string();
+        let command_hash =
+            u64::from_str_radix(lines.next()?.strip_prefix("command ")?, 16)
+                .ok()?;
+        let mut file_hashes = vec![];
+        for line in lines {
+            let (hash, path) = line.strip_prefix("file ")?.split_once(' ')?;
+            file_hashes.push((
+                Utf8PathBuf::from(path),
+                u64::from_str_radix(hash, 16).ok()?,
+            ));
+        }
+
+        Some(Self {
+            verilator_version,
+            command_hash,
+            file_hashes,
+        })
+    }
+
+    /// A hash identifying the build recorded by this manifest.
+    pub fn hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        self.verilator_version.hash(&mut hasher);
+        self.command_hash.hash(&mut hasher);
+        self.file_hashes.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The files the build depended on.
+    pub fn dependencies(&self) -> Vec<Utf8PathBuf> {
+        self.file_hashes
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Writes this manifest to `artifact_directory`.
+    pub fn write(&self, artifact_directory: &Utf8Path) -> Result<(), Whatever> {
+        let mut buffer = String::new();
+        writeln!(&mut buffer, "verilator {}", self.verilator_version).unwrap();
+        writeln!(&mut buffer, "command {:016x}", self.command_hash).unwrap();
+        for (path, hash) in &self.file_hashes {
+            writeln!(&mut buffer, "file {hash:016x} {path}").unwrap();
+        }
+
+        let manifest_path = artifact_directory.join(MANIFEST_FILE_NAME);
+        fs::write(&manifest_path, buffer).whatever_context(format!(
+            "Failed to write build manifest {manifest_path}"
+        ))
+    }
+
+    /// Removes the manifest in `artifact_directory` so that an interrupted or
+    /// failed build is never mistaken for an up-to-date one.
+    pub fn invalidate(artifact_directory: &Utf8Path) -> Result<(), Whatever> {
+        let manifest_path = artifact_directory.join(MANIFEST_FILE_NAME);
+        match fs::remove_file(&manifest_path) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                Err(error).whatever_context(format!(
+                    "Failed to remove build manifest {manifest_path}"
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns a description of why the library built under this manifest is
+    /// out of date, or `None` if it is up to date.
+    pub fn staleness(
+        &self,
+        verilator_version: &str,
+        command_hash: u64,
+    ) -> Option<String> {
+        if self.verilator_version != verilator_version {
+            return Some(format!(
+                "Verilator version changed from `{}` to `{}`",
+                self.verilator_version, verilator_version
+            ));
+        }
+        if self.command_hash != command_hash {
+            return Some("Verilator flags or inputs changed".into());
+        }
+        self.changed_dependency()
+    }
+
+    /// Returns a description of the first file the build depended on that has
+    /// changed since, or `None` if there is no such file.
+    pub fn changed_dependency(&self) -> Option<String> {
+        for (path, hash) in &self.file_hashes {
+            match hash_file(path) {
+                Ok(current_hash) if current_hash == *hash => {}
+                Ok(_) => return Some(format!("{path} changed")),
+                Err(_) => return Some(format!("{path} could not be read")),
+            }
+        }
+        None
+    }
+}
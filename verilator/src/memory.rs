@@ -0,0 +1,206 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Backdoor access to the memories of a design, i.e., unpacked arrays like
+//! `reg[31:0] ram[0:1023]`, for models built with
+//! [`crate::VerilatedModelConfig::public_signals`].
+//!
+//! A memory is read and written a whole word at a time with
+//! [`crate::SimContext::read_memory`] and
+//! [`crate::SimContext::write_memory`], without simulating any cycles. Its
+//! contents can also be loaded from and dumped to files in one of the
+//! [`MemoryFormat`]s with [`crate::SimContext::load_memory`] and
+//! [`crate::SimContext::dump_memory`].
+
+use std::fmt::Write;
+
+use snafu::{Whatever, whatever};
+
+/// The shape of a memory, obtained with [`crate::SimContext::memory_layout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MemoryLayout {
+    /// The width of each word in bits.
+    pub width: usize,
+    /// The lowest address of the memory, e.g., `0` for `ram[0:1023]` or
+    /// `ram[1023:0]`.
+    pub low: i64,
+    /// The highest address of the memory.
+    pub high: i64,
+}
+
+impl MemoryLayout {
+    /// The number of words in the memory.
+    pub fn depth(&self) -> usize {
+        (self.high - self.low + 1) as usize
+    }
+
+    /// Whether `address` is in the memory.
+    pub fn contains(&self, address: i64) -> bool {
+        (self.low..=self.high).contains(&address)
+    }
+}
+
+/// The format of a memory file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryFormat {
+    /// Whitespace-separated hexadecimal words, as read by `$readmemh`. This
+    /// is the usual format of `.hex` and `.mem` files. `@address` directives,
+    /// `_` separators, and `//` and `/* */` comments are allowed.
+    Hex,
+    /// Whitespace-separated binary words, as read by `$readmemb`, with the
+    /// same syntax as [`MemoryFormat::Hex`].
+    Binary,
+    /// Raw bytes, e.g., a firmware image produced by `objcopy -O binary`.
+    /// Each word takes the fewest bytes that fit its width and is stored
+    /// little-endian, starting at the lowest address.
+    Raw,
+}
+
+/// Parses the contents of a memory file into runs of words, each starting at
+/// the given address.
+pub(crate) fn parse(
+    contents: &[u8],
+    format: MemoryFormat,
+    layout: &MemoryLayout,
+) -> Result<Vec<(i64, Vec<u64>)>, Whatever> {
+    let radix = match format {
+        MemoryFormat::Hex => 16,
+        MemoryFormat::Binary => 2,
+        MemoryFormat::Raw => return parse_raw(contents, layout),
+    };
+    let Ok(text) = std::str::from_utf8(contents) else {
+        whatever!("Memory file is not valid UTF-8");
+    };
+
+    // like $readmemh, start at the lowest address unless told otherwise
+    let mut runs = vec![(layout.low, vec![])];
+    for (index, line) in strip_comments(text).lines().enumerate() {
+        for token in line.split_whitespace() {
+            if let Some(address) = token.strip_prefix('@') {
+                let Ok(address) = i64::from_str_radix(address, 16) else {
+                    whatever!(
+                        "Invalid address on memory file line {}: {token}",
+                        index + 1
+                    );
+                };
+                runs.push((address, vec![]));
+                continue;
+            }
+            // two-state simulation has no X or Z, so they are loaded as 0
+            let digits = token
+                .chars()
+                .filter(|digit| *digit != '_')
+                .map(|digit| match digit {
+                    'x' | 'X' | 'z' | 'Z' => '0',
+                    digit => digit,
+                })
+                .collect::<String>();
+            let Ok(word) = u64::from_str_radix(&digits, radix) else {
+                whatever!(
+                    "Invalid word on memory file line {}: {token}",
+                    index + 1
+                );
+            };
+            if layout.width < 64 && word >> layout.width != 0 {
+                whatever!(
+                    "Word on memory file line {} does not fit in {} bits: {token}",
+                    index + 1,
+                    layout.width
+                );
+            }
+            runs.last_mut().unwrap().1.push(word);
+        }
+    }
+    runs.retain(|(_, words)| !words.is_empty());
+    Ok(runs)
+}
+
+/// Replaces the `//` and `/* */` comments in `text` with spaces, keeping line
+/// breaks so that line numbers are unchanged.
+fn strip_comments(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                stripped.push(' ');
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push('\n');
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                stripped.push(' ');
+            }
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+fn parse_raw(
+    contents: &[u8],
+    layout: &MemoryLayout,
+) -> Result<Vec<(i64, Vec<u64>)>, Whatever> {
+    let word_bytes = layout.width.div_ceil(8);
+    let chunks = contents.chunks_exact(word_bytes);
+    if !chunks.remainder().is_empty() {
+        whatever!(
+            "Raw memory file of {} bytes is not a whole number of {word_bytes}-byte words",
+            contents.len()
+        );
+    }
+    let words = chunks
+        .map(|chunk| {
+            let mut bytes = [0; 8];
+            bytes[..word_bytes].copy_from_slice(chunk);
+            u64::from_le_bytes(bytes)
+        })
+        .collect();
+    Ok(vec![(layout.low, words)])
+}
+
+/// Formats the words of a memory, starting at its lowest address, as the
+/// contents of a memory file.
+pub(crate) fn format(
+    words: &[u64],
+    format: MemoryFormat,
+    layout: &MemoryLayout,
+) -> Vec<u8> {
+    let width = layout.width;
+    let digits = match format {
+        MemoryFormat::Hex => width.div_ceil(4),
+        MemoryFormat::Binary => width,
+        MemoryFormat::Raw => {
+            let word_bytes = width.div_ceil(8);
+            return words
+                .iter()
+                .flat_map(|word| word.to_le_bytes()[..word_bytes].to_vec())
+                .collect();
+        }
+    };
+
+    let mut buffer = String::new();
+    if layout.low != 0 {
+        writeln!(&mut buffer, "@{:x}", layout.low).unwrap();
+    }
+    for word in words {
+        match format {
+            MemoryFormat::Hex => writeln!(&mut buffer, "{word:0digits$x}"),
+            _ => writeln!(&mut buffer, "{word:0digits$b}"),
+        }
+        .unwrap();
+    }
+    buffer.into_bytes()
+}
@@ -24,10 +24,12 @@ pub fn eprintln_nocapture_impl(contents: &str) -> Result<(), Whatever> {
     let mut stderr = STDERR.lock().expect("poisoned");
     stderr
         .write_all(contents.as_bytes())
-        .whatever_context("Failed to write to non-captured stderr")?;
-    stderr
-        .write_all(b"\n")
-        .whatever_context("Failed to write to non-captured stderr")?;
+        .whatever_context::<_, Whatever>(
+            "Failed to write to non-captured stderr",
+        )?;
+    stderr.write_all(b"\n").whatever_context::<_, Whatever>(
+        "Failed to write to non-captured stderr",
+    )?;
     Ok(())
 }
 
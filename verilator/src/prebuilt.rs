@@ -0,0 +1,193 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Metadata describing what a library was built for, written next to every
+//! library so that it can be copied to a machine without Verilator and loaded
+//! there with [`crate::VerilatorRuntime::load_prebuilt`].
+//!
+//! The metadata is a text file with one field per line:
+//!
+//! ```text
+//! marlin 0.11.1
+//! hash 1234567890
+//! tracing false
+//! module main
+//! source src/main.sv
+//! port medium_input 31 0 input
+//! port medium_output 31 0 output
+//! ```
+//!
+//! The `hash` covers the ports and configuration of the library, like the
+//! name of its directory in the artifact directory. It is computed with a
+//! fixed hash function and with paths inside the project directory taken
+//! relative to it, as are the `source` paths, so that it does not depend on
+//! the machine or checkout the library was built on. A library built with
+//! [`crate::VerilatedModelConfig::savable`] also has a `checkpoint` tag, and
+//! the library of a [`crate::ModelGroup`] has a `group` name and a `module`
+//! (with its `source`, `parameter`s, and `port`s) for each member.
+
+use std::fs;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use snafu::{ResultExt, Whatever, whatever};
+
+use crate::{PortDirection, group::GroupMember};
+
+/// What a library was built for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LibraryMetadata {
+    /// The version of Marlin that built the library.
+    pub(crate) marlin_version: String,
+    /// The hash of the key of the library.
+    pub(crate) hash: u64,
+    pub(crate) tracing: bool,
+    /// Identifies the build of the library in its checkpoints, if it is
+    /// savable.
+    pub(crate) checkpoint_tag: Option<u64>,
+    /// The name of the model group the library was built for, if any.
+    pub(crate) group: Option<String>,
+    /// The top module, or the members of the group.
+    pub(crate) modules: Vec<GroupMember>,
+}
+
+/// The path of the metadata of the library at `library_path`.
+pub(crate) fn metadata_path(library_path: &Utf8Path) -> Utf8PathBuf {
+    format!("{library_path}.metadata").into()
+}
+
+impl LibraryMetadata {
+    pub(crate) fn write(&self, path: &Utf8Path) -> Result<(), Whatever> {
+        let mut contents = format!(
+            "marlin {}\nhash {}\ntracing {}\n",
+            self.marlin_version, self.hash, self.tracing
+        );
+        if let Some(checkpoint_tag) = self.checkpoint_tag {
+            contents += &format!("checkpoint {checkpoint_tag}\n");
+        }
+        if let Some(group) = &self.group {
+            contents += &format!("group {group}\n");
+        }
+        for module in &self.modules {
+            contents += &format!(
+                "module {}\nsource {}\n",
+                module.name, module.source_path
+            );
+            for (parameter, value) in &module.parameters {
+                contents += &format!("parameter {parameter} {value}\n");
+            }
+            for (port, high, low, direction) in &module.ports {
+                let direction = match direction {
+                    PortDirection::Input => "input",
+                    PortDirection::Output => "output",
+                    PortDirection::Inout => "inout",
+                };
+                contents += &format!("port {port} {high} {low} {direction}\n");
+            }
+        }
+
+        fs::write(path, contents).whatever_context(format!(
+            "Failed to write library metadata {path}"
+        ))
+    }
+
+    pub(crate) fn read(path: &Utf8Path) -> Result<Self, Whatever> {
+        let contents = fs::read_to_string(path)
+            .whatever_context::<_, Whatever>(format!(
+                "Failed to read library metadata {path}"
+            ))?;
+
+        let mut marlin_version = None;
+        let mut hash = None;
+        let mut tracing = false;
+        let mut checkpoint_tag = None;
+        let mut group = None;
+        let mut modules: Vec<GroupMember> = vec![];
+        for (index, line) in contents.lines().enumerate() {
+            let line_number = index + 1;
+            let Some((field, value)) = line.split_once(' ') else {
+                whatever!("Invalid line {line_number} of {path}: {line}");
+            };
+            let parse_number = |value: &str| {
+                value
+                    .parse::<u64>()
+                    .whatever_context::<_, Whatever>(format!(
+                        "Invalid number on line {line_number} of {path}"
+                    ))
+            };
+            match field {
+                "marlin" => marlin_version = Some(value.to_string()),
+                "hash" => hash = Some(parse_number(value)?),
+                "tracing" => tracing = value == "true",
+                "checkpoint" => checkpoint_tag = Some(parse_number(value)?),
+                "group" => group = Some(value.to_string()),
+                "module" => modules.push(GroupMember::new(value, "", &[], &[])),
+                _ => {
+                    let Some(module) = modules.last_mut() else {
+                        whatever!(
+                            "Line {line_number} of {path} is not part of a module: {line}"
+                        );
+                    };
+                    match field {
+                        "source" => module.source_path = value.to_string(),
+                        "parameter" => {
+                            let Some((parameter, value)) =
+                                value.split_once(' ')
+                            else {
+                                whatever!(
+                                    "Invalid parameter on line {line_number} of {path}: {line}"
+                                );
+                            };
+                            module.parameters.push((
+                                parameter.to_string(),
+                                value.to_string(),
+                            ));
+                        }
+                        "port" => {
+                            let parts = value.split(' ').collect::<Vec<_>>();
+                            let [port, high, low, direction] = parts[..] else {
+                                whatever!(
+                                    "Invalid port on line {line_number} of {path}: {line}"
+                                );
+                            };
+                            let direction = match direction {
+                                "input" => PortDirection::Input,
+                                "output" => PortDirection::Output,
+                                "inout" => PortDirection::Inout,
+                                _ => whatever!(
+                                    "Invalid port direction on line {line_number} of {path}: {direction}"
+                                ),
+                            };
+                            module.ports.push((
+                                port.to_string(),
+                                parse_number(high)? as usize,
+                                parse_number(low)? as usize,
+                                direction,
+                            ));
+                        }
+                        _ => whatever!(
+                            "Unknown field on line {line_number} of {path}: {field}"
+                        ),
+                    }
+                }
+            }
+        }
+
+        let (Some(marlin_version), Some(hash)) = (marlin_version, hash) else {
+            whatever!("Library metadata {path} is missing its version or hash");
+        };
+        if modules.is_empty() {
+            whatever!("Library metadata {path} lists no modules");
+        }
+        Ok(Self {
+            marlin_version,
+            hash,
+            tracing,
+            checkpoint_tag,
+            group,
+            modules,
+        })
+    }
+}
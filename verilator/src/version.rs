@@ -0,0 +1,130 @@
+// Copyright (C) 2026 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Detecting the version of Verilator, so that models are built with the flags
+//! it supports and an unsupported version is reported up front instead of
+//! through an opaque build failure.
+
+use std::{ffi::OsStr, fmt, process::Command};
+
+use snafu::ResultExt;
+
+use crate::{
+    VerilatorRuntimeError,
+    error::{
+        UnsupportedVerilatorSnafu, VerilatorNotFoundSnafu,
+        VerilatorVersionFailedSnafu,
+    },
+};
+
+/// A release of Verilator, e.g., 5.030.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VerilatorVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl VerilatorVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl fmt::Display for VerilatorVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:03}", self.major, self.minor)
+    }
+}
+
+/// The oldest version of Verilator that Marlin supports.
+pub const MINIMUM_VERILATOR_VERSION: VerilatorVersion =
+    VerilatorVersion::new(5, 25);
+
+/// The first version of Verilator with `--trace-vcd`, which replaces
+/// `--trace`.
+const TRACE_VCD_VERSION: VerilatorVersion = VerilatorVersion::new(5, 36);
+
+/// What a runtime knows about the Verilator it invokes. See
+/// [`crate::VerilatorRuntime::verilator_info`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VerilatorInfo {
+    /// The output of `verilator --version` with its whitespace normalized,
+    /// e.g., `Verilator 5.030 2024-10-27 rev v5.030`.
+    pub version_string: String,
+    pub version: VerilatorVersion,
+}
+
+impl VerilatorInfo {
+    /// Runs `verilator_executable --version`, failing if Verilator cannot be
+    /// run or is older than [`MINIMUM_VERILATOR_VERSION`].
+    pub fn detect(
+        verilator_executable: &OsStr,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        let output = Command::new(verilator_executable)
+            .arg("--version")
+            .output()
+            .context(VerilatorNotFoundSnafu {
+                executable: verilator_executable.to_string_lossy(),
+            })?;
+        if !output.status.success() {
+            return VerilatorVersionFailedSnafu {
+                executable: verilator_executable.to_string_lossy(),
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr),
+            }
+            .fail();
+        }
+        Self::from_version_string(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Parses the output of `verilator --version`, failing if it is not from
+    /// a supported version of Verilator.
+    pub fn from_version_string(
+        version_string: &str,
+    ) -> Result<Self, VerilatorRuntimeError> {
+        let version_string = version_string
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // e.g., "Verilator 5.030 2024-10-27 rev v5.030"
+        let version = version_string
+            .strip_prefix("Verilator ")
+            .and_then(|rest| rest.split(' ').next())
+            .and_then(|release| release.split_once('.'))
+            .and_then(|(major, minor)| {
+                Some(VerilatorVersion::new(
+                    major.parse().ok()?,
+                    minor.parse().ok()?,
+                ))
+            });
+        let Some(version) =
+            version.filter(|version| *version >= MINIMUM_VERILATOR_VERSION)
+        else {
+            return UnsupportedVerilatorSnafu {
+                version: version
+                    .map_or(version_string, |version| version.to_string()),
+                minimum: MINIMUM_VERILATOR_VERSION,
+            }
+            .fail();
+        };
+
+        Ok(Self {
+            version_string,
+            version,
+        })
+    }
+
+    /// The flag enabling VCD tracing: `--trace-vcd` if supported, and
+    /// otherwise `--trace`.
+    pub fn trace_flag(&self) -> &'static str {
+        if self.version >= TRACE_VCD_VERSION {
+            "--trace-vcd"
+        } else {
+            "--trace"
+        }
+    }
+}